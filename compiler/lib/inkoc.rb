@@ -96,6 +96,7 @@ require 'inkoc/tir/basic_block'
 require 'inkoc/tir/instruction/predicates'
 require 'inkoc/tir/instruction/binary'
 require 'inkoc/tir/instruction/ternary'
+require 'inkoc/tir/instruction/quaternary'
 require 'inkoc/tir/instruction/copy_blocks'
 require 'inkoc/tir/instruction/drop'
 require 'inkoc/tir/instruction/unary'
@@ -145,6 +145,61 @@ module Inkoc
         ByteArrayEquals
         ByteArrayToString
         GetBooleanPrototype
+        EnvGet
+        EnvSet
+        EnvRemove
+        EnvVariables
+        EnvArguments
+        EnvGetWorkingDirectory
+        EnvSetWorkingDirectory
+        ProcessMonitor
+        ProcessDemonitor
+        ProcessLink
+        ProcessUnlink
+        ProcessTrapExits
+        ProcessTerminate
+        ProcessRegister
+        ProcessUnregister
+        ProcessWhereis
+        ProcessRegisteredNames
+        ProcessReceiveMessageIf
+        ProcessSetMailboxCapacity
+        ProcessMailboxLength
+        ReferenceNew
+        ReferenceEquals
+        TimerSchedule
+        TimerCancel
+        TcpListen
+        TcpConnect
+        SocketAccept
+        SocketFinishConnect
+        SocketRead
+        SocketWrite
+        SocketShutdown
+        SocketLocalAddress
+        SocketPeerAddress
+        UdpBind
+        UnixListen
+        UnixConnect
+        UnixDatagramBind
+        UnixPair
+        SocketConnect
+        SocketSendTo
+        SocketReceiveFrom
+        SocketSetOption
+        SocketGetOption
+        SocketJoinMulticast
+        SocketLeaveMulticast
+        ChildSpawn
+        ChildWrite
+        ChildRead
+        ChildCloseInput
+        ChildWait
+        ChildExitCode
+        ChildExitSignal
+        ChildKill
+        SignalSubscribe
+        SignalUnsubscribe
       ]
         .each_with_index
         .each_with_object({}) { |(value, index), hash| hash[value] = index }
@@ -246,6 +246,17 @@ module Inkoc
           .instruct(tir_ins.name, [reg, one, two, three], tir_ins.location)
       end
 
+      def on_quaternary(tir_ins, compiled_code, *)
+        reg = tir_ins.register.id
+        one = tir_ins.one.id
+        two = tir_ins.two.id
+        three = tir_ins.three.id
+        four = tir_ins.four.id
+        operands = [reg, one, two, three, four]
+
+        compiled_code.instruct(tir_ins.name, operands, tir_ins.location)
+      end
+
       def on_process_suspend_current(tir_ins, compiled_code, *)
         timeout = tir_ins.timeout.id
 
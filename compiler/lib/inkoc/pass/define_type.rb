@@ -1585,6 +1585,226 @@ module Inkoc
         typedb.boolean_type.new_instance
       end
 
+      def on_raw_env_get(*)
+        TypeSystem::Optional.new(typedb.string_type.new_instance)
+      end
+
+      def on_raw_env_set(*)
+        typedb.string_type.new_instance
+      end
+
+      def on_raw_env_remove(*)
+        typedb.nil_type.new_instance
+      end
+
+      def on_raw_env_variables(*)
+        typedb.new_array_of_type(typedb.string_type.new_instance)
+      end
+
+      def on_raw_env_arguments(*)
+        typedb.new_array_of_type(typedb.string_type.new_instance)
+      end
+
+      def on_raw_env_get_working_directory(*)
+        typedb.string_type.new_instance
+      end
+
+      def on_raw_env_set_working_directory(*)
+        typedb.string_type.new_instance
+      end
+
+      def on_raw_process_monitor(*)
+        typedb.boolean_type.new_instance
+      end
+
+      def on_raw_process_demonitor(*)
+        typedb.nil_type.new_instance
+      end
+
+      def on_raw_process_link(*)
+        typedb.boolean_type.new_instance
+      end
+
+      def on_raw_process_unlink(*)
+        typedb.nil_type.new_instance
+      end
+
+      def on_raw_process_trap_exits(*)
+        typedb.boolean_type.new_instance
+      end
+
+      def on_raw_process_terminate(*)
+        typedb.boolean_type.new_instance
+      end
+
+      def on_raw_process_register(*)
+        typedb.boolean_type.new_instance
+      end
+
+      def on_raw_process_unregister(*)
+        typedb.boolean_type.new_instance
+      end
+
+      def on_raw_process_whereis(*)
+        TypeSystem::Optional.new(typedb.integer_type.new_instance)
+      end
+
+      def on_raw_process_registered_names(*)
+        typedb.new_array_of_type(typedb.string_type.new_instance)
+      end
+
+      def on_raw_process_receive_message_if(*)
+        TypeSystem::Dynamic.new
+      end
+
+      def on_raw_process_set_mailbox_capacity(*)
+        typedb.boolean_type.new_instance
+      end
+
+      def on_raw_process_mailbox_length(*)
+        typedb.integer_type.new_instance
+      end
+
+      def on_raw_reference_new(*)
+        TypeSystem::Dynamic.new
+      end
+
+      def on_raw_reference_equals(*)
+        typedb.boolean_type.new_instance
+      end
+
+      def on_raw_timer_schedule(*)
+        TypeSystem::Optional.new(typedb.integer_type.new_instance)
+      end
+
+      def on_raw_timer_cancel(*)
+        typedb.boolean_type.new_instance
+      end
+
+      def on_raw_tcp_listen(*)
+        TypeSystem::Dynamic.new
+      end
+
+      def on_raw_tcp_connect(*)
+        TypeSystem::Dynamic.new
+      end
+
+      def on_raw_socket_accept(*)
+        TypeSystem::Dynamic.new
+      end
+
+      def on_raw_socket_finish_connect(*)
+        typedb.nil_type.new_instance
+      end
+
+      def on_raw_socket_read(*)
+        typedb.integer_type.new_instance
+      end
+
+      def on_raw_socket_write(*)
+        typedb.integer_type.new_instance
+      end
+
+      def on_raw_socket_shutdown(*)
+        typedb.nil_type.new_instance
+      end
+
+      def on_raw_socket_local_address(*)
+        typedb.string_type.new_instance
+      end
+
+      def on_raw_socket_peer_address(*)
+        typedb.string_type.new_instance
+      end
+
+      def on_raw_udp_bind(*)
+        TypeSystem::Dynamic.new
+      end
+
+      def on_raw_unix_listen(*)
+        TypeSystem::Dynamic.new
+      end
+
+      def on_raw_unix_connect(*)
+        TypeSystem::Dynamic.new
+      end
+
+      def on_raw_unix_datagram_bind(*)
+        TypeSystem::Dynamic.new
+      end
+
+      def on_raw_unix_pair(*)
+        typedb.new_array_of_type(TypeSystem::Dynamic.new)
+      end
+
+      def on_raw_socket_connect(*)
+        typedb.nil_type.new_instance
+      end
+
+      def on_raw_socket_send_to(*)
+        typedb.integer_type.new_instance
+      end
+
+      def on_raw_socket_receive_from(*)
+        typedb.string_type.new_instance
+      end
+
+      def on_raw_socket_set_option(*)
+        typedb.nil_type.new_instance
+      end
+
+      def on_raw_socket_get_option(*)
+        TypeSystem::Dynamic.new
+      end
+
+      def on_raw_socket_join_multicast(*)
+        typedb.nil_type.new_instance
+      end
+
+      def on_raw_socket_leave_multicast(*)
+        typedb.nil_type.new_instance
+      end
+
+      def on_raw_child_spawn(*)
+        TypeSystem::Dynamic.new
+      end
+
+      def on_raw_child_write(*)
+        typedb.integer_type.new_instance
+      end
+
+      def on_raw_child_read(*)
+        typedb.integer_type.new_instance
+      end
+
+      def on_raw_child_close_input(*)
+        typedb.nil_type.new_instance
+      end
+
+      def on_raw_child_wait(*)
+        typedb.nil_type.new_instance
+      end
+
+      def on_raw_child_exit_code(*)
+        TypeSystem::Optional.new(typedb.integer_type.new_instance)
+      end
+
+      def on_raw_child_exit_signal(*)
+        TypeSystem::Optional.new(typedb.integer_type.new_instance)
+      end
+
+      def on_raw_child_kill(*)
+        typedb.nil_type.new_instance
+      end
+
+      def on_raw_signal_subscribe(*)
+        typedb.nil_type.new_instance
+      end
+
+      def on_raw_signal_unsubscribe(*)
+        typedb.nil_type.new_instance
+      end
+
       def define_block_signature(node, scope, expected_block = nil)
         define_type_parameters(node, scope)
         define_argument_types(node, scope, expected_block)
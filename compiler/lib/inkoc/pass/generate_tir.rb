@@ -768,6 +768,25 @@ module Inkoc
         body.instruct(:Ternary, name, register, one, two, three, node.location)
       end
 
+      def raw_quaternary_instruction(name, node, body)
+        register = body.register(node.type)
+        one = process_node(node.arguments.fetch(0), body)
+        two = process_node(node.arguments.fetch(1), body)
+        three = process_node(node.arguments.fetch(2), body)
+        four = process_node(node.arguments.fetch(3), body)
+
+        body.instruct(
+          :Quaternary,
+          name,
+          register,
+          one,
+          two,
+          three,
+          four,
+          node.location
+        )
+      end
+
       def on_raw_get_toplevel(node, body)
         get_toplevel(body, node.location)
       end
@@ -1310,6 +1329,226 @@ module Inkoc
         raw_nullary_instruction(:GetBooleanPrototype, node, body)
       end
 
+      def on_raw_env_get(node, body)
+        raw_unary_instruction(:EnvGet, node, body)
+      end
+
+      def on_raw_env_set(node, body)
+        raw_binary_instruction(:EnvSet, node, body)
+      end
+
+      def on_raw_env_remove(node, body)
+        raw_unary_instruction(:EnvRemove, node, body)
+      end
+
+      def on_raw_env_variables(node, body)
+        raw_nullary_instruction(:EnvVariables, node, body)
+      end
+
+      def on_raw_env_arguments(node, body)
+        raw_nullary_instruction(:EnvArguments, node, body)
+      end
+
+      def on_raw_env_get_working_directory(node, body)
+        raw_nullary_instruction(:EnvGetWorkingDirectory, node, body)
+      end
+
+      def on_raw_env_set_working_directory(node, body)
+        raw_unary_instruction(:EnvSetWorkingDirectory, node, body)
+      end
+
+      def on_raw_process_monitor(node, body)
+        raw_unary_instruction(:ProcessMonitor, node, body)
+      end
+
+      def on_raw_process_demonitor(node, body)
+        raw_unary_instruction(:ProcessDemonitor, node, body)
+      end
+
+      def on_raw_process_link(node, body)
+        raw_unary_instruction(:ProcessLink, node, body)
+      end
+
+      def on_raw_process_unlink(node, body)
+        raw_unary_instruction(:ProcessUnlink, node, body)
+      end
+
+      def on_raw_process_trap_exits(node, body)
+        raw_unary_instruction(:ProcessTrapExits, node, body)
+      end
+
+      def on_raw_process_terminate(node, body)
+        raw_binary_instruction(:ProcessTerminate, node, body)
+      end
+
+      def on_raw_process_register(node, body)
+        raw_binary_instruction(:ProcessRegister, node, body)
+      end
+
+      def on_raw_process_unregister(node, body)
+        raw_unary_instruction(:ProcessUnregister, node, body)
+      end
+
+      def on_raw_process_whereis(node, body)
+        raw_unary_instruction(:ProcessWhereis, node, body)
+      end
+
+      def on_raw_process_registered_names(node, body)
+        raw_nullary_instruction(:ProcessRegisteredNames, node, body)
+      end
+
+      def on_raw_process_receive_message_if(node, body)
+        raw_binary_instruction(:ProcessReceiveMessageIf, node, body)
+      end
+
+      def on_raw_process_set_mailbox_capacity(node, body)
+        raw_ternary_instruction(:ProcessSetMailboxCapacity, node, body)
+      end
+
+      def on_raw_process_mailbox_length(node, body)
+        raw_unary_instruction(:ProcessMailboxLength, node, body)
+      end
+
+      def on_raw_reference_new(node, body)
+        raw_nullary_instruction(:ReferenceNew, node, body)
+      end
+
+      def on_raw_reference_equals(node, body)
+        raw_binary_instruction(:ReferenceEquals, node, body)
+      end
+
+      def on_raw_timer_schedule(node, body)
+        raw_quaternary_instruction(:TimerSchedule, node, body)
+      end
+
+      def on_raw_timer_cancel(node, body)
+        raw_unary_instruction(:TimerCancel, node, body)
+      end
+
+      def on_raw_tcp_listen(node, body)
+        raw_unary_instruction(:TcpListen, node, body)
+      end
+
+      def on_raw_tcp_connect(node, body)
+        raw_unary_instruction(:TcpConnect, node, body)
+      end
+
+      def on_raw_socket_accept(node, body)
+        raw_unary_instruction(:SocketAccept, node, body)
+      end
+
+      def on_raw_socket_finish_connect(node, body)
+        raw_unary_instruction(:SocketFinishConnect, node, body)
+      end
+
+      def on_raw_socket_read(node, body)
+        raw_ternary_instruction(:SocketRead, node, body)
+      end
+
+      def on_raw_socket_write(node, body)
+        raw_binary_instruction(:SocketWrite, node, body)
+      end
+
+      def on_raw_socket_shutdown(node, body)
+        raw_binary_instruction(:SocketShutdown, node, body)
+      end
+
+      def on_raw_socket_local_address(node, body)
+        raw_unary_instruction(:SocketLocalAddress, node, body)
+      end
+
+      def on_raw_socket_peer_address(node, body)
+        raw_unary_instruction(:SocketPeerAddress, node, body)
+      end
+
+      def on_raw_udp_bind(node, body)
+        raw_unary_instruction(:UdpBind, node, body)
+      end
+
+      def on_raw_unix_listen(node, body)
+        raw_unary_instruction(:UnixListen, node, body)
+      end
+
+      def on_raw_unix_connect(node, body)
+        raw_unary_instruction(:UnixConnect, node, body)
+      end
+
+      def on_raw_unix_datagram_bind(node, body)
+        raw_unary_instruction(:UnixDatagramBind, node, body)
+      end
+
+      def on_raw_unix_pair(node, body)
+        raw_unary_instruction(:UnixPair, node, body)
+      end
+
+      def on_raw_socket_connect(node, body)
+        raw_binary_instruction(:SocketConnect, node, body)
+      end
+
+      def on_raw_socket_send_to(node, body)
+        raw_ternary_instruction(:SocketSendTo, node, body)
+      end
+
+      def on_raw_socket_receive_from(node, body)
+        raw_ternary_instruction(:SocketReceiveFrom, node, body)
+      end
+
+      def on_raw_socket_set_option(node, body)
+        raw_ternary_instruction(:SocketSetOption, node, body)
+      end
+
+      def on_raw_socket_get_option(node, body)
+        raw_binary_instruction(:SocketGetOption, node, body)
+      end
+
+      def on_raw_socket_join_multicast(node, body)
+        raw_ternary_instruction(:SocketJoinMulticast, node, body)
+      end
+
+      def on_raw_socket_leave_multicast(node, body)
+        raw_ternary_instruction(:SocketLeaveMulticast, node, body)
+      end
+
+      def on_raw_child_spawn(node, body)
+        raw_quaternary_instruction(:ChildSpawn, node, body)
+      end
+
+      def on_raw_child_write(node, body)
+        raw_binary_instruction(:ChildWrite, node, body)
+      end
+
+      def on_raw_child_read(node, body)
+        raw_quaternary_instruction(:ChildRead, node, body)
+      end
+
+      def on_raw_child_close_input(node, body)
+        raw_unary_instruction(:ChildCloseInput, node, body)
+      end
+
+      def on_raw_child_wait(node, body)
+        raw_unary_instruction(:ChildWait, node, body)
+      end
+
+      def on_raw_child_exit_code(node, body)
+        raw_unary_instruction(:ChildExitCode, node, body)
+      end
+
+      def on_raw_child_exit_signal(node, body)
+        raw_unary_instruction(:ChildExitSignal, node, body)
+      end
+
+      def on_raw_child_kill(node, body)
+        raw_unary_instruction(:ChildKill, node, body)
+      end
+
+      def on_raw_signal_subscribe(node, body)
+        raw_unary_instruction(:SignalSubscribe, node, body)
+      end
+
+      def on_raw_signal_unsubscribe(node, body)
+        raw_unary_instruction(:SignalUnsubscribe, node, body)
+      end
+
       def on_return(node, body)
         location = node.location
         register =
@@ -0,0 +1,872 @@
+//! An assembler for a textual representation of Inko bytecode.
+//!
+//! The assembler makes it possible to write programs for the VM without having
+//! to use the compiler, which is useful for testing and reproducing VM
+//! behaviour. Source code consists of lines containing directives, labels, and
+//! instructions. Comments start with a `;` and run until the end of the line.
+//!
+//! A simple program looks as follows:
+//!
+//!     .code "main" "main.inko" 1
+//!     .locals 1
+//!     .registers 2
+//!     .literal 10
+//!     .literal "hello"
+//!
+//!       SetLiteral 0, 0
+//!       GotoIfTrue @done, 0
+//!       SetLiteral 0, 1
+//!     done:
+//!       Return 1, 0
+//!     .end
+//!
+//! The following directives are supported:
+//!
+//! * `.code NAME FILE LINE`: starts a new code object. Code objects defined
+//!   inside another code object are added to its list of code objects.
+//! * `.end`: ends the current code object.
+//! * `.arguments NAME...`: the names of the arguments.
+//! * `.required_arguments N`: the number of required arguments.
+//! * `.rest_argument`: marks the last argument as a rest argument.
+//! * `.captures`: marks the code object as capturing its enclosing scope.
+//! * `.locals N`: the number of local variables.
+//! * `.registers N`: the number of registers.
+//! * `.literal VALUE`: adds an integer, float, or string literal. Integers too
+//!   large for a 64 bits integer are stored as big integers.
+//! * `.line N`: the source line of all instructions that follow.
+//! * `.catch START END JUMP REGISTER`: adds an entry to the catch table. The
+//!   end of the range is inclusive.
+//!
+//! Instructions are written using the names of the `InstructionType` variants,
+//! followed by their arguments. Arguments can be separated using commas.
+//! Labels are defined using `name:` and refer to the instruction that follows
+//! them, and are referred to using `@name`. Labels are scoped to the code
+//! object they are defined in.
+//!
+//! To assemble source code into a CompiledCode you can use the `assemble`
+//! function:
+//!
+//!     let state = State::new(Config::new());
+//!     let code = assembler::assemble(&state, source).unwrap();
+//!
+//! Alternatively you can write a bytecode file directly:
+//!
+//!     assembler::assemble_file(&state, "main.inkoa", "main.inkoc").unwrap();
+use num_bigint::BigInt;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::prelude::*;
+use std::iter::Peekable;
+use std::str::Chars;
+use std::u16;
+
+use bytecode_writer::{self, WriterError};
+use catch_table::{CatchEntry, CatchTable};
+use compiled_code::CompiledCode;
+use object_pointer::ObjectPointer;
+use vm::instruction::{Instruction, InstructionType, INSTRUCTION_TYPES};
+use vm::state::RcState;
+
+#[derive(Debug)]
+pub enum AssemblerError {
+    /// A file could not be read or written.
+    InvalidFile(String),
+
+    /// A string literal is not terminated or contains an invalid escape
+    /// sequence.
+    InvalidString(usize),
+
+    /// A number could not be parsed or is out of range.
+    InvalidNumber(usize),
+
+    /// A token was found where it is not allowed.
+    UnexpectedToken(usize),
+
+    /// A directive does not exist.
+    UnknownDirective(String, usize),
+
+    /// An instruction does not exist.
+    UnknownInstruction(String, usize),
+
+    /// A label is defined more than once in the same code object.
+    DuplicateLabel(String, usize),
+
+    /// A label is used but never defined.
+    UndefinedLabel(String, usize),
+
+    /// A code object is not terminated using `.end`.
+    UnterminatedCode(usize),
+
+    /// The source does not define a code object.
+    MissingCode,
+
+    /// The assembled code could not be serialized.
+    FailedToWrite(WriterError),
+}
+
+pub type AssemblerResult<T> = Result<T, AssemblerError>;
+
+/// A single token of a line of source code.
+#[derive(Debug, PartialEq, Clone)]
+enum Token {
+    /// An instruction name, or a bare word argument.
+    Word(String),
+
+    /// A directive such as `.code`.
+    Directive(String),
+
+    /// The definition of a label.
+    Label(String),
+
+    /// A reference to a label.
+    LabelRef(String),
+
+    /// A string literal.
+    Str(String),
+
+    /// An integer or float literal.
+    Number(String),
+}
+
+impl Token {
+    fn is_label(&self) -> bool {
+        match *self {
+            Token::Label(_) => true,
+            _ => false,
+        }
+    }
+}
+
+/// A literal value of a code object.
+enum Literal {
+    Integer(i64),
+    BigInt(BigInt),
+    Float(f64),
+    String(String),
+}
+
+/// An instruction argument, or a catch table value.
+enum Operand {
+    Value(u16),
+
+    /// A reference to a label, and the line it was referred to on.
+    Label(String, usize),
+}
+
+/// A code object that is still being assembled.
+struct PendingCode {
+    code: Code,
+    instructions: Vec<(InstructionType, Vec<Operand>, u16)>,
+    catch_entries: Vec<Vec<Operand>>,
+    labels: HashMap<String, usize>,
+    current_line: u16,
+    start_line: usize,
+}
+
+/// A fully assembled code object.
+struct Code {
+    name: String,
+    file: String,
+    line: u16,
+    arguments: Vec<String>,
+    required_arguments: u8,
+    rest_argument: bool,
+    locals: u16,
+    registers: u16,
+    captures: bool,
+    instructions: Vec<Instruction>,
+    literals: Vec<Literal>,
+    code_objects: Vec<Code>,
+    catch_table: CatchTable,
+}
+
+/// Assembles source code into a CompiledCode.
+pub fn assemble(
+    state: &RcState,
+    source: &str,
+) -> AssemblerResult<CompiledCode> {
+    Ok(compile(state, parse(source)?))
+}
+
+/// Assembles source code into a stream of bytes that can be read using
+/// `bytecode_parser::parse`.
+pub fn assemble_bytecode(
+    state: &RcState,
+    source: &str,
+) -> AssemblerResult<Vec<u8>> {
+    let code = assemble(state, source)?;
+
+    bytecode_writer::write(&code).map_err(AssemblerError::FailedToWrite)
+}
+
+/// Assembles the source file `input` and writes the bytecode to `output`.
+pub fn assemble_file(
+    state: &RcState,
+    input: &str,
+    output: &str,
+) -> AssemblerResult<()> {
+    let mut source = String::new();
+
+    File::open(input)
+        .and_then(|mut file| file.read_to_string(&mut source))
+        .map_err(|_| AssemblerError::InvalidFile(input.to_string()))?;
+
+    let bytes = assemble_bytecode(state, &source)?;
+
+    File::create(output)
+        .and_then(|mut file| file.write_all(&bytes))
+        .map_err(|_| AssemblerError::InvalidFile(output.to_string()))
+}
+
+fn parse(source: &str) -> AssemblerResult<Code> {
+    let mut stack: Vec<PendingCode> = Vec::new();
+    let mut root = None;
+
+    for (index, line) in source.lines().enumerate() {
+        let line_number = index + 1;
+        let mut tokens = tokenize(line, line_number)?;
+        let amount = tokens.iter().take_while(|token| token.is_label()).count();
+
+        for token in tokens.drain(0..amount) {
+            if let Token::Label(name) = token {
+                let pending = stack
+                    .last_mut()
+                    .ok_or(AssemblerError::UnexpectedToken(line_number))?;
+
+                let index = pending.instructions.len();
+
+                if pending.labels.insert(name.clone(), index).is_some() {
+                    return Err(AssemblerError::DuplicateLabel(
+                        name,
+                        line_number,
+                    ));
+                }
+            }
+        }
+
+        let mut tokens = tokens.into_iter();
+        let first = match tokens.next() {
+            Some(token) => token,
+            None => continue,
+        };
+
+        let rest: Vec<Token> = tokens.collect();
+
+        match first {
+            Token::Directive(ref name) if name == "code" => {
+                if root.is_some() {
+                    return Err(AssemblerError::UnexpectedToken(line_number));
+                }
+
+                stack.push(new_pending_code(&rest, line_number)?);
+            }
+            Token::Directive(ref name) if name == "end" => {
+                expect_operands(&rest, 0, line_number)?;
+
+                let pending = stack
+                    .pop()
+                    .ok_or(AssemblerError::UnexpectedToken(line_number))?;
+
+                let code = finish_code(pending)?;
+
+                if let Some(parent) = stack.last_mut() {
+                    parent.code.code_objects.push(code);
+                } else {
+                    root = Some(code);
+                }
+            }
+            Token::Directive(name) => {
+                let pending = stack
+                    .last_mut()
+                    .ok_or(AssemblerError::UnexpectedToken(line_number))?;
+
+                directive(pending, name, &rest, line_number)?;
+            }
+            Token::Word(name) => {
+                let pending = stack
+                    .last_mut()
+                    .ok_or(AssemblerError::UnexpectedToken(line_number))?;
+
+                let ins_type = instruction_type(&name).ok_or(
+                    AssemblerError::UnknownInstruction(name, line_number),
+                )?;
+
+                let mut operands = Vec::with_capacity(rest.len());
+
+                for token in rest {
+                    operands.push(operand(token, line_number)?);
+                }
+
+                pending.instructions.push((
+                    ins_type,
+                    operands,
+                    pending.current_line,
+                ));
+            }
+            _ => return Err(AssemblerError::UnexpectedToken(line_number)),
+        }
+    }
+
+    if let Some(pending) = stack.pop() {
+        return Err(AssemblerError::UnterminatedCode(pending.start_line));
+    }
+
+    root.ok_or(AssemblerError::MissingCode)
+}
+
+fn new_pending_code(
+    tokens: &[Token],
+    line_number: usize,
+) -> AssemblerResult<PendingCode> {
+    expect_operands(tokens, 3, line_number)?;
+
+    let name = name_operand(&tokens[0], line_number)?;
+    let file = name_operand(&tokens[1], line_number)?;
+    let line = number_operand(&tokens[2], line_number)?;
+
+    Ok(PendingCode {
+        code: Code {
+            name,
+            file,
+            line,
+            arguments: Vec::new(),
+            required_arguments: 0,
+            rest_argument: false,
+            locals: 0,
+            registers: 0,
+            captures: false,
+            instructions: Vec::new(),
+            literals: Vec::new(),
+            code_objects: Vec::new(),
+            catch_table: CatchTable::new(),
+        },
+        instructions: Vec::new(),
+        catch_entries: Vec::new(),
+        labels: HashMap::new(),
+        current_line: line,
+        start_line: line_number,
+    })
+}
+
+fn directive(
+    pending: &mut PendingCode,
+    name: String,
+    tokens: &[Token],
+    line_number: usize,
+) -> AssemblerResult<()> {
+    match name.as_str() {
+        "arguments" => {
+            for token in tokens {
+                let name = name_operand(token, line_number)?;
+
+                pending.code.arguments.push(name);
+            }
+        }
+        "required_arguments" => {
+            expect_operands(tokens, 1, line_number)?;
+
+            let amount: u16 = number_operand(&tokens[0], line_number)?;
+
+            if amount > u16::from(u8::max_value()) {
+                return Err(AssemblerError::InvalidNumber(line_number));
+            }
+
+            pending.code.required_arguments = amount as u8;
+        }
+        "rest_argument" => {
+            expect_operands(tokens, 0, line_number)?;
+
+            pending.code.rest_argument = true;
+        }
+        "captures" => {
+            expect_operands(tokens, 0, line_number)?;
+
+            pending.code.captures = true;
+        }
+        "locals" => {
+            expect_operands(tokens, 1, line_number)?;
+
+            pending.code.locals = number_operand(&tokens[0], line_number)?;
+        }
+        "registers" => {
+            expect_operands(tokens, 1, line_number)?;
+
+            pending.code.registers = number_operand(&tokens[0], line_number)?;
+        }
+        "line" => {
+            expect_operands(tokens, 1, line_number)?;
+
+            pending.current_line = number_operand(&tokens[0], line_number)?;
+        }
+        "literal" => {
+            expect_operands(tokens, 1, line_number)?;
+
+            let literal = literal(&tokens[0], line_number)?;
+
+            pending.code.literals.push(literal);
+        }
+        "catch" => {
+            expect_operands(tokens, 4, line_number)?;
+
+            let mut operands = Vec::with_capacity(4);
+
+            for token in tokens {
+                operands.push(operand(token.clone(), line_number)?);
+            }
+
+            pending.catch_entries.push(operands);
+        }
+        _ => {
+            return Err(AssemblerError::UnknownDirective(name, line_number));
+        }
+    }
+
+    Ok(())
+}
+
+fn finish_code(pending: PendingCode) -> AssemblerResult<Code> {
+    let PendingCode {
+        mut code,
+        instructions,
+        catch_entries,
+        labels,
+        ..
+    } = pending;
+
+    let resolve = |operand: &Operand| -> AssemblerResult<u16> {
+        match *operand {
+            Operand::Value(value) => Ok(value),
+            Operand::Label(ref name, line_number) => {
+                let index = *labels.get(name).ok_or_else(|| {
+                    AssemblerError::UndefinedLabel(name.clone(), line_number)
+                })?;
+
+                if index > u16::MAX as usize {
+                    return Err(AssemblerError::InvalidNumber(line_number));
+                }
+
+                Ok(index as u16)
+            }
+        }
+    };
+
+    for (ins_type, operands, line) in instructions {
+        let mut arguments = Vec::with_capacity(operands.len());
+
+        for operand in &operands {
+            arguments.push(resolve(operand)?);
+        }
+
+        code.instructions
+            .push(Instruction::new(ins_type, arguments, line));
+    }
+
+    for operands in catch_entries {
+        code.catch_table.entries.push(CatchEntry::new(
+            resolve(&operands[0])? as usize,
+            resolve(&operands[1])? as usize,
+            resolve(&operands[2])? as usize,
+            resolve(&operands[3])? as usize,
+        ));
+    }
+
+    Ok(code)
+}
+
+fn expect_operands(
+    tokens: &[Token],
+    amount: usize,
+    line_number: usize,
+) -> AssemblerResult<()> {
+    if tokens.len() == amount {
+        Ok(())
+    } else {
+        Err(AssemblerError::UnexpectedToken(line_number))
+    }
+}
+
+fn instruction_type(name: &str) -> Option<InstructionType> {
+    INSTRUCTION_TYPES
+        .iter()
+        .find(|ins_type| format!("{:?}", ins_type) == name)
+        .cloned()
+}
+
+fn operand(token: Token, line_number: usize) -> AssemblerResult<Operand> {
+    match token {
+        Token::LabelRef(name) => Ok(Operand::Label(name, line_number)),
+        token => Ok(Operand::Value(number_operand(&token, line_number)?)),
+    }
+}
+
+fn number_operand(token: &Token, line_number: usize) -> AssemblerResult<u16> {
+    match *token {
+        Token::Number(ref number) => number
+            .parse::<u16>()
+            .map_err(|_| AssemblerError::InvalidNumber(line_number)),
+        _ => Err(AssemblerError::UnexpectedToken(line_number)),
+    }
+}
+
+fn name_operand(token: &Token, line_number: usize) -> AssemblerResult<String> {
+    match *token {
+        Token::Str(ref value) | Token::Word(ref value) => Ok(value.clone()),
+        _ => Err(AssemblerError::UnexpectedToken(line_number)),
+    }
+}
+
+fn literal(token: &Token, line_number: usize) -> AssemblerResult<Literal> {
+    match *token {
+        Token::Str(ref value) => Ok(Literal::String(value.clone())),
+        Token::Number(ref number) => {
+            if number.contains('.') || number.contains('e') {
+                return number
+                    .parse::<f64>()
+                    .map(Literal::Float)
+                    .map_err(|_| AssemblerError::InvalidNumber(line_number));
+            }
+
+            if let Ok(integer) = number.parse::<i64>() {
+                return Ok(Literal::Integer(integer));
+            }
+
+            number
+                .parse::<BigInt>()
+                .map(Literal::BigInt)
+                .map_err(|_| AssemblerError::InvalidNumber(line_number))
+        }
+        _ => Err(AssemblerError::UnexpectedToken(line_number)),
+    }
+}
+
+fn tokenize(line: &str, line_number: usize) -> AssemblerResult<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = line.chars().peekable();
+
+    while let Some(&current) = chars.peek() {
+        match current {
+            ';' => break,
+            ',' => {
+                chars.next();
+            }
+            '"' => {
+                chars.next();
+                tokens.push(Token::Str(string(&mut chars, line_number)?));
+            }
+            '.' => {
+                chars.next();
+                tokens.push(Token::Directive(word(&mut chars)));
+            }
+            '@' => {
+                chars.next();
+                tokens.push(Token::LabelRef(word(&mut chars)));
+            }
+            _ if current == '-' || current.is_ascii_digit() => {
+                tokens.push(Token::Number(word(&mut chars)));
+            }
+            _ if current.is_whitespace() => {
+                chars.next();
+            }
+            _ => {
+                let value = word(&mut chars);
+
+                if value.is_empty() {
+                    return Err(AssemblerError::UnexpectedToken(line_number));
+                }
+
+                if let Some(&':') = chars.peek() {
+                    chars.next();
+                    tokens.push(Token::Label(value));
+                } else {
+                    tokens.push(Token::Word(value));
+                }
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+fn word(chars: &mut Peekable<Chars>) -> String {
+    let mut buffer = String::new();
+
+    while let Some(&current) = chars.peek() {
+        if current.is_whitespace() || ",;:\"@".contains(current) {
+            break;
+        }
+
+        buffer.push(current);
+        chars.next();
+    }
+
+    buffer
+}
+
+fn string(
+    chars: &mut Peekable<Chars>,
+    line_number: usize,
+) -> AssemblerResult<String> {
+    let mut buffer = String::new();
+
+    loop {
+        match chars.next() {
+            Some('"') => return Ok(buffer),
+            Some('\\') => {
+                let escaped = match chars.next() {
+                    Some('n') => '\n',
+                    Some('t') => '\t',
+                    Some('r') => '\r',
+                    Some('0') => '\0',
+                    Some('\\') => '\\',
+                    Some('"') => '"',
+                    _ => {
+                        return Err(AssemblerError::InvalidString(line_number))
+                    }
+                };
+
+                buffer.push(escaped);
+            }
+            Some(current) => buffer.push(current),
+            None => return Err(AssemblerError::InvalidString(line_number)),
+        }
+    }
+}
+
+fn compile(state: &RcState, code: Code) -> CompiledCode {
+    let literals = code
+        .literals
+        .into_iter()
+        .map(|literal| match literal {
+            Literal::Integer(value) => {
+                if ObjectPointer::integer_too_large(value) {
+                    state.allocate_permanent_integer(value)
+                } else {
+                    ObjectPointer::integer(value)
+                }
+            }
+            Literal::BigInt(value) => state.allocate_permanent_bigint(value),
+            Literal::Float(value) => state.allocate_permanent_float(value),
+            Literal::String(value) => state.intern_owned(value),
+        })
+        .collect();
+
+    CompiledCode {
+        name: state.intern_owned(code.name),
+        file: state.intern_owned(code.file),
+        line: code.line,
+        arguments: code
+            .arguments
+            .into_iter()
+            .map(|name| state.intern_owned(name))
+            .collect(),
+        required_arguments: code.required_arguments,
+        rest_argument: code.rest_argument,
+        locals: code.locals,
+        registers: code.registers,
+        captures: code.captures,
+        instructions: code.instructions,
+        literals,
+        code_objects: code
+            .code_objects
+            .into_iter()
+            .map(|child| compile(state, child))
+            .collect(),
+        catch_table: code.catch_table,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytecode_parser;
+    use config::Config;
+    use vm::state::State;
+
+    const SOURCE: &str = r#"
+; A program that exercises most of the assembler.
+.code "main" "main.inko" 1
+.arguments self "number"
+.required_arguments 1
+.locals 2
+.registers 3
+.literal 10
+.literal -2.5
+.literal "hello \"world\""
+.literal 123456789012345678901234567890
+
+start:
+  SetLiteral 0, 0
+.line 3
+  GotoIfTrue @done, 0
+  SetBlock 1, 0
+  Throw 1
+done: Return 1, 2
+
+.catch @start @start @done 2
+
+  .code "<block>" "main.inko" 4
+  .captures
+    Return 0
+  .end
+.end
+"#;
+
+    #[test]
+    fn test_assemble() {
+        let state = State::new(Config::new());
+        let code = assemble(&state, SOURCE).unwrap();
+
+        assert_eq!(code.name.string_value().unwrap(), "main");
+        assert_eq!(code.file.string_value().unwrap(), "main.inko");
+        assert_eq!(code.line, 1);
+        assert_eq!(code.arguments.len(), 2);
+        assert_eq!(code.arguments[1].string_value().unwrap(), "number");
+        assert_eq!(code.required_arguments, 1);
+        assert_eq!(code.rest_argument, false);
+        assert_eq!(code.locals, 2);
+        assert_eq!(code.registers, 3);
+        assert_eq!(code.captures, false);
+
+        assert_eq!(code.literals[0].integer_value().unwrap(), 10);
+        assert_eq!(code.literals[1].float_value().unwrap(), -2.5);
+        assert_eq!(code.literals[2].string_value().unwrap(), "hello \"world\"");
+        assert_eq!(
+            code.literals[3].bigint_value().unwrap().to_string(),
+            "123456789012345678901234567890"
+        );
+
+        assert_eq!(code.instructions.len(), 5);
+        assert_eq!(
+            code.instructions[0].instruction_type,
+            InstructionType::SetLiteral
+        );
+        assert_eq!(code.instructions[0].arguments, vec![0, 0]);
+        assert_eq!(code.instructions[0].line, 1);
+        assert_eq!(code.instructions[1].arguments, vec![4, 0]);
+        assert_eq!(code.instructions[1].line, 3);
+        assert_eq!(
+            code.instructions[4].instruction_type,
+            InstructionType::Return
+        );
+
+        let entry = &code.catch_table.entries[0];
+
+        assert_eq!(entry.start, 0);
+        assert_eq!(entry.end, 0);
+        assert_eq!(entry.jump_to, 4);
+        assert_eq!(entry.register, 2);
+
+        assert_eq!(code.code_objects.len(), 1);
+        assert!(code.code_objects[0].captures);
+        assert_eq!(code.code_objects[0].line, 4);
+    }
+
+    #[test]
+    fn test_assemble_bytecode() {
+        let state = State::new(Config::new());
+        let bytes = assemble_bytecode(&state, SOURCE).unwrap();
+        let code =
+            bytecode_parser::parse(&state, &mut (&bytes[..]).bytes()).unwrap();
+
+        assert_eq!(code.name.string_value().unwrap(), "main");
+        assert_eq!(code.arguments.len(), 2);
+        assert_eq!(code.required_arguments, 1);
+        assert_eq!(code.locals, 2);
+        assert_eq!(code.registers, 3);
+        assert_eq!(code.literals[0].integer_value().unwrap(), 10);
+        assert_eq!(code.literals[1].float_value().unwrap(), -2.5);
+        assert_eq!(
+            code.literals[3].bigint_value().unwrap().to_string(),
+            "123456789012345678901234567890"
+        );
+        assert_eq!(code.instructions.len(), 5);
+        assert_eq!(code.instructions[1].arguments, vec![4, 0]);
+        assert_eq!(code.instructions[1].line, 3);
+        assert_eq!(code.catch_table.entries[0].jump_to, 4);
+        assert!(code.code_objects[0].captures);
+    }
+
+    #[test]
+    fn test_assemble_unknown_instruction() {
+        let state = State::new(Config::new());
+        let result = assemble(&state, ".code a b 1\nFoo 1\n.end");
+
+        match result {
+            Err(AssemblerError::UnknownInstruction(name, 2)) => {
+                assert_eq!(name, "Foo".to_string());
+            }
+            _ => panic!("expected an UnknownInstruction error"),
+        }
+    }
+
+    #[test]
+    fn test_assemble_undefined_label() {
+        let state = State::new(Config::new());
+        let result = assemble(&state, ".code a b 1\nGotoIfTrue @foo, 0\n.end");
+
+        match result {
+            Err(AssemblerError::UndefinedLabel(name, 2)) => {
+                assert_eq!(name, "foo".to_string());
+            }
+            _ => panic!("expected an UndefinedLabel error"),
+        }
+    }
+
+    #[test]
+    fn test_assemble_label_out_of_range() {
+        let state = State::new(Config::new());
+        let mut source = ".code a b 1\nGotoIfTrue @end, 0\n".to_string();
+
+        for _ in 0..u16::MAX {
+            source.push_str("Return 0\n");
+        }
+
+        source.push_str("end: Return 0\n.end");
+
+        match assemble(&state, &source) {
+            Err(AssemblerError::InvalidNumber(2)) => {}
+            _ => panic!("expected an InvalidNumber error"),
+        }
+    }
+
+    #[test]
+    fn test_assemble_duplicate_label() {
+        let state = State::new(Config::new());
+        let result = assemble(&state, ".code a b 1\nfoo:\nfoo:\n.end");
+
+        match result {
+            Err(AssemblerError::DuplicateLabel(_, 3)) => {}
+            _ => panic!("expected a DuplicateLabel error"),
+        }
+    }
+
+    #[test]
+    fn test_assemble_unterminated_code() {
+        let state = State::new(Config::new());
+
+        match assemble(&state, "\n.code a b 1\nReturn 0") {
+            Err(AssemblerError::UnterminatedCode(2)) => {}
+            _ => panic!("expected an UnterminatedCode error"),
+        }
+    }
+
+    #[test]
+    fn test_assemble_missing_code() {
+        let state = State::new(Config::new());
+
+        match assemble(&state, "; nothing to see here") {
+            Err(AssemblerError::MissingCode) => {}
+            _ => panic!("expected a MissingCode error"),
+        }
+    }
+
+    #[test]
+    fn test_assemble_invalid_string() {
+        let state = State::new(Config::new());
+
+        match assemble(&state, ".code \"a b 1") {
+            Err(AssemblerError::InvalidString(1)) => {}
+            _ => panic!("expected an InvalidString error"),
+        }
+    }
+}
@@ -2,15 +2,24 @@ extern crate getopts;
 extern crate libinko;
 
 use std::env;
+use std::fs;
 use std::io::{self, Write};
 use std::process;
+use std::sync::{Arc, RwLock};
 
+use libinko::bundle::{self, Bundle, BundleError, BundleResult};
 use libinko::config::Config;
+use libinko::disassembler;
+use libinko::module_registry::ModuleRegistry;
 use libinko::vm::machine::Machine;
-use libinko::vm::state::State;
+use libinko::vm::state::{RcState, State};
 
 fn print_usage(options: &getopts::Options) {
-    print_stderr(&options.usage("Usage: ivm FILE [OPTIONS]").to_string());
+    print_stderr(
+        &options
+            .usage("Usage: ivm FILE [OPTIONS] [-- ARGS]")
+            .to_string(),
+    );
 }
 
 fn print_stderr(message: &str) {
@@ -21,13 +30,170 @@ fn print_stderr(message: &str) {
     stderr.flush().unwrap();
 }
 
+fn disassemble(state: &RcState, path: &str) -> i32 {
+    match disassembler::disassemble_file(state, path) {
+        Ok(listing) => {
+            print!("{}", listing);
+            0
+        }
+        Err(err) => {
+            print_stderr(&format!(
+                "Failed to parse {}:\n{}",
+                path,
+                err.message()
+            ));
+            1
+        }
+    }
+}
+
+/// Returns the bundle to use for FILE.
+///
+/// If FILE is already a bundle it's used as-is, otherwise a new bundle is
+/// created using FILE and the modules in the include directories.
+fn bundle_bytes(config: &Config, path: &str) -> BundleResult<Vec<u8>> {
+    if bundle::is_bundle_file(path) {
+        Bundle::from_file(path)?;
+
+        fs::read(path).map_err(|_| BundleError::InvalidFile(path.to_string()))
+    } else {
+        bundle::bundle_program(path, &config.directories)
+    }
+}
+
+fn create_bundle(config: &Config, path: &str, output: &str) -> i32 {
+    let result =
+        bundle::bundle_program(path, &config.directories).and_then(|bytes| {
+            fs::write(output, &bytes)
+                .map_err(|_| BundleError::InvalidFile(output.to_string()))
+        });
+
+    match result {
+        Ok(_) => 0,
+        Err(err) => {
+            print_stderr(&format!(
+                "Failed to create bundle {}:\n{}",
+                output,
+                err.message()
+            ));
+            1
+        }
+    }
+}
+
+fn create_executable(config: &Config, path: &str, output: &str) -> i32 {
+    let result = env::current_exe()
+        .map_err(|_| BundleError::InvalidFile("ivm".to_string()))
+        .and_then(|runtime| {
+            let bytes = bundle_bytes(config, path)?;
+
+            bundle::create_executable(
+                &runtime.to_string_lossy(),
+                &bytes,
+                output,
+            )
+        });
+
+    match result {
+        Ok(_) => 0,
+        Err(err) => {
+            print_stderr(&format!(
+                "Failed to create executable {}:\n{}",
+                output,
+                err.message()
+            ));
+            1
+        }
+    }
+}
+
+fn run_bundle_file(state: RcState, path: &str) -> i32 {
+    match Bundle::from_file(path) {
+        Ok(bundle) => run_bundle(state, bundle),
+        Err(err) => {
+            print_stderr(&format!(
+                "Failed to load bundle {}:\n{}",
+                path,
+                err.message()
+            ));
+            1
+        }
+    }
+}
+
+/// Runs the program embedded in the current executable, if there is any.
+fn run_embedded() -> Option<i32> {
+    let executable = env::current_exe().ok()?;
+    let path = executable.to_string_lossy();
+
+    match Bundle::from_executable(&path) {
+        Ok(Some(bundle)) => {
+            let mut config = Config::new();
+
+            // All arguments are passed to the embedded program, as the
+            // executable is the program itself.
+            for argument in env::args().skip(1) {
+                config.add_argument(argument);
+            }
+
+            config.populate_from_env();
+
+            Some(run_bundle(State::new(config), bundle))
+        }
+        Ok(None) => None,
+        Err(err) => {
+            print_stderr(&format!(
+                "Failed to load the program embedded in {}:\n{}",
+                path,
+                err.message()
+            ));
+            Some(1)
+        }
+    }
+}
+
+fn run_bundle(state: RcState, bundle: Bundle) -> i32 {
+    let entry_point = bundle.entry_point().to_string();
+    let mut registry = ModuleRegistry::new(state.clone());
+
+    registry.set_bundle(bundle);
+
+    let machine = Machine::new(state, Arc::new(RwLock::new(registry)));
+
+    machine.start(&entry_point);
+    machine.state.current_exit_status()
+}
+
 #[cfg_attr(feature = "cargo-clippy", allow(print_literal))]
 fn run() -> i32 {
+    if let Some(status) = run_embedded() {
+        return status;
+    }
+
     let args: Vec<String> = env::args().collect();
     let mut options = getopts::Options::new();
 
     options.optflag("h", "help", "Shows this help message");
     options.optflag("v", "version", "Prints the version number");
+    options.optflag(
+        "d",
+        "disassemble",
+        "Prints the bytecode of FILE instead of running it",
+    );
+
+    options.optopt(
+        "b",
+        "bundle",
+        "Bundles FILE and the modules in the include directories into OUTPUT",
+        "OUTPUT",
+    );
+
+    options.optopt(
+        "x",
+        "executable",
+        "Creates a self-contained executable for FILE in OUTPUT",
+        "OUTPUT",
+    );
 
     options.optmulti(
         "I",
@@ -69,8 +235,28 @@ fn run() -> i32 {
             }
         }
 
+        for argument in &matches.free[1..] {
+            config.add_argument(argument.clone());
+        }
+
         config.populate_from_env();
 
+        if matches.opt_present("d") {
+            return disassemble(&State::new(config), path);
+        }
+
+        if let Some(output) = matches.opt_str("b") {
+            return create_bundle(&config, path, &output);
+        }
+
+        if let Some(output) = matches.opt_str("x") {
+            return create_executable(&config, path, &output);
+        }
+
+        if bundle::is_bundle_file(path) {
+            return run_bundle_file(State::new(config), path);
+        }
+
         let machine = Machine::default(State::new(config));
 
         machine.start(path);
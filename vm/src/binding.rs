@@ -123,9 +123,14 @@ impl Binding {
 
     /// Creates a new binding and recursively copies over all pointers to the
     /// target heap.
-    pub fn clone_to<H: CopyObject>(&self, heap: &mut H) -> RcBinding {
+    ///
+    /// An error is returned if any of the pointers can't be copied.
+    pub fn clone_to<H: CopyObject>(
+        &self,
+        heap: &mut H,
+    ) -> Result<RcBinding, String> {
         let parent = if let Some(ref bind) = self.parent {
-            Some(bind.clone_to(heap))
+            Some(bind.clone_to(heap)?)
         } else {
             None
         };
@@ -137,14 +142,14 @@ impl Binding {
             let pointer = locals[index];
 
             if !pointer.is_null() {
-                new_locals[index] = heap.copy_object(pointer);
+                new_locals[index] = heap.try_copy_object(pointer)?;
             }
         }
 
-        ArcWithoutWeak::new(Binding {
+        Ok(ArcWithoutWeak::new(Binding {
             locals: UnsafeCell::new(new_locals),
             parent,
-        })
+        }))
     }
 
     // Moves all pointers in this binding to the given heap.
@@ -385,7 +390,7 @@ mod tests {
         src_bind1.set_local(0, ptr1);
         src_bind2.set_local(0, ptr2);
 
-        let bind_copy = src_bind2.clone_to(&mut alloc2);
+        let bind_copy = src_bind2.clone_to(&mut alloc2).unwrap();
 
         assert_eq!(bind_copy.locals().len(), 1);
         assert!(bind_copy.parent.is_some());
@@ -0,0 +1,587 @@
+//! Bundles of multiple bytecode modules stored in a single file.
+//!
+//! A bundle contains the bytecode of many modules, keyed by the paths used to
+//! import them, along with the path of the module to run first. Modules are
+//! stored as-is and only parsed when they are imported.
+//!
+//! A bundle uses the following layout, with all integers stored in big endian
+//! order:
+//!
+//! 1. The signature "inkb".
+//! 2. The version of the bundle format, as a single byte.
+//! 3. The path of the entry point, as a u64 size followed by the bytes.
+//! 4. The number of modules, as a u64.
+//! 5. For every module its path (stored like the entry point), followed by the
+//!    offset and size of its bytecode as u64 values. Offsets are relative to
+//!    the start of the data section.
+//! 6. The data section, containing the bytecode of all modules.
+//!
+//! Loading a bundle is done using `Bundle::from_file`:
+//!
+//!     let bundle = Bundle::from_file("app.bundle").unwrap();
+//!     let bytes = bundle.module(bundle.entry_point()).unwrap();
+//!
+//! A bundle can also be appended to an executable of the VM, producing a
+//! single self-contained executable. Such an executable ends with a trailer
+//! containing the size of the bundle as a u64, followed by the image
+//! signature "inkoimg1". The bundle of such an executable is loaded using
+//! `Bundle::from_executable`.
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::prelude::*;
+use std::io::{self, SeekFrom};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::str;
+
+pub const SIGNATURE_BYTES: [u8; 4] = [105, 110, 107, 98]; // "inkb"
+
+pub const VERSION: u8 = 1;
+
+/// The signature stored at the end of an executable containing a bundle.
+pub const IMAGE_SIGNATURE_BYTES: [u8; 8] =
+    [105, 110, 107, 111, 105, 109, 103, 49]; // "inkoimg1"
+
+/// The size of the trailer stored at the end of an executable containing a
+/// bundle.
+const IMAGE_TRAILER_SIZE: u64 = 16;
+
+/// The file extension of bytecode files to include in a bundle.
+const BYTECODE_EXTENSION: &str = "inkoc";
+
+#[derive(Debug)]
+pub enum BundleError {
+    /// A file could not be read or written.
+    InvalidFile(String),
+
+    /// The input does not start with the bundle signature.
+    InvalidSignature,
+
+    /// The version of the bundle format is not supported.
+    InvalidVersion,
+
+    /// The index is truncated, or refers to data that does not exist.
+    InvalidIndex,
+
+    /// The entry point is not included in the bundle.
+    MissingEntryPoint(String),
+}
+
+pub type BundleResult<T> = Result<T, BundleError>;
+
+impl BundleError {
+    /// Returns a human friendly error message.
+    pub fn message(&self) -> String {
+        match *self {
+            BundleError::InvalidFile(ref path) => {
+                format!("The file {} could not be read or written", path)
+            }
+            BundleError::InvalidSignature => {
+                "The file is not an Inko bundle".to_string()
+            }
+            BundleError::InvalidVersion => {
+                "The bundle version is not supported".to_string()
+            }
+            BundleError::InvalidIndex => {
+                "The bundle index is invalid".to_string()
+            }
+            BundleError::MissingEntryPoint(ref path) => {
+                format!(
+                    "The entry point {} is not included in the bundle",
+                    path
+                )
+            }
+        }
+    }
+}
+
+/// A collection of modules loaded from a bundle.
+pub struct Bundle {
+    /// The raw bytes of the bundle.
+    bytes: Vec<u8>,
+
+    /// The path of the module to run first.
+    entry_point: String,
+
+    /// The byte ranges of every module, keyed by their import paths.
+    index: HashMap<String, Range<usize>>,
+}
+
+impl Bundle {
+    /// Loads a bundle from a file.
+    pub fn from_file(path: &str) -> BundleResult<Self> {
+        let mut bytes = Vec::new();
+
+        File::open(path)
+            .and_then(|mut file| file.read_to_end(&mut bytes))
+            .map_err(|_| BundleError::InvalidFile(path.to_string()))?;
+
+        Bundle::from_bytes(bytes)
+    }
+
+    /// Loads the bundle embedded in an executable.
+    ///
+    /// If the executable does not contain a bundle, `None` is returned.
+    pub fn from_executable(path: &str) -> BundleResult<Option<Self>> {
+        File::open(path)
+            .and_then(|mut file| read_image(&mut file))
+            .map_err(|_| BundleError::InvalidFile(path.to_string()))?
+            .map_or(Ok(None), |bytes| Bundle::from_bytes(bytes).map(Some))
+    }
+
+    /// Loads a bundle from a Vec of bytes.
+    pub fn from_bytes(bytes: Vec<u8>) -> BundleResult<Self> {
+        if !is_bundle(&bytes) {
+            return Err(BundleError::InvalidSignature);
+        }
+
+        let mut position = SIGNATURE_BYTES.len();
+
+        if read_bytes(&bytes, &mut position, 1)?[0] != VERSION {
+            return Err(BundleError::InvalidVersion);
+        }
+
+        let entry_point = read_string(&bytes, &mut position)?;
+        let amount = read_u64(&bytes, &mut position)?;
+        let mut paths = Vec::new();
+
+        for _ in 0..amount {
+            let path = read_string(&bytes, &mut position)?;
+            let offset = read_u64(&bytes, &mut position)?;
+            let size = read_u64(&bytes, &mut position)?;
+
+            paths.push((path, offset, size));
+        }
+
+        let data_size = (bytes.len() - position) as u64;
+        let mut index = HashMap::new();
+
+        for (path, offset, size) in paths {
+            let end =
+                offset.checked_add(size).ok_or(BundleError::InvalidIndex)?;
+
+            if end > data_size {
+                return Err(BundleError::InvalidIndex);
+            }
+
+            let start = position + offset as usize;
+
+            index.insert(path, start..(start + size as usize));
+        }
+
+        if !index.contains_key(&entry_point) {
+            return Err(BundleError::MissingEntryPoint(entry_point));
+        }
+
+        Ok(Bundle {
+            bytes,
+            entry_point,
+            index,
+        })
+    }
+
+    /// Returns the path of the module to run first.
+    pub fn entry_point(&self) -> &str {
+        &self.entry_point
+    }
+
+    /// Returns true if the bundle contains a module for the given path.
+    pub fn contains(&self, path: &str) -> bool {
+        self.index.contains_key(path)
+    }
+
+    /// Returns the bytecode of the module for the given path.
+    pub fn module(&self, path: &str) -> Option<&[u8]> {
+        self.index
+            .get(path)
+            .map(|range| &self.bytes[range.start..range.end])
+    }
+}
+
+/// Returns true if the given bytes start with the bundle signature.
+pub fn is_bundle(bytes: &[u8]) -> bool {
+    bytes.starts_with(&SIGNATURE_BYTES)
+}
+
+/// Returns true if the given file is a bundle.
+pub fn is_bundle_file(path: &str) -> bool {
+    let mut signature = [0; 4];
+
+    File::open(path)
+        .and_then(|mut file| file.read_exact(&mut signature))
+        .map(|_| is_bundle(&signature))
+        .unwrap_or(false)
+}
+
+/// Returns the size of an executable, excluding any embedded bundle.
+pub fn runtime_size(bytes: &[u8]) -> usize {
+    read_image(&mut io::Cursor::new(bytes))
+        .ok()
+        .and_then(|image| image)
+        .map_or(bytes.len(), |image| {
+            bytes.len() - image.len() - IMAGE_TRAILER_SIZE as usize
+        })
+}
+
+/// Appends a bundle to the executable of the VM.
+///
+/// Any bundle already embedded in the executable is replaced.
+pub fn write_executable(runtime: &[u8], bundle: &[u8]) -> Vec<u8> {
+    let mut buffer = runtime[0..runtime_size(runtime)].to_vec();
+
+    buffer.extend_from_slice(bundle);
+
+    write_u64(&mut buffer, bundle.len() as u64);
+
+    buffer.extend_from_slice(&IMAGE_SIGNATURE_BYTES);
+    buffer
+}
+
+/// Writes a self-contained executable to `output`.
+///
+/// The executable is made up of the VM executable `runtime` and the bundle in
+/// `bundle`.
+pub fn create_executable(
+    runtime: &str,
+    bundle: &[u8],
+    output: &str,
+) -> BundleResult<()> {
+    let runtime_bytes = read_file(Path::new(runtime))?;
+    let bytes = write_executable(&runtime_bytes, bundle);
+
+    File::create(output)
+        .and_then(|mut file| {
+            file.write_all(&bytes)?;
+            make_executable(&file)
+        })
+        .map_err(|_| BundleError::InvalidFile(output.to_string()))
+}
+
+#[cfg(unix)]
+fn make_executable(file: &File) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    file.set_permissions(fs::Permissions::from_mode(0o755))
+}
+
+#[cfg(not(unix))]
+fn make_executable(_: &File) -> io::Result<()> {
+    Ok(())
+}
+
+/// Reads the bundle stored at the end of an executable, if there is any.
+fn read_image<R: Read + Seek>(reader: &mut R) -> io::Result<Option<Vec<u8>>> {
+    let size = reader.seek(SeekFrom::End(0))?;
+
+    if size < IMAGE_TRAILER_SIZE {
+        return Ok(None);
+    }
+
+    let mut trailer = [0; IMAGE_TRAILER_SIZE as usize];
+
+    reader.seek(SeekFrom::Start(size - IMAGE_TRAILER_SIZE))?;
+    reader.read_exact(&mut trailer)?;
+
+    if trailer[8..] != IMAGE_SIGNATURE_BYTES {
+        return Ok(None);
+    }
+
+    let bundle_size = read_u64(&trailer, &mut 0).unwrap_or(u64::max_value());
+
+    if bundle_size > size - IMAGE_TRAILER_SIZE {
+        return Ok(None);
+    }
+
+    let mut bytes = vec![0; bundle_size as usize];
+
+    reader.seek(SeekFrom::Start(size - IMAGE_TRAILER_SIZE - bundle_size))?;
+    reader.read_exact(&mut bytes)?;
+
+    Ok(Some(bytes))
+}
+
+/// Serializes a bundle containing the given modules.
+///
+/// The modules are given as pairs of import paths and bytecode.
+pub fn write(entry_point: &str, modules: &[(String, Vec<u8>)]) -> Vec<u8> {
+    let mut buffer = Vec::new();
+    let mut offset = 0;
+
+    buffer.extend_from_slice(&SIGNATURE_BYTES);
+    buffer.push(VERSION);
+
+    write_string(&mut buffer, entry_point);
+    write_u64(&mut buffer, modules.len() as u64);
+
+    for (path, bytes) in modules {
+        write_string(&mut buffer, path);
+        write_u64(&mut buffer, offset);
+        write_u64(&mut buffer, bytes.len() as u64);
+
+        offset += bytes.len() as u64;
+    }
+
+    for (_, bytes) in modules {
+        buffer.extend_from_slice(bytes);
+    }
+
+    buffer
+}
+
+/// Creates a bundle for a program.
+///
+/// The bundle contains the bytecode file `main`, which is used as the entry
+/// point, and all bytecode files found in the given directories. Modules found
+/// in the directories are keyed by their paths relative to these directories,
+/// matching the paths used to import them. If a module exists in multiple
+/// directories, the first one is used.
+pub fn bundle_program(
+    main: &str,
+    directories: &[PathBuf],
+) -> BundleResult<Vec<u8>> {
+    let main_path = Path::new(main);
+    let entry_point = main_path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| BundleError::InvalidFile(main.to_string()))?
+        .to_string();
+
+    let mut modules = vec![(entry_point.clone(), read_file(main_path)?)];
+
+    for directory in directories {
+        let mut files = Vec::new();
+
+        find_bytecode_files(directory, &mut files)?;
+
+        for file in files {
+            let import_path = import_path(directory, &file)?;
+
+            if modules.iter().any(|(path, _)| *path == import_path) {
+                continue;
+            }
+
+            modules.push((import_path, read_file(&file)?));
+        }
+    }
+
+    Ok(write(&entry_point, &modules))
+}
+
+fn find_bytecode_files(
+    directory: &Path,
+    files: &mut Vec<PathBuf>,
+) -> BundleResult<()> {
+    let invalid = || BundleError::InvalidFile(directory.display().to_string());
+    let mut entries = Vec::new();
+
+    for entry in fs::read_dir(directory).map_err(|_| invalid())? {
+        entries.push(entry.map_err(|_| invalid())?.path());
+    }
+
+    // Sorting the entries ensures bundles are created in a deterministic
+    // order.
+    entries.sort();
+
+    for path in entries {
+        if path.is_dir() {
+            find_bytecode_files(&path, files)?;
+        } else if path
+            .extension()
+            .map_or(false, |ext| ext == BYTECODE_EXTENSION)
+        {
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+fn import_path(directory: &Path, file: &Path) -> BundleResult<String> {
+    let relative = file
+        .strip_prefix(directory)
+        .map_err(|_| BundleError::InvalidFile(file.display().to_string()))?;
+
+    let components: Vec<&str> = relative
+        .iter()
+        .map(|component| component.to_str())
+        .collect::<Option<Vec<&str>>>()
+        .ok_or_else(|| BundleError::InvalidFile(file.display().to_string()))?;
+
+    Ok(components.join("/"))
+}
+
+fn read_file(path: &Path) -> BundleResult<Vec<u8>> {
+    let mut bytes = Vec::new();
+
+    File::open(path)
+        .and_then(|mut file| file.read_to_end(&mut bytes))
+        .map_err(|_| BundleError::InvalidFile(path.display().to_string()))?;
+
+    Ok(bytes)
+}
+
+fn read_bytes<'a>(
+    bytes: &'a [u8],
+    position: &mut usize,
+    amount: usize,
+) -> BundleResult<&'a [u8]> {
+    let end = position
+        .checked_add(amount)
+        .ok_or(BundleError::InvalidIndex)?;
+
+    let slice = bytes.get(*position..end).ok_or(BundleError::InvalidIndex)?;
+
+    *position = end;
+
+    Ok(slice)
+}
+
+fn read_u64(bytes: &[u8], position: &mut usize) -> BundleResult<u64> {
+    let slice = read_bytes(bytes, position, 8)?;
+
+    Ok(slice
+        .iter()
+        .fold(0, |value, byte| (value << 8) | u64::from(*byte)))
+}
+
+fn read_string(bytes: &[u8], position: &mut usize) -> BundleResult<String> {
+    let size = read_u64(bytes, position)?;
+
+    if size > bytes.len() as u64 {
+        return Err(BundleError::InvalidIndex);
+    }
+
+    let slice = read_bytes(bytes, position, size as usize)?;
+
+    str::from_utf8(slice)
+        .map(|string| string.to_string())
+        .map_err(|_| BundleError::InvalidIndex)
+}
+
+fn write_string(buffer: &mut Vec<u8>, value: &str) {
+    write_u64(buffer, value.len() as u64);
+    buffer.extend_from_slice(value.as_bytes());
+}
+
+fn write_u64(buffer: &mut Vec<u8>, value: u64) {
+    for shift in (0..8).rev() {
+        buffer.push((value >> (shift * 8)) as u8);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn modules() -> Vec<(String, Vec<u8>)> {
+        vec![
+            ("main.inkoc".to_string(), vec![1, 2, 3]),
+            ("std/foo.inkoc".to_string(), vec![4, 5]),
+            ("std/empty.inkoc".to_string(), Vec::new()),
+        ]
+    }
+
+    #[test]
+    fn test_write_and_from_bytes() {
+        let bundle =
+            Bundle::from_bytes(write("main.inkoc", &modules())).unwrap();
+
+        assert_eq!(bundle.entry_point(), "main.inkoc");
+        assert!(bundle.contains("std/foo.inkoc"));
+        assert!(!bundle.contains("std/bar.inkoc"));
+        assert_eq!(bundle.module("main.inkoc"), Some(&[1, 2, 3][..]));
+        assert_eq!(bundle.module("std/foo.inkoc"), Some(&[4, 5][..]));
+        assert_eq!(bundle.module("std/empty.inkoc"), Some(&[][..]));
+        assert_eq!(bundle.module("std/bar.inkoc"), None);
+    }
+
+    #[test]
+    fn test_from_bytes_invalid_signature() {
+        match Bundle::from_bytes(vec![105, 110, 107, 111, VERSION]) {
+            Err(BundleError::InvalidSignature) => {}
+            _ => panic!("expected an InvalidSignature error"),
+        }
+    }
+
+    #[test]
+    fn test_from_bytes_invalid_version() {
+        let mut bytes = write("main.inkoc", &modules());
+
+        bytes[4] = VERSION + 1;
+
+        match Bundle::from_bytes(bytes) {
+            Err(BundleError::InvalidVersion) => {}
+            _ => panic!("expected an InvalidVersion error"),
+        }
+    }
+
+    #[test]
+    fn test_from_bytes_truncated() {
+        let bytes = write("main.inkoc", &modules());
+
+        for length in 0..bytes.len() {
+            assert!(Bundle::from_bytes(bytes[0..length].to_vec()).is_err());
+        }
+    }
+
+    #[test]
+    fn test_from_bytes_missing_entry_point() {
+        match Bundle::from_bytes(write("foo.inkoc", &modules())) {
+            Err(BundleError::MissingEntryPoint(path)) => {
+                assert_eq!(path, "foo.inkoc".to_string());
+            }
+            _ => panic!("expected a MissingEntryPoint error"),
+        }
+    }
+
+    #[test]
+    fn test_write_executable() {
+        let bundle = write("main.inkoc", &modules());
+        let executable = write_executable(&[1, 2, 3], &bundle);
+        let image = read_image(&mut io::Cursor::new(&executable))
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(image, bundle);
+        assert_eq!(runtime_size(&executable), 3);
+        assert!(Bundle::from_bytes(image).unwrap().contains("main.inkoc"));
+    }
+
+    #[test]
+    fn test_write_executable_replaces_existing_bundle() {
+        let old_bundle = write("main.inkoc", &modules());
+        let new_bundle = write("std/foo.inkoc", &modules());
+        let executable = write_executable(
+            &write_executable(&[1, 2, 3], &old_bundle),
+            &new_bundle,
+        );
+
+        let image = read_image(&mut io::Cursor::new(&executable))
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(image, new_bundle);
+        assert_eq!(runtime_size(&executable), 3);
+    }
+
+    #[test]
+    fn test_read_image_without_bundle() {
+        let mut bytes = vec![1, 2, 3];
+
+        assert!(read_image(&mut io::Cursor::new(&bytes)).unwrap().is_none());
+
+        // A signature with a size larger than the executable is ignored.
+        write_u64(&mut bytes, 1024);
+        bytes.extend_from_slice(&IMAGE_SIGNATURE_BYTES);
+
+        assert!(read_image(&mut io::Cursor::new(&bytes)).unwrap().is_none());
+        assert_eq!(runtime_size(&bytes), bytes.len());
+    }
+
+    #[test]
+    fn test_is_bundle() {
+        assert!(is_bundle(&write("main.inkoc", &modules())));
+        assert!(!is_bundle(&[105, 110, 107, 111]));
+        assert!(!is_bundle(&[]));
+    }
+}
@@ -1,73 +1,60 @@
 //! A parser for Inko bytecode streams
 //!
 //! This module provides various functions that can be used for parsing Inko
-//! bytecode files provided as a stream of bytes.
+//! bytecode files provided as a slice of bytes.
 //!
-//! To parse a stream of bytes you can use the `parse` function:
+//! To parse a slice of bytes you can use the `parse_slice` function:
 //!
-//!     let mut bytes = File::open("path/to/file.inkoc").unwrap().bytes();
-//!     let result = bytecode_parser::parse(&mut bytes);
+//!     let bytes = fs::read("path/to/file.inkoc").unwrap();
+//!     let result = bytecode_parser::parse_slice(&state, &bytes);
 //!
 //! Alternatively you can also parse a file directly:
 //!
-//!     let result = bytecode_parser::parse_file("path/to/file.inkoc");
+//!     let result = bytecode_parser::parse_file(&state, "path/to/file.inkoc");
 
 use num_bigint::BigInt;
+use num_traits::Num;
+use std::convert::TryFrom;
 use std::f64;
 use std::fs::File;
 use std::io::prelude::*;
 use std::io::Bytes;
-use std::mem;
 use std::str;
-use num_traits::Num;
 
 use catch_table::{CatchEntry, CatchTable};
 use compiled_code::CompiledCode;
+use config::Config;
 use object_pointer::ObjectPointer;
 use vm::instruction::{Instruction, InstructionType};
 use vm::state::RcState;
 
 macro_rules! parser_error {
     ($variant:ident) => {
-        return Err(ParserError::$variant);
+        return Err(ParserErrorKind::$variant);
     };
 }
 
-macro_rules! try_byte {
-    ($expr:expr, $variant:ident) => {
-        match $expr {
-            Some(result) => match result {
-                Ok(byte) => byte,
-                Err(_) => parser_error!($variant),
-            },
+macro_rules! try_bytes {
+    ($bytes:expr, $amount:expr, $variant:ident) => {
+        match $bytes.take($amount) {
+            Some(slice) => slice,
             None => parser_error!($variant),
         }
     };
 }
 
-macro_rules! read_u16_vector {
-    ($byte_type:ident, $bytes:expr) => {
-        read_vector::<u16, $byte_type>($bytes, read_u16)?;
-    };
-}
-
-macro_rules! read_instruction_vector {
-    ($byte_type:ident, $bytes:expr) => {
-        read_vector::<Instruction, $byte_type>($bytes, read_instruction)?;
-    };
-}
-
-const SIGNATURE_BYTES: [u8; 4] = [105, 110, 107, 111]; // "inko"
+pub const SIGNATURE_BYTES: [u8; 4] = [105, 110, 107, 111]; // "inko"
 
-const VERSION: u8 = 2;
+pub const VERSION: u8 = 2;
 
-const LITERAL_INTEGER: u8 = 0;
-const LITERAL_FLOAT: u8 = 1;
-const LITERAL_STRING: u8 = 2;
-const LITERAL_BIGINT: u8 = 3;
+pub const LITERAL_INTEGER: u8 = 0;
+pub const LITERAL_FLOAT: u8 = 1;
+pub const LITERAL_STRING: u8 = 2;
+pub const LITERAL_BIGINT: u8 = 3;
 
+/// The kind of error produced when bytecode could not be parsed.
 #[derive(Debug)]
-pub enum ParserError {
+pub enum ParserErrorKind {
     InvalidFile,
     InvalidSignature,
     InvalidVersion,
@@ -80,11 +67,301 @@ pub enum ParserError {
     InvalidLiteralType(u8),
     MissingReturnInstruction(String, u16),
     MissingInstructions(String, u16),
+    InvalidInstruction(u8, String, u16),
+
+    /// A string or byte array is larger than the configured maximum.
+    StringTooLong(u64),
+
+    /// Code objects are nested deeper than the configured maximum.
+    CodeTooDeep(usize),
+
+    /// A code object defines more literals or arguments than the configured
+    /// maximum.
+    TooManyLiterals(u64),
+
+    /// A sequence claims to contain more values than the remaining input can
+    /// hold.
+    InvalidLength(u64),
+}
+
+/// A part of a bytecode file that is being read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Signature,
+    Version,
+    Name,
+    File,
+    Line,
+    Arguments,
+    Argument(usize),
+    RequiredArguments,
+    RestArgument,
+    Locals,
+    Registers,
+    Captures,
+    Instructions,
+    Instruction(usize),
+    Literals,
+    Literal(usize),
+    CodeObjects,
+    CatchTable,
+    CatchEntry(usize),
+}
+
+/// An error produced when bytecode could not be parsed, along with the
+/// location the error occurred at.
+#[derive(Debug)]
+pub struct ParserError {
+    pub kind: ParserErrorKind,
+
+    /// The byte offset of the field that could not be parsed.
+    pub offset: usize,
+
+    /// The field that could not be parsed, if any.
+    pub field: Option<Field>,
+
+    /// The names of the code objects that were being parsed, starting with
+    /// the outermost code object.
+    pub code_objects: Vec<String>,
 }
 
 pub type ParserResult<T> = Result<T, ParserError>;
 pub type BytecodeResult = ParserResult<CompiledCode>;
 
+/// The result of reading a single value, before any location information is
+/// added to an error.
+type ReadResult<T> = Result<T, ParserErrorKind>;
+
+impl ParserErrorKind {
+    /// Returns a human friendly description of the error.
+    pub fn message(&self) -> String {
+        match *self {
+            ParserErrorKind::InvalidFile => {
+                "The file could not be read".to_string()
+            }
+            ParserErrorKind::InvalidSignature => {
+                "The file is not an Inko bytecode file".to_string()
+            }
+            ParserErrorKind::InvalidVersion => {
+                "The bytecode version is not supported".to_string()
+            }
+            ParserErrorKind::InvalidString => {
+                "The string is truncated or not valid UTF-8".to_string()
+            }
+            ParserErrorKind::InvalidByteArray => {
+                "The byte array is truncated".to_string()
+            }
+            ParserErrorKind::InvalidInteger => {
+                "The integer is truncated".to_string()
+            }
+            ParserErrorKind::InvalidBigInteger => {
+                "The big integer is not a valid hexadecimal number".to_string()
+            }
+            ParserErrorKind::InvalidFloat => {
+                "The float is truncated".to_string()
+            }
+            ParserErrorKind::MissingByte => "A byte is missing".to_string(),
+            ParserErrorKind::InvalidLiteralType(literal_type) => {
+                format!("{} is not a valid literal type", literal_type)
+            }
+            ParserErrorKind::MissingReturnInstruction(ref file, line) => {
+                format!(
+                    "The code object defined in {}:{} does not end with a \
+                     return instruction",
+                    file, line
+                )
+            }
+            ParserErrorKind::MissingInstructions(ref file, line) => {
+                format!(
+                    "The code object defined in {}:{} does not contain any \
+                     instructions",
+                    file, line
+                )
+            }
+            ParserErrorKind::InvalidInstruction(byte, ref file, line) => {
+                format!(
+                    "{} is not a valid instruction type ({}:{})",
+                    byte, file, line
+                )
+            }
+            ParserErrorKind::StringTooLong(size) => format!(
+                "The size of {} bytes exceeds the maximum string size",
+                size
+            ),
+            ParserErrorKind::CodeTooDeep(depth) => format!(
+                "Code objects are nested more than {} levels deep",
+                depth
+            ),
+            ParserErrorKind::TooManyLiterals(amount) => format!(
+                "The number of literals ({}) exceeds the maximum",
+                amount
+            ),
+            ParserErrorKind::InvalidLength(amount) => format!(
+                "A sequence of {} values does not fit in the remaining input",
+                amount
+            ),
+        }
+    }
+}
+
+impl Field {
+    /// Returns a human friendly description of the field.
+    pub fn describe(&self) -> String {
+        match *self {
+            Field::Signature => "the signature".to_string(),
+            Field::Version => "the version".to_string(),
+            Field::Name => "the name".to_string(),
+            Field::File => "the file path".to_string(),
+            Field::Line => "the line number".to_string(),
+            Field::Arguments => "the number of arguments".to_string(),
+            Field::Argument(index) => format!("argument {}", index),
+            Field::RequiredArguments => {
+                "the number of required arguments".to_string()
+            }
+            Field::RestArgument => "the rest argument flag".to_string(),
+            Field::Locals => "the number of local variables".to_string(),
+            Field::Registers => "the number of registers".to_string(),
+            Field::Captures => "the captures flag".to_string(),
+            Field::Instructions => "the number of instructions".to_string(),
+            Field::Instruction(index) => format!("instruction {}", index),
+            Field::Literals => "the number of literals".to_string(),
+            Field::Literal(index) => format!("literal {}", index),
+            Field::CodeObjects => "the number of code objects".to_string(),
+            Field::CatchTable => {
+                "the number of catch table entries".to_string()
+            }
+            Field::CatchEntry(index) => format!("catch table entry {}", index),
+        }
+    }
+}
+
+impl ParserError {
+    fn new(kind: ParserErrorKind) -> Self {
+        ParserError {
+            kind,
+            offset: 0,
+            field: None,
+            code_objects: Vec::new(),
+        }
+    }
+
+    /// Returns a description of where the error occurred, such as "literal 3
+    /// of code object `foo` in `bar`".
+    pub fn location(&self) -> String {
+        let mut location = match self.field {
+            Some(field) => field.describe(),
+            None => "the file".to_string(),
+        };
+
+        for (index, name) in self.code_objects.iter().rev().enumerate() {
+            let name = if name.is_empty() { "<unknown>" } else { name };
+
+            if index == 0 {
+                location.push_str(&format!(" of code object `{}`", name));
+            } else {
+                location.push_str(&format!(" in `{}`", name));
+            }
+        }
+
+        location
+    }
+
+    /// Returns a human friendly, multi-line error message.
+    pub fn message(&self) -> String {
+        if self.field.is_none() {
+            return self.kind.message();
+        }
+
+        format!(
+            "{}\nLocation: {}\nByte offset: {}",
+            self.kind.message(),
+            self.location(),
+            self.offset
+        )
+    }
+}
+
+/// The minimum number of bytes used by a single instruction.
+const MIN_INSTRUCTION_SIZE: usize = 11;
+
+/// The minimum number of bytes used by a single literal.
+const MIN_LITERAL_SIZE: usize = 9;
+
+/// The minimum number of bytes used by a single code object.
+const MIN_CODE_SIZE: usize = 76;
+
+/// The number of bytes used by a single catch table entry.
+const CATCH_ENTRY_SIZE: usize = 8;
+
+/// A cursor over a slice of bytes that are being parsed.
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+
+    /// The field that is being read.
+    field: Option<Field>,
+
+    /// The offset at which the current field starts.
+    field_offset: usize,
+
+    /// The names of the code objects that are being read.
+    code_objects: Vec<String>,
+
+    /// The nesting depth of the code object that is being parsed.
+    depth: usize,
+
+    max_string_length: usize,
+    max_code_depth: usize,
+    max_literals: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8], config: &Config) -> Self {
+        Reader {
+            bytes,
+            position: 0,
+            field: None,
+            field_offset: 0,
+            code_objects: Vec::new(),
+            depth: 0,
+            max_string_length: config.bytecode_max_string_length,
+            max_code_depth: config.bytecode_max_code_depth,
+            max_literals: config.bytecode_max_literals,
+        }
+    }
+
+    /// Marks the start of a new field.
+    fn field(&mut self, field: Field) {
+        self.field = Some(field);
+        self.field_offset = self.position;
+    }
+
+    /// Produces a ParserError for the current position.
+    fn error(&self, kind: ParserErrorKind) -> ParserError {
+        ParserError {
+            kind,
+            offset: self.field_offset,
+            field: self.field,
+            code_objects: self.code_objects.clone(),
+        }
+    }
+
+    /// Returns the number of bytes that have yet to be parsed.
+    fn remaining(&self) -> usize {
+        self.bytes.len() - self.position
+    }
+
+    /// Returns the next `amount` bytes, or None if not enough bytes remain.
+    fn take(&mut self, amount: usize) -> Option<&'a [u8]> {
+        let end = self.position.checked_add(amount)?;
+        let slice = self.bytes.get(self.position..end)?;
+
+        self.position = end;
+
+        Some(slice)
+    }
+}
+
 /// Parses a file
 ///
 /// # Examples
@@ -92,155 +369,184 @@ pub type BytecodeResult = ParserResult<CompiledCode>;
 ///     let state = State::new(Config::new());
 ///     let result = bytecode_parser::parse_file(&state, "path/to/file.inkoc");
 pub fn parse_file(state: &RcState, path: &str) -> BytecodeResult {
-    match File::open(path) {
-        Ok(file) => parse(state, &mut file.bytes()),
-        Err(_) => parser_error!(InvalidFile),
-    }
+    let mut bytes = Vec::new();
+
+    File::open(path)
+        .and_then(|mut file| file.read_to_end(&mut bytes))
+        .map_err(|_| ParserError::new(ParserErrorKind::InvalidFile))?;
+
+    parse_slice(state, &bytes)
 }
 
 /// Parses a stream of bytes
 ///
+/// The stream is read into memory before parsing it. When the bytes are
+/// already available in memory, `parse_slice` should be used instead.
+///
 /// # Examples
 ///
 ///     let mut bytes = File::open("path/to/file.inkoc").unwrap().bytes();
 ///     let state = State::new(Config::new());
 ///     let result = bytecode_parser::parse(&state, &mut bytes);
 pub fn parse<T: Read>(state: &RcState, bytes: &mut Bytes<T>) -> BytecodeResult {
-    // Verify the bytecode signature.
-    for expected in &SIGNATURE_BYTES {
-        let byte = try_byte!(bytes.next(), InvalidSignature);
-
-        if byte != *expected {
-            parser_error!(InvalidSignature);
-        }
-    }
+    let buffer = bytes
+        .collect::<Result<Vec<u8>, _>>()
+        .map_err(|_| ParserError::new(ParserErrorKind::InvalidFile))?;
 
-    // Verify the version
-    if try_byte!(bytes.next(), InvalidVersion) != VERSION {
-        parser_error!(InvalidVersion);
-    }
+    parse_slice(state, &buffer)
+}
 
-    let code = read_compiled_code(state, bytes)?;
+/// Parses a slice of bytes
+///
+/// # Examples
+///
+///     let state = State::new(Config::new());
+///     let result = bytecode_parser::parse_slice(&state, &[105, 110, 107]);
+pub fn parse_slice(state: &RcState, bytes: &[u8]) -> BytecodeResult {
+    let mut reader = Reader::new(bytes, &state.config);
 
-    Ok(code)
+    read_bytecode(state, &mut reader).map_err(|kind| reader.error(kind))
 }
 
-fn read_string<T: Read>(bytes: &mut Bytes<T>) -> ParserResult<String> {
-    let size = read_u64(bytes)?;
-    let mut buff: Vec<u8> = Vec::with_capacity(size as usize);
+fn read_bytecode(
+    state: &RcState,
+    reader: &mut Reader,
+) -> ReadResult<CompiledCode> {
+    // Verify the bytecode signature.
+    reader.field(Field::Signature);
 
-    for _ in 0..size {
-        buff.push(try_byte!(bytes.next(), InvalidString));
+    if try_bytes!(reader, SIGNATURE_BYTES.len(), InvalidSignature)
+        != &SIGNATURE_BYTES[..]
+    {
+        parser_error!(InvalidSignature);
     }
 
-    match String::from_utf8(buff) {
-        Ok(string) => Ok(string),
-        Err(_) => parser_error!(InvalidString),
+    // Verify the version
+    reader.field(Field::Version);
+
+    if try_bytes!(reader, 1, InvalidVersion)[0] != VERSION {
+        parser_error!(InvalidVersion);
     }
+
+    read_compiled_code(state, reader)
 }
 
-fn read_byte_array<T: Read>(bytes: &mut Bytes<T>) -> ParserResult<Vec<u8>> {
-    let size = read_u64(bytes)?;
-    let mut buff: Vec<u8> = Vec::with_capacity(size as usize);
+fn read_string(bytes: &mut Reader) -> ReadResult<String> {
+    let size = read_string_length(bytes)?;
+    let slice = try_bytes!(bytes, size, InvalidString);
 
-    for _ in 0..size {
-        buff.push(try_byte!(bytes.next(), InvalidByteArray));
+    match str::from_utf8(slice) {
+        Ok(string) => Ok(string.to_string()),
+        Err(_) => parser_error!(InvalidString),
     }
-
-    Ok(buff)
 }
 
-fn read_u8<T: Read>(bytes: &mut Bytes<T>) -> ParserResult<u8> {
-    let byte = try_byte!(bytes.next(), InvalidInteger);
+fn read_byte_array(bytes: &mut Reader) -> ReadResult<Vec<u8>> {
+    let size = read_string_length(bytes)?;
 
-    let value: u8 = unsafe { mem::transmute([byte]) };
-
-    Ok(u8::from_be(value))
+    Ok(try_bytes!(bytes, size, InvalidByteArray).to_vec())
 }
 
-fn read_bool<T: Read>(bytes: &mut Bytes<T>) -> ParserResult<bool> {
-    let byte = try_byte!(bytes.next(), InvalidInteger);
+/// Reads the size of a string or byte array.
+fn read_string_length(bytes: &mut Reader) -> ReadResult<usize> {
+    let size = read_u64(bytes)?;
 
-    let value: u8 = unsafe { mem::transmute([byte]) };
+    if size > bytes.max_string_length as u64 {
+        return Err(ParserErrorKind::StringTooLong(size));
+    }
 
-    Ok(u8::from_be(value) == 1)
+    Ok(size as usize)
 }
 
-#[cfg_attr(feature = "cargo-clippy", allow(needless_range_loop))]
-fn read_u16<T: Read>(bytes: &mut Bytes<T>) -> ParserResult<u16> {
-    let mut buff: [u8; 2] = [0, 0];
+/// Reads the number of values in a sequence, ensuring the remaining input is
+/// large enough to contain that many values of at least `size` bytes.
+///
+/// This prevents allocating memory for values that can not possibly exist.
+fn read_length(bytes: &mut Reader, size: usize) -> ReadResult<usize> {
+    let amount = read_u64(bytes)?;
 
-    for index in 0..2 {
-        buff[index] = try_byte!(bytes.next(), InvalidInteger);
+    if amount > (bytes.remaining() / size) as u64 {
+        return Err(ParserErrorKind::InvalidLength(amount));
     }
 
-    let value: u16 = unsafe { mem::transmute(buff) };
+    Ok(amount as usize)
+}
 
-    Ok(u16::from_be(value))
+fn read_u8(bytes: &mut Reader) -> ReadResult<u8> {
+    Ok(try_bytes!(bytes, 1, InvalidInteger)[0])
 }
 
-#[cfg_attr(feature = "cargo-clippy", allow(needless_range_loop))]
-fn read_u16_as_usize<T: Read>(bytes: &mut Bytes<T>) -> ParserResult<usize> {
-    let mut buff: [u8; 2] = [0, 0];
+fn read_bool(bytes: &mut Reader) -> ReadResult<bool> {
+    Ok(read_u8(bytes)? == 1)
+}
 
-    for index in 0..2 {
-        buff[index] = try_byte!(bytes.next(), InvalidInteger);
-    }
+fn read_u16(bytes: &mut Reader) -> ReadResult<u16> {
+    let slice = try_bytes!(bytes, 2, InvalidInteger);
 
-    let value: u16 = unsafe { mem::transmute(buff) };
+    Ok(u16_from_slice(slice))
+}
 
-    Ok(u16::from_be(value) as usize)
+fn read_u16_as_usize(bytes: &mut Reader) -> ReadResult<usize> {
+    Ok(read_u16(bytes)? as usize)
 }
 
-#[cfg_attr(feature = "cargo-clippy", allow(needless_range_loop))]
-fn read_i64<T: Read>(bytes: &mut Bytes<T>) -> ParserResult<i64> {
-    let mut buff: [u8; 8] = [0, 0, 0, 0, 0, 0, 0, 0];
+fn read_u16_vector(bytes: &mut Reader) -> ReadResult<Vec<u16>> {
+    let amount = read_length(bytes, 2)?;
+    let slice = try_bytes!(bytes, amount * 2, InvalidInteger);
 
-    for index in 0..8 {
-        buff[index] = try_byte!(bytes.next(), InvalidInteger);
-    }
+    Ok(slice.chunks(2).map(u16_from_slice).collect())
+}
 
-    let value: i64 = unsafe { mem::transmute(buff) };
+fn read_i64(bytes: &mut Reader) -> ReadResult<i64> {
+    let slice = try_bytes!(bytes, 8, InvalidInteger);
 
-    Ok(i64::from_be(value))
+    Ok(u64_from_slice(slice) as i64)
 }
 
-fn read_u64<T: Read>(bytes: &mut Bytes<T>) -> ParserResult<u64> {
+fn read_u64(bytes: &mut Reader) -> ReadResult<u64> {
     Ok(read_i64(bytes)? as u64)
 }
 
-#[cfg_attr(feature = "cargo-clippy", allow(needless_range_loop))]
-fn read_f64<T: Read>(bytes: &mut Bytes<T>) -> ParserResult<f64> {
-    let mut buff: [u8; 8] = [0, 0, 0, 0, 0, 0, 0, 0];
+fn read_f64(bytes: &mut Reader) -> ReadResult<f64> {
+    let slice = try_bytes!(bytes, 8, InvalidFloat);
 
-    for index in 0..8 {
-        buff[index] = try_byte!(bytes.next(), InvalidFloat);
-    }
+    Ok(f64::from_bits(u64_from_slice(slice)))
+}
 
-    let int: u64 = u64::from_be(unsafe { mem::transmute(buff) });
+fn u16_from_slice(slice: &[u8]) -> u16 {
+    (u16::from(slice[0]) << 8) | u16::from(slice[1])
+}
 
-    Ok(f64::from_bits(int))
+fn u64_from_slice(slice: &[u8]) -> u64 {
+    slice
+        .iter()
+        .fold(0, |value, byte| (value << 8) | u64::from(*byte))
 }
 
-fn read_vector<V, T: Read>(
-    bytes: &mut Bytes<T>,
-    reader: fn(&mut Bytes<T>) -> ParserResult<V>,
-) -> ParserResult<Vec<V>> {
-    let amount = read_u64(bytes)? as usize;
+fn read_vector<V>(
+    bytes: &mut Reader,
+    size: usize,
+    field: fn(usize) -> Field,
+    reader: fn(&mut Reader) -> ReadResult<V>,
+) -> ReadResult<Vec<V>> {
+    let amount = read_length(bytes, size)?;
     let mut buff: Vec<V> = Vec::with_capacity(amount);
 
-    for _ in 0..amount {
-        buff.push(reader(bytes)? as V);
+    for index in 0..amount {
+        bytes.field(field(index));
+        buff.push(reader(bytes)?);
     }
 
     Ok(buff)
 }
 
-fn read_code_vector<T: Read>(
+fn read_code_vector(
     state: &RcState,
-    bytes: &mut Bytes<T>,
-) -> ParserResult<Vec<CompiledCode>> {
-    let amount = read_u64(bytes)? as usize;
+    bytes: &mut Reader,
+) -> ReadResult<Vec<CompiledCode>> {
+    bytes.field(Field::CodeObjects);
+
+    let amount = read_length(bytes, MIN_CODE_SIZE)?;
     let mut buff = Vec::with_capacity(amount);
 
     for _ in 0..amount {
@@ -250,52 +556,111 @@ fn read_code_vector<T: Read>(
     Ok(buff)
 }
 
-fn read_instruction<T: Read>(
-    bytes: &mut Bytes<T>,
-) -> ParserResult<Instruction> {
-    let ins_type: InstructionType = unsafe { mem::transmute(read_u8(bytes)?) };
+fn read_instructions(
+    bytes: &mut Reader,
+    file: &str,
+) -> ReadResult<Vec<Instruction>> {
+    bytes.field(Field::Instructions);
+
+    let amount = read_length(bytes, MIN_INSTRUCTION_SIZE)?;
+    let mut buff = Vec::with_capacity(amount);
+
+    for index in 0..amount {
+        bytes.field(Field::Instruction(index));
+        buff.push(read_instruction(bytes, file)?);
+    }
+
+    Ok(buff)
+}
 
-    let args = read_u16_vector!(T, bytes);
+fn read_instruction(bytes: &mut Reader, file: &str) -> ReadResult<Instruction> {
+    let byte = read_u8(bytes)?;
+    let args = read_u16_vector(bytes)?;
     let line = read_u16(bytes)?;
-    let ins = Instruction::new(ins_type, args, line);
 
-    Ok(ins)
+    let ins_type = InstructionType::try_from(byte).map_err(|byte| {
+        ParserErrorKind::InvalidInstruction(byte, file.to_string(), line)
+    })?;
+
+    Ok(Instruction::new(ins_type, args, line))
 }
 
-fn read_compiled_code<T: Read>(
+fn read_compiled_code(
     state: &RcState,
-    bytes: &mut Bytes<T>,
-) -> ParserResult<CompiledCode> {
+    bytes: &mut Reader,
+) -> ReadResult<CompiledCode> {
+    bytes.depth += 1;
+
+    if bytes.depth > bytes.max_code_depth {
+        return Err(ParserErrorKind::CodeTooDeep(bytes.max_code_depth));
+    }
+
+    // The name is filled in once read, ensuring errors produced while reading
+    // the name still include the enclosing code objects.
+    bytes.code_objects.push(String::new());
+    bytes.field(Field::Name);
+
     let name_string = read_string(bytes)?;
+
+    if let Some(name) = bytes.code_objects.last_mut() {
+        name.push_str(&name_string);
+    }
+
+    bytes.field(Field::File);
+
     let file_string = read_string(bytes)?;
+
+    bytes.field(Field::Line);
+
     let line = read_u16(bytes)?;
-    let args = read_literals_vector(state, bytes)?;
+    let args =
+        read_literals_vector(state, bytes, Field::Arguments, Field::Argument)?;
+
+    bytes.field(Field::RequiredArguments);
+
     let req_args = read_u8(bytes)?;
+
+    bytes.field(Field::RestArgument);
+
     let rest_arg = read_bool(bytes)?;
+
+    bytes.field(Field::Locals);
+
     let locals = read_u16(bytes)?;
+
+    bytes.field(Field::Registers);
+
     let registers = read_u16(bytes)?;
+
+    bytes.field(Field::Captures);
+
     let captures = read_bool(bytes)?;
-    let instructions = read_instruction_vector!(T, bytes);
+    let instructions = read_instructions(bytes, &file_string)?;
 
     // Make sure we always have a return at the end.
     if let Some(ins) = instructions.last() {
         match ins.instruction_type {
             InstructionType::Return | InstructionType::Throw => {}
             _ => {
-                return Err(ParserError::MissingReturnInstruction(
+                return Err(ParserErrorKind::MissingReturnInstruction(
                     file_string,
                     line,
                 ))
             }
         };
     } else {
-        return Err(ParserError::MissingInstructions(file_string, line));
+        return Err(ParserErrorKind::MissingInstructions(file_string, line));
     }
 
-    let literals = read_literals_vector(state, bytes)?;
+    let literals =
+        read_literals_vector(state, bytes, Field::Literals, Field::Literal)?;
+
     let code_objects = read_code_vector(state, bytes)?;
     let catch_table = read_catch_table(bytes)?;
 
+    bytes.depth -= 1;
+    bytes.code_objects.pop();
+
     Ok(CompiledCode {
         name: state.intern_owned(name_string),
         file: state.intern_owned(file_string),
@@ -313,24 +678,34 @@ fn read_compiled_code<T: Read>(
     })
 }
 
-fn read_literals_vector<T: Read>(
+fn read_literals_vector(
     state: &RcState,
-    bytes: &mut Bytes<T>,
-) -> ParserResult<Vec<ObjectPointer>> {
-    let amount = read_u64(bytes)?;
-    let mut buff = Vec::with_capacity(amount as usize);
+    bytes: &mut Reader,
+    amount_field: Field,
+    field: fn(usize) -> Field,
+) -> ReadResult<Vec<ObjectPointer>> {
+    bytes.field(amount_field);
 
-    for _ in 0..amount {
+    let amount = read_length(bytes, MIN_LITERAL_SIZE)?;
+
+    if amount > bytes.max_literals {
+        return Err(ParserErrorKind::TooManyLiterals(amount as u64));
+    }
+
+    let mut buff = Vec::with_capacity(amount);
+
+    for index in 0..amount {
+        bytes.field(field(index));
         buff.push(read_literal(state, bytes)?);
     }
 
     Ok(buff)
 }
 
-fn read_literal<T: Read>(
+fn read_literal(
     state: &RcState,
-    bytes: &mut Bytes<T>,
-) -> ParserResult<ObjectPointer> {
+    bytes: &mut Reader,
+) -> ReadResult<ObjectPointer> {
     let literal_type = read_u8(bytes)?;
 
     let literal = match literal_type {
@@ -346,32 +721,35 @@ fn read_literal<T: Read>(
         LITERAL_BIGINT => {
             let bytes = read_byte_array(bytes)?;
             let slice = str::from_utf8(&bytes)
-                .map_err(|_| ParserError::InvalidBigInteger)?;
+                .map_err(|_| ParserErrorKind::InvalidBigInteger)?;
 
-            let bigint = BigInt::from_str_radix(slice, 16).unwrap();
+            let bigint = BigInt::from_str_radix(slice, 16)
+                .map_err(|_| ParserErrorKind::InvalidBigInteger)?;
 
             state.allocate_permanent_bigint(bigint)
         }
         LITERAL_FLOAT => state.allocate_permanent_float(read_f64(bytes)?),
         LITERAL_STRING => state.intern_owned(read_string(bytes)?),
-        _ => return Err(ParserError::InvalidLiteralType(literal_type)),
+        _ => return Err(ParserErrorKind::InvalidLiteralType(literal_type)),
     };
 
     Ok(literal)
 }
 
-fn read_catch_table<T: Read>(bytes: &mut Bytes<T>) -> ParserResult<CatchTable> {
-    let amount = read_u64(bytes)? as usize;
-    let mut entries = Vec::with_capacity(amount);
+fn read_catch_table(bytes: &mut Reader) -> ReadResult<CatchTable> {
+    bytes.field(Field::CatchTable);
 
-    for _ in 0..amount {
-        entries.push(read_catch_entry(bytes)?);
-    }
+    let entries = read_vector(
+        bytes,
+        CATCH_ENTRY_SIZE,
+        Field::CatchEntry,
+        read_catch_entry,
+    )?;
 
     Ok(CatchTable { entries })
 }
 
-fn read_catch_entry<T: Read>(bytes: &mut Bytes<T>) -> ParserResult<CatchEntry> {
+fn read_catch_entry(bytes: &mut Reader) -> ReadResult<CatchEntry> {
     let start = read_u16_as_usize(bytes)?;
     let end = read_u16_as_usize(bytes)?;
     let jump_to = read_u16_as_usize(bytes)?;
@@ -385,6 +763,7 @@ mod tests {
     use super::*;
     use config::Config;
     use std::mem;
+    use std::u64;
     use vm::instruction::InstructionType;
     use vm::state::{RcState, State};
 
@@ -392,6 +771,10 @@ mod tests {
         State::new(Config::new())
     }
 
+    fn reader(buffer: &[u8]) -> Reader {
+        Reader::new(buffer, &Config::new())
+    }
+
     macro_rules! unwrap {
         ($expr:expr) => {{
             match $expr {
@@ -403,7 +786,7 @@ mod tests {
 
     macro_rules! read {
         ($name:ident, $buffer:expr) => {
-            $name(&mut $buffer.bytes())
+            $name(&mut reader(&$buffer))
         };
     }
 
@@ -531,6 +914,48 @@ mod tests {
         assert_eq!(object.line, 4);
     }
 
+    #[test]
+    fn test_parse_slice() {
+        let mut buffer = Vec::new();
+        let state = state();
+
+        buffer.extend_from_slice(&SIGNATURE_BYTES);
+        buffer.push(VERSION);
+
+        pack_string!("main", buffer);
+        pack_string!("test.inko", buffer);
+        pack_u16!(4, buffer); // line
+        pack_u64!(0, buffer); // arguments
+        pack_u8!(0, buffer); // required arguments
+        pack_u8!(0, buffer); // rest argument
+        pack_u16!(0, buffer); // locals
+        pack_u16!(0, buffer); // registers
+        pack_u8!(0, buffer); // captures
+
+        pack_u64!(1, buffer); // instructions
+
+        pack_u8!(InstructionType::Return as u8, buffer);
+        pack_u64!(0, buffer); // args count
+        pack_u16!(2, buffer); // line number
+
+        pack_u64!(0, buffer); // literals
+        pack_u64!(0, buffer); // code objects
+        pack_u64!(0, buffer); // catch table entries
+
+        let object = unwrap!(parse_slice(&state, &buffer));
+
+        assert_eq!(*object.name.string_value().unwrap(), "main".to_string());
+        assert_eq!(object.instructions.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_slice_truncated() {
+        let state = state();
+        let buffer = [105, 110, 107, 111, VERSION, 0, 0];
+
+        assert!(parse_slice(&state, &buffer).is_err());
+    }
+
     #[test]
     fn test_read_string() {
         let mut buffer = Vec::new();
@@ -623,6 +1048,32 @@ mod tests {
         assert!(output.is_err());
     }
 
+    #[test]
+    fn test_read_u16_vector() {
+        let mut buffer = Vec::new();
+
+        pack_u64!(3, buffer);
+        pack_u16!(1, buffer);
+        pack_u16!(256, buffer);
+        pack_u16!(65535, buffer);
+
+        let output = unwrap!(read!(read_u16_vector, buffer));
+
+        assert_eq!(output, vec![1, 256, 65535]);
+    }
+
+    #[test]
+    fn test_read_u16_vector_too_short() {
+        let mut buffer = Vec::new();
+
+        pack_u64!(2, buffer);
+        pack_u16!(1, buffer);
+
+        let output = read!(read_u16_vector, buffer);
+
+        assert!(output.is_err());
+    }
+
     #[test]
     fn test_read_i64() {
         let mut buffer = Vec::new();
@@ -678,8 +1129,10 @@ mod tests {
         pack_string!("hello", buffer);
         pack_string!("world", buffer);
 
-        let output = unwrap!(read_vector::<String, &[u8]>(
-            &mut buffer.bytes(),
+        let output = unwrap!(read_vector::<String>(
+            &mut reader(&buffer),
+            8,
+            Field::Literal,
             read_string,
         ));
 
@@ -691,8 +1144,12 @@ mod tests {
     #[test]
     fn test_read_vector_empty() {
         let buffer = Vec::new();
-        let output =
-            read_vector::<String, &[u8]>(&mut buffer.bytes(), read_string);
+        let output = read_vector::<String>(
+            &mut reader(&buffer),
+            8,
+            Field::Literal,
+            read_string,
+        );
 
         assert!(output.is_err());
     }
@@ -706,13 +1163,31 @@ mod tests {
         pack_u16!(6, buffer);
         pack_u16!(2, buffer); // line
 
-        let ins = unwrap!(read_instruction(&mut buffer.bytes()));
+        let ins = unwrap!(read_instruction(&mut reader(&buffer), "foo.inko"));
 
         assert_eq!(ins.instruction_type, InstructionType::SetLiteral);
         assert_eq!(ins.arguments[0], 6);
         assert_eq!(ins.line, 2);
     }
 
+    #[test]
+    fn test_read_instruction_invalid_type() {
+        let mut buffer = Vec::new();
+
+        pack_u8!(255, buffer); // type
+        pack_u64!(0, buffer); // args
+        pack_u16!(4, buffer); // line
+
+        let result = read_instruction(&mut reader(&buffer), "foo.inko");
+
+        match result {
+            Err(ParserErrorKind::InvalidInstruction(255, file, 4)) => {
+                assert_eq!(file, "foo.inko".to_string());
+            }
+            _ => panic!("expected an InvalidInstruction error"),
+        }
+    }
+
     #[test]
     fn test_read_compiled_code() {
         let mut buffer = Vec::new();
@@ -780,7 +1255,7 @@ mod tests {
         pack_u16!(8, buffer); // jump-to
         pack_u16!(10, buffer); // register
 
-        let object = unwrap!(read_compiled_code(&state, &mut buffer.bytes()));
+        let object = unwrap!(read_compiled_code(&state, &mut reader(&buffer)));
 
         assert_eq!(*object.name.string_value().unwrap(), "main".to_string());
         assert_eq!(
@@ -856,7 +1331,7 @@ mod tests {
         // catch table entries
         pack_u64!(0, buffer);
 
-        assert!(read_compiled_code(&state, &mut buffer.bytes()).is_err());
+        assert!(read_compiled_code(&state, &mut reader(&buffer)).is_err());
     }
 
     #[test]
@@ -886,6 +1361,281 @@ mod tests {
         // catch table entries
         pack_u64!(0, buffer);
 
-        assert!(read_compiled_code(&state, &mut buffer.bytes()).is_err());
+        assert!(read_compiled_code(&state, &mut reader(&buffer)).is_err());
+    }
+
+    fn pack_code_header(buffer: &mut Vec<u8>, name: &str) {
+        pack_string!(name, buffer); // name
+        pack_string!("test.inko", buffer); // file
+        pack_u16!(4, buffer); // line
+        pack_u64!(0, buffer); // arguments
+        pack_u8!(0, buffer); // required args
+        pack_u8!(0, buffer); // rest argument
+        pack_u16!(0, buffer); // locals
+        pack_u16!(1, buffer); // registers
+        pack_u8!(0, buffer); // captures
+
+        // instructions
+        pack_u64!(1, buffer);
+        pack_u8!(InstructionType::Return as u8, buffer); // type
+        pack_u64!(0, buffer); // args count
+        pack_u16!(2, buffer); // line number
+    }
+
+    fn pack_code(buffer: &mut Vec<u8>, depth: usize) {
+        pack_code_header(buffer, "main");
+
+        // literals
+        pack_u64!(2, buffer);
+        pack_u8!(LITERAL_STRING, buffer);
+        pack_string!("foo", buffer);
+        pack_u8!(LITERAL_BIGINT, buffer);
+        pack_string!("-ff", buffer);
+
+        // code objects
+        if depth > 1 {
+            pack_u64!(1, buffer);
+            pack_code(buffer, depth - 1);
+        } else {
+            pack_u64!(0, buffer);
+        }
+
+        // catch table entries
+        pack_u64!(0, buffer);
+    }
+
+    #[test]
+    fn test_read_string_too_long() {
+        let mut buffer = Vec::new();
+        let mut config = Config::new();
+
+        config.bytecode_max_string_length = 2;
+
+        pack_string!("inko", buffer);
+
+        let output = read_string(&mut Reader::new(&buffer, &config));
+
+        match output {
+            Err(ParserErrorKind::StringTooLong(4)) => {}
+            _ => panic!("expected a StringTooLong error"),
+        }
+    }
+
+    #[test]
+    fn test_read_byte_array_too_long() {
+        let mut buffer = Vec::new();
+        let mut config = Config::new();
+
+        config.bytecode_max_string_length = 2;
+
+        pack_string!("inko", buffer);
+
+        let output = read_byte_array(&mut Reader::new(&buffer, &config));
+
+        match output {
+            Err(ParserErrorKind::StringTooLong(4)) => {}
+            _ => panic!("expected a StringTooLong error"),
+        }
+    }
+
+    #[test]
+    fn test_read_u16_vector_invalid_length() {
+        let mut buffer = Vec::new();
+
+        pack_u64!(u64::MAX, buffer);
+        pack_u16!(1, buffer);
+
+        match read!(read_u16_vector, buffer) {
+            Err(ParserErrorKind::InvalidLength(u64::MAX)) => {}
+            _ => panic!("expected an InvalidLength error"),
+        }
+    }
+
+    #[test]
+    fn test_read_compiled_code_too_deep() {
+        let state = state();
+        let mut config = Config::new();
+        let mut valid = Vec::new();
+        let mut invalid = Vec::new();
+
+        config.bytecode_max_code_depth = 2;
+
+        pack_code(&mut valid, 2);
+        pack_code(&mut invalid, 3);
+
+        let valid_output =
+            read_compiled_code(&state, &mut Reader::new(&valid, &config));
+
+        let invalid_output =
+            read_compiled_code(&state, &mut Reader::new(&invalid, &config));
+
+        assert!(valid_output.is_ok());
+
+        match invalid_output {
+            Err(ParserErrorKind::CodeTooDeep(2)) => {}
+            _ => panic!("expected a CodeTooDeep error"),
+        }
+    }
+
+    #[test]
+    fn test_read_literals_vector_too_many_literals() {
+        let state = state();
+        let mut buffer = Vec::new();
+        let mut config = Config::new();
+
+        config.bytecode_max_literals = 1;
+
+        pack_u64!(2, buffer);
+        pack_u8!(LITERAL_INTEGER, buffer);
+        pack_u64!(1, buffer);
+        pack_u8!(LITERAL_INTEGER, buffer);
+        pack_u64!(2, buffer);
+
+        let output = read_literals_vector(
+            &state,
+            &mut Reader::new(&buffer, &config),
+            Field::Literals,
+            Field::Literal,
+        );
+
+        match output {
+            Err(ParserErrorKind::TooManyLiterals(2)) => {}
+            _ => panic!("expected a TooManyLiterals error"),
+        }
+    }
+
+    #[test]
+    fn test_read_literal_invalid_bigint() {
+        let state = state();
+        let mut buffer = Vec::new();
+
+        pack_u8!(LITERAL_BIGINT, buffer);
+        pack_string!("zz", buffer);
+
+        match read_literal(&state, &mut reader(&buffer)) {
+            Err(ParserErrorKind::InvalidBigInteger) => {}
+            _ => panic!("expected an InvalidBigInteger error"),
+        }
+    }
+
+    #[test]
+    fn test_parse_slice_invalid_input() {
+        let state = state();
+        let mut buffer = Vec::new();
+
+        buffer.extend_from_slice(&SIGNATURE_BYTES);
+        buffer.push(VERSION);
+
+        pack_code(&mut buffer, 3);
+
+        assert!(parse_slice(&state, &buffer).is_ok());
+
+        // Parsing truncated or corrupted input should produce an error, and
+        // never panic or allocate absurd amounts of memory.
+        for length in 0..buffer.len() {
+            assert!(parse_slice(&state, &buffer[0..length]).is_err());
+        }
+
+        for index in 0..buffer.len() {
+            for byte in &[0, 1, 127, 255] {
+                let mut corrupted = buffer.clone();
+
+                corrupted[index] = *byte;
+
+                let _ = parse_slice(&state, &corrupted);
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_slice_error_location() {
+        let state = state();
+        let mut buffer = Vec::new();
+
+        buffer.extend_from_slice(&SIGNATURE_BYTES);
+        buffer.push(VERSION);
+
+        pack_code_header(&mut buffer, "main");
+        pack_u64!(0, buffer); // literals
+        pack_u64!(1, buffer); // code objects
+
+        pack_code_header(&mut buffer, "foo");
+        pack_u64!(2, buffer); // literals
+        pack_u8!(LITERAL_STRING, buffer);
+        pack_string!("a", buffer);
+
+        let offset = buffer.len();
+
+        pack_u8!(9, buffer);
+        pack_u64!(0, buffer); // code objects
+        pack_u64!(0, buffer); // catch table entries
+        pack_u64!(0, buffer); // catch table entries
+
+        let error = parse_slice(&state, &buffer).err().unwrap();
+
+        match error.kind {
+            ParserErrorKind::InvalidLiteralType(9) => {}
+            _ => panic!("expected an InvalidLiteralType error"),
+        }
+
+        assert_eq!(error.offset, offset);
+        assert_eq!(error.field, Some(Field::Literal(1)));
+        assert_eq!(
+            error.code_objects,
+            vec!["main".to_string(), "foo".to_string()]
+        );
+
+        assert_eq!(
+            error.location(),
+            "literal 1 of code object `foo` in `main`".to_string()
+        );
+
+        assert_eq!(
+            error.message(),
+            format!(
+                "9 is not a valid literal type\n\
+                 Location: literal 1 of code object `foo` in `main`\n\
+                 Byte offset: {}",
+                offset
+            )
+        );
+    }
+
+    #[test]
+    fn test_parse_slice_error_in_name() {
+        let state = state();
+        let mut buffer = Vec::new();
+
+        buffer.extend_from_slice(&SIGNATURE_BYTES);
+        buffer.push(VERSION);
+        pack_u64!(4, buffer); // name size
+
+        let error = parse_slice(&state, &buffer).err().unwrap();
+
+        assert_eq!(error.offset, 5);
+        assert_eq!(error.field, Some(Field::Name));
+        assert_eq!(
+            error.location(),
+            "the name of code object `<unknown>`".to_string()
+        );
+    }
+
+    #[test]
+    fn test_parse_slice_invalid_signature_location() {
+        let state = state();
+        let error = parse_slice(&state, &[1, 2, 3, 4]).err().unwrap();
+
+        assert_eq!(error.offset, 0);
+        assert_eq!(error.field, Some(Field::Signature));
+        assert_eq!(error.location(), "the signature".to_string());
+    }
+
+    #[test]
+    fn test_parse_file_invalid_file() {
+        let state = state();
+        let error = parse_file(&state, "/does/not/exist.inkoc").err().unwrap();
+
+        assert_eq!(error.field, None);
+        assert_eq!(error.message(), "The file could not be read".to_string());
     }
 }
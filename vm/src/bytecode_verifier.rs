@@ -0,0 +1,794 @@
+//! Verification of parsed bytecode.
+//!
+//! The VM accesses registers, local variables, literals and instructions
+//! without performing any bounds checking. This module provides a verification
+//! pass that is run before a module is executed, ensuring that every operand
+//! of every instruction refers to something that actually exists.
+//!
+//! Verifying a CompiledCode is done using the `verify` function:
+//!
+//!     let code = bytecode_parser::parse_file(&state, "foo.inkoc").unwrap();
+//!
+//!     bytecode_verifier::verify(&code).unwrap();
+
+use compiled_code::CompiledCode;
+use vm::instruction::{Instruction, InstructionType};
+
+/// The type of value an instruction argument refers to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operand {
+    /// A register of the current CompiledCode.
+    Register,
+
+    /// A local variable of the current CompiledCode.
+    Local,
+
+    /// An index in the literals table of the current CompiledCode.
+    Literal,
+
+    /// An index in the code objects of the current CompiledCode.
+    CodeObject,
+
+    /// An instruction index to jump to.
+    Jump,
+
+    /// A value that is used as-is, such as a boolean flag.
+    Value,
+}
+
+/// The arguments an instruction expects.
+enum Operands {
+    /// A fixed list of operands. The last N operands (as specified by the
+    /// second value) may be left out.
+    Fixed(&'static [Operand], usize),
+
+    /// A fixed list of operands followed by any number of registers.
+    Variadic(&'static [Operand]),
+
+    /// A fixed list of operands followed by the counts of positional and
+    /// keyword arguments, followed by the registers of those arguments.
+    Call(&'static [Operand]),
+}
+
+const REG: Operand = Operand::Register;
+const LOCAL: Operand = Operand::Local;
+const LITERAL: Operand = Operand::Literal;
+const CODE: Operand = Operand::CodeObject;
+const JUMP: Operand = Operand::Jump;
+const VALUE: Operand = Operand::Value;
+
+#[derive(Debug)]
+pub enum VerifierError {
+    /// The number of local variables is smaller than the number of arguments.
+    InvalidArguments(String, u16),
+
+    /// An instruction has an invalid number of arguments.
+    InvalidArgumentCount(String, u16, usize),
+
+    /// An instruction refers to a register that does not exist.
+    InvalidRegister(String, u16, usize),
+
+    /// An instruction refers to a local variable that does not exist.
+    InvalidLocal(String, u16, usize),
+
+    /// An instruction refers to a literal that does not exist.
+    InvalidLiteral(String, u16, usize),
+
+    /// An instruction refers to a code object that does not exist.
+    InvalidCodeObject(String, u16, usize),
+
+    /// An instruction jumps to an instruction that does not exist.
+    InvalidJump(String, u16, usize),
+
+    /// A catch table entry refers to instructions or registers that do not
+    /// exist.
+    InvalidCatchEntry(String, u16, usize),
+}
+
+pub type VerifierResult = Result<(), VerifierError>;
+
+impl VerifierError {
+    /// Returns a human friendly, multi-line error message.
+    pub fn message(&self) -> String {
+        let (description, file, line, index) = match *self {
+            VerifierError::InvalidArguments(ref file, line) => {
+                return format!(
+                    "The code object has more arguments than local \
+                     variables\nLocation: {}:{}",
+                    file, line
+                );
+            }
+            VerifierError::InvalidArgumentCount(ref file, line, index) => (
+                "The instruction has an invalid number of arguments",
+                file,
+                line,
+                index,
+            ),
+            VerifierError::InvalidRegister(ref file, line, index) => (
+                "The instruction refers to a register that does not exist",
+                file,
+                line,
+                index,
+            ),
+            VerifierError::InvalidLocal(ref file, line, index) => (
+                "The instruction refers to a local variable that does not \
+                 exist",
+                file,
+                line,
+                index,
+            ),
+            VerifierError::InvalidLiteral(ref file, line, index) => (
+                "The instruction refers to a literal that does not exist",
+                file,
+                line,
+                index,
+            ),
+            VerifierError::InvalidCodeObject(ref file, line, index) => (
+                "The instruction refers to a code object that does not exist",
+                file,
+                line,
+                index,
+            ),
+            VerifierError::InvalidJump(ref file, line, index) => (
+                "The instruction jumps to an instruction that does not exist",
+                file,
+                line,
+                index,
+            ),
+            VerifierError::InvalidCatchEntry(ref file, line, index) => {
+                return format!(
+                    "The catch table entry refers to an instruction or \
+                     register that does not exist\nLocation: {}:{}\n\
+                     Catch table entry: {}",
+                    file, line, index
+                );
+            }
+        };
+
+        format!(
+            "{}\nLocation: {}:{}\nInstruction: {}",
+            description, file, line, index
+        )
+    }
+}
+
+/// Verifies a CompiledCode and all the code objects stored in it.
+pub fn verify(code: &CompiledCode) -> VerifierResult {
+    if code.arguments.len() > code.locals() {
+        return Err(VerifierError::InvalidArguments(file_of(code), code.line));
+    }
+
+    for (index, instruction) in code.instructions.iter().enumerate() {
+        verify_instruction(code, index, instruction)?;
+    }
+
+    verify_catch_table(code)?;
+
+    for child in &code.code_objects {
+        verify(child)?;
+    }
+
+    Ok(())
+}
+
+/// Returns the type of every argument of an instruction.
+///
+/// Arguments beyond those the instruction expects are treated as registers
+/// for variadic instructions, and as plain values otherwise.
+pub fn operand_types(instruction: &Instruction) -> Vec<Operand> {
+    let (leading, trailing) = match operands(&instruction.instruction_type) {
+        Operands::Fixed(operands, _) => (operands.to_vec(), VALUE),
+        Operands::Variadic(operands) => (operands.to_vec(), REG),
+        Operands::Call(operands) => {
+            let mut leading = operands.to_vec();
+
+            // The counts of the positional and keyword arguments.
+            leading.push(VALUE);
+            leading.push(VALUE);
+
+            (leading, REG)
+        }
+    };
+
+    (0..instruction.arguments.len())
+        .map(|index| leading.get(index).cloned().unwrap_or(trailing))
+        .collect()
+}
+
+fn verify_instruction(
+    code: &CompiledCode,
+    index: usize,
+    instruction: &Instruction,
+) -> VerifierResult {
+    let arguments = &instruction.arguments;
+
+    // The fixed operands to verify, and the index of the first argument that
+    // refers to a register of a variadic instruction.
+    let expected = operands(&instruction.instruction_type);
+    let (leading, variadic_start) = match expected {
+        Operands::Fixed(operands, optional) => {
+            let max = operands.len();
+            let min = max - optional;
+
+            if arguments.len() < min || arguments.len() > max {
+                return Err(VerifierError::InvalidArgumentCount(
+                    file_of(code),
+                    instruction.line,
+                    index,
+                ));
+            }
+
+            (&operands[0..arguments.len()], arguments.len())
+        }
+        Operands::Variadic(operands) => {
+            if arguments.len() < operands.len() {
+                return Err(VerifierError::InvalidArgumentCount(
+                    file_of(code),
+                    instruction.line,
+                    index,
+                ));
+            }
+
+            (operands, operands.len())
+        }
+        Operands::Call(operands) => {
+            let counts = operands.len() + 2;
+
+            if arguments.len() < counts {
+                return Err(VerifierError::InvalidArgumentCount(
+                    file_of(code),
+                    instruction.line,
+                    index,
+                ));
+            }
+
+            let positional = arguments[counts - 2] as usize;
+            let keyword = arguments[counts - 1] as usize;
+
+            if arguments.len() != counts + positional + (keyword * 2) {
+                return Err(VerifierError::InvalidArgumentCount(
+                    file_of(code),
+                    instruction.line,
+                    index,
+                ));
+            }
+
+            (operands, counts)
+        }
+    };
+
+    for (operand, value) in leading.iter().zip(arguments) {
+        verify_operand(code, index, instruction, *operand, *value as usize)?;
+    }
+
+    for value in &arguments[variadic_start..] {
+        verify_operand(code, index, instruction, REG, *value as usize)?;
+    }
+
+    Ok(())
+}
+
+fn verify_operand(
+    code: &CompiledCode,
+    index: usize,
+    instruction: &Instruction,
+    operand: Operand,
+    value: usize,
+) -> VerifierResult {
+    let file = || file_of(code);
+    let line = instruction.line;
+
+    match operand {
+        Operand::Register if value >= code.registers as usize => {
+            Err(VerifierError::InvalidRegister(file(), line, index))
+        }
+        Operand::Local if value >= code.locals() => {
+            Err(VerifierError::InvalidLocal(file(), line, index))
+        }
+        Operand::Literal if value >= code.literals.len() => {
+            Err(VerifierError::InvalidLiteral(file(), line, index))
+        }
+        Operand::CodeObject if value >= code.code_objects.len() => {
+            Err(VerifierError::InvalidCodeObject(file(), line, index))
+        }
+        Operand::Jump if value >= code.instructions.len() => {
+            Err(VerifierError::InvalidJump(file(), line, index))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn verify_catch_table(code: &CompiledCode) -> VerifierResult {
+    let instructions = code.instructions.len();
+    let registers = code.registers as usize;
+
+    for (index, entry) in code.catch_table.entries.iter().enumerate() {
+        if entry.start > entry.end
+            || entry.end >= instructions
+            || entry.jump_to >= instructions
+            || entry.register >= registers
+        {
+            return Err(VerifierError::InvalidCatchEntry(
+                file_of(code),
+                code.line,
+                index,
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+fn file_of(code: &CompiledCode) -> String {
+    code.file
+        .string_value()
+        .map(|string| string.clone())
+        .unwrap_or_else(|_| String::new())
+}
+
+/// Returns the operands expected by the given instruction type.
+#[cfg_attr(feature = "cargo-clippy", allow(cyclomatic_complexity))]
+fn operands(instruction_type: &InstructionType) -> Operands {
+    match *instruction_type {
+        InstructionType::SetLiteral => Operands::Fixed(&[REG, LITERAL], 0),
+        InstructionType::SetObject => Operands::Fixed(&[REG, REG, REG], 1),
+        InstructionType::SetArray => Operands::Variadic(&[REG]),
+        InstructionType::GetIntegerPrototype
+        | InstructionType::GetFloatPrototype
+        | InstructionType::GetStringPrototype
+        | InstructionType::GetArrayPrototype
+        | InstructionType::GetBlockPrototype
+        | InstructionType::GetObjectPrototype
+        | InstructionType::GetBooleanPrototype
+        | InstructionType::GetTrue
+        | InstructionType::GetFalse
+        | InstructionType::GetToplevel
+        | InstructionType::GetNil
+        | InstructionType::ArrayClear
+        | InstructionType::StdoutFlush
+        | InstructionType::StderrFlush
+        | InstructionType::FileFlush
+        | InstructionType::ProcessCurrentPid
+        | InstructionType::ProcessSuspendCurrent
+        | InstructionType::TimeMonotonic
+        | InstructionType::TimeSystem
+        | InstructionType::TimeSystemOffset
+        | InstructionType::TimeSystemDst
+        | InstructionType::Throw
+        | InstructionType::Drop
+        | InstructionType::MoveToPool
+        | InstructionType::Panic
+        | InstructionType::Exit
+        | InstructionType::Platform
+        | InstructionType::HasherNew
+        | InstructionType::ByteArrayClear
+        | InstructionType::EnvVariables
+        | InstructionType::EnvArguments
+        | InstructionType::ProcessRegisteredNames
+        | InstructionType::ReferenceNew
+        | InstructionType::EnvGetWorkingDirectory => Operands::Fixed(&[REG], 0),
+        InstructionType::SetLocal => Operands::Fixed(&[LOCAL, REG], 0),
+        InstructionType::GetLocal | InstructionType::LocalExists => {
+            Operands::Fixed(&[REG, LOCAL], 0)
+        }
+        InstructionType::SetBlock => Operands::Fixed(&[REG, CODE], 0),
+        InstructionType::Return => Operands::Fixed(&[VALUE, REG], 1),
+        InstructionType::GotoIfFalse | InstructionType::GotoIfTrue => {
+            Operands::Fixed(&[JUMP, REG], 0)
+        }
+        InstructionType::Goto => Operands::Fixed(&[JUMP], 0),
+        InstructionType::RunBlock => Operands::Call(&[REG, REG]),
+        InstructionType::TailCall => Operands::Call(&[]),
+        InstructionType::IntegerToFloat
+        | InstructionType::IntegerToString
+        | InstructionType::FloatToInteger
+        | InstructionType::FloatToString
+        | InstructionType::FloatIsNan
+        | InstructionType::FloatIsInfinite
+        | InstructionType::FloatFloor
+        | InstructionType::FloatCeil
+        | InstructionType::ArrayLength
+        | InstructionType::StringToLower
+        | InstructionType::StringToUpper
+        | InstructionType::StringToByteArray
+        | InstructionType::StringLength
+        | InstructionType::StringSize
+        | InstructionType::StringFormatDebug
+        | InstructionType::StringConcatMultiple
+        | InstructionType::StdoutWrite
+        | InstructionType::StderrWrite
+        | InstructionType::FileSize
+        | InstructionType::FileRemove
+        | InstructionType::FileType
+        | InstructionType::DirectoryList
+        | InstructionType::LoadModule
+        | InstructionType::SetPrototype
+        | InstructionType::GetPrototype
+        | InstructionType::GetAttributeNames
+        | InstructionType::ProcessReceiveMessage
+        | InstructionType::ProcessStatus
+        | InstructionType::SetRegister
+        | InstructionType::CopyBlocks
+        | InstructionType::HasherFinish
+        | InstructionType::ByteArrayFromArray
+        | InstructionType::ByteArrayLength
+        | InstructionType::EnvGet
+        | InstructionType::EnvRemove
+        | InstructionType::EnvSetWorkingDirectory
+        | InstructionType::ProcessMonitor
+        | InstructionType::ProcessDemonitor
+        | InstructionType::ProcessLink
+        | InstructionType::ProcessUnlink
+        | InstructionType::ProcessTrapExits
+        | InstructionType::ProcessUnregister
+        | InstructionType::ProcessWhereis
+        | InstructionType::ProcessMailboxLength
+        | InstructionType::TimerCancel
+        | InstructionType::TcpListen
+        | InstructionType::TcpConnect
+        | InstructionType::SocketAccept
+        | InstructionType::SocketFinishConnect
+        | InstructionType::SocketLocalAddress
+        | InstructionType::SocketPeerAddress
+        | InstructionType::UdpBind
+        | InstructionType::UnixListen
+        | InstructionType::UnixConnect
+        | InstructionType::UnixDatagramBind
+        | InstructionType::UnixPair
+        | InstructionType::ChildCloseInput
+        | InstructionType::ChildWait
+        | InstructionType::ChildExitCode
+        | InstructionType::ChildExitSignal
+        | InstructionType::ChildKill
+        | InstructionType::SignalSubscribe
+        | InstructionType::SignalUnsubscribe => Operands::Fixed(&[REG, REG], 0),
+        InstructionType::IntegerAdd
+        | InstructionType::IntegerDiv
+        | InstructionType::IntegerMul
+        | InstructionType::IntegerSub
+        | InstructionType::IntegerMod
+        | InstructionType::IntegerBitwiseAnd
+        | InstructionType::IntegerBitwiseOr
+        | InstructionType::IntegerBitwiseXor
+        | InstructionType::IntegerShiftLeft
+        | InstructionType::IntegerShiftRight
+        | InstructionType::IntegerSmaller
+        | InstructionType::IntegerGreater
+        | InstructionType::IntegerEquals
+        | InstructionType::IntegerGreaterOrEqual
+        | InstructionType::IntegerSmallerOrEqual
+        | InstructionType::FloatAdd
+        | InstructionType::FloatMul
+        | InstructionType::FloatDiv
+        | InstructionType::FloatSub
+        | InstructionType::FloatMod
+        | InstructionType::FloatSmaller
+        | InstructionType::FloatGreater
+        | InstructionType::FloatEquals
+        | InstructionType::FloatGreaterOrEqual
+        | InstructionType::FloatSmallerOrEqual
+        | InstructionType::FloatRound
+        | InstructionType::ArrayAt
+        | InstructionType::ArrayRemove
+        | InstructionType::StringEquals
+        | InstructionType::StringConcat
+        | InstructionType::StdinRead
+        | InstructionType::FileOpen
+        | InstructionType::FileWrite
+        | InstructionType::FileSeek
+        | InstructionType::FileCopy
+        | InstructionType::FileTime
+        | InstructionType::DirectoryCreate
+        | InstructionType::DirectoryRemove
+        | InstructionType::GetAttribute
+        | InstructionType::SetAttributeToObject
+        | InstructionType::AttributeExists
+        | InstructionType::RemoveAttribute
+        | InstructionType::ProcessSendMessage
+        | InstructionType::ObjectEquals
+        | InstructionType::ObjectIsKindOf
+        | InstructionType::HasherWrite
+        | InstructionType::Stacktrace
+        | InstructionType::BlockMetadata
+        | InstructionType::ByteArrayAt
+        | InstructionType::ByteArrayRemove
+        | InstructionType::ByteArrayEquals
+        | InstructionType::ByteArrayToString
+        | InstructionType::EnvSet
+        | InstructionType::ProcessTerminate
+        | InstructionType::ProcessRegister
+        | InstructionType::ProcessReceiveMessageIf
+        | InstructionType::ReferenceEquals
+        | InstructionType::SocketWrite
+        | InstructionType::SocketShutdown
+        | InstructionType::SocketConnect
+        | InstructionType::SocketGetOption
+        | InstructionType::ChildWrite => Operands::Fixed(&[REG, REG, REG], 0),
+        InstructionType::ArraySet
+        | InstructionType::FileRead
+        | InstructionType::SetAttribute
+        | InstructionType::PrototypeChainAttributeContains
+        | InstructionType::StringSlice
+        | InstructionType::ByteArraySet
+        | InstructionType::ProcessSetMailboxCapacity
+        | InstructionType::SocketRead
+        | InstructionType::SocketSendTo
+        | InstructionType::SocketReceiveFrom
+        | InstructionType::SocketSetOption
+        | InstructionType::SocketJoinMulticast
+        | InstructionType::SocketLeaveMulticast => {
+            Operands::Fixed(&[REG, REG, REG, REG], 0)
+        }
+        InstructionType::ProcessSpawn => Operands::Fixed(&[REG, REG, REG], 1),
+        InstructionType::TimerSchedule
+        | InstructionType::ChildSpawn
+        | InstructionType::ChildRead => {
+            Operands::Fixed(&[REG, REG, REG, REG, REG], 0)
+        }
+        InstructionType::SetParentLocal => {
+            Operands::Fixed(&[VALUE, VALUE, REG], 0)
+        }
+        InstructionType::GetParentLocal => {
+            Operands::Fixed(&[REG, VALUE, VALUE], 0)
+        }
+        InstructionType::GetGlobal => Operands::Fixed(&[REG, VALUE], 0),
+        InstructionType::SetGlobal => Operands::Fixed(&[REG, VALUE, REG], 0),
+        InstructionType::ProcessTerminateCurrent => Operands::Fixed(&[], 0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use catch_table::CatchEntry;
+    use config::Config;
+    use object_pointer::ObjectPointer;
+    use vm::state::{RcState, State};
+
+    fn state() -> RcState {
+        State::new(Config::new())
+    }
+
+    fn new_compiled_code(
+        state: &RcState,
+        instructions: Vec<Instruction>,
+    ) -> CompiledCode {
+        let name = state.intern(&"foo".to_string());
+        let file = state.intern(&"foo.inko".to_string());
+        let mut code = CompiledCode::new(name, file, 1, instructions);
+
+        code.locals = 2;
+        code.registers = 2;
+
+        code
+    }
+
+    fn ins(ins_type: InstructionType, args: Vec<u16>) -> Instruction {
+        Instruction::new(ins_type, args, 4)
+    }
+
+    #[test]
+    fn test_verify_valid() {
+        let state = state();
+        let mut code = new_compiled_code(
+            &state,
+            vec![
+                ins(InstructionType::SetLiteral, vec![0, 0]),
+                ins(InstructionType::SetLocal, vec![1, 0]),
+                ins(InstructionType::SetBlock, vec![1, 0]),
+                ins(InstructionType::RunBlock, vec![0, 1, 1, 1, 0, 0, 1]),
+                ins(InstructionType::GotoIfTrue, vec![0, 1]),
+                ins(InstructionType::Return, vec![0, 0]),
+            ],
+        );
+
+        let child = new_compiled_code(
+            &state,
+            vec![ins(InstructionType::Return, vec![0])],
+        );
+
+        code.literals.push(ObjectPointer::integer(1));
+        code.code_objects.push(child);
+        code.catch_table.entries.push(CatchEntry::new(0, 4, 5, 1));
+
+        assert!(verify(&code).is_ok());
+    }
+
+    #[test]
+    fn test_operand_types() {
+        let set_literal = ins(InstructionType::SetLiteral, vec![0, 0]);
+        let run_block = ins(InstructionType::RunBlock, vec![0, 1, 1, 0, 2]);
+
+        assert_eq!(
+            operand_types(&set_literal),
+            vec![Operand::Register, Operand::Literal]
+        );
+
+        assert_eq!(
+            operand_types(&run_block),
+            vec![
+                Operand::Register,
+                Operand::Register,
+                Operand::Value,
+                Operand::Value,
+                Operand::Register,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_verify_invalid_argument_count() {
+        let state = state();
+        let code = new_compiled_code(
+            &state,
+            vec![ins(InstructionType::SetLiteral, vec![0])],
+        );
+
+        match verify(&code) {
+            Err(VerifierError::InvalidArgumentCount(file, line, index)) => {
+                assert_eq!(file, "foo.inko".to_string());
+                assert_eq!(line, 4);
+                assert_eq!(index, 0);
+            }
+            _ => panic!("Expected an InvalidArgumentCount error"),
+        }
+    }
+
+    #[test]
+    fn test_verify_invalid_call_argument_count() {
+        let state = state();
+        let code = new_compiled_code(
+            &state,
+            vec![ins(InstructionType::RunBlock, vec![0, 1, 2, 0, 0])],
+        );
+
+        match verify(&code) {
+            Err(VerifierError::InvalidArgumentCount(_, _, 0)) => {}
+            _ => panic!("Expected an InvalidArgumentCount error"),
+        }
+    }
+
+    #[test]
+    fn test_verify_invalid_register() {
+        let state = state();
+        let code = new_compiled_code(
+            &state,
+            vec![
+                ins(InstructionType::GetNil, vec![0]),
+                ins(InstructionType::SetArray, vec![0, 1, 2]),
+            ],
+        );
+
+        match verify(&code) {
+            Err(VerifierError::InvalidRegister(_, _, 1)) => {}
+            _ => panic!("Expected an InvalidRegister error"),
+        }
+    }
+
+    #[test]
+    fn test_verify_invalid_local() {
+        let state = state();
+        let code = new_compiled_code(
+            &state,
+            vec![ins(InstructionType::GetLocal, vec![0, 2])],
+        );
+
+        match verify(&code) {
+            Err(VerifierError::InvalidLocal(_, _, 0)) => {}
+            _ => panic!("Expected an InvalidLocal error"),
+        }
+    }
+
+    #[test]
+    fn test_verify_invalid_literal() {
+        let state = state();
+        let code = new_compiled_code(
+            &state,
+            vec![ins(InstructionType::SetLiteral, vec![0, 0])],
+        );
+
+        match verify(&code) {
+            Err(VerifierError::InvalidLiteral(_, _, 0)) => {}
+            _ => panic!("Expected an InvalidLiteral error"),
+        }
+    }
+
+    #[test]
+    fn test_verify_invalid_code_object() {
+        let state = state();
+        let code = new_compiled_code(
+            &state,
+            vec![ins(InstructionType::SetBlock, vec![0, 0])],
+        );
+
+        match verify(&code) {
+            Err(VerifierError::InvalidCodeObject(_, _, 0)) => {}
+            _ => panic!("Expected an InvalidCodeObject error"),
+        }
+    }
+
+    #[test]
+    fn test_verify_invalid_jump() {
+        let state = state();
+        let code = new_compiled_code(
+            &state,
+            vec![
+                ins(InstructionType::Goto, vec![2]),
+                ins(InstructionType::Return, vec![0]),
+            ],
+        );
+
+        match verify(&code) {
+            Err(VerifierError::InvalidJump(_, _, 0)) => {}
+            _ => panic!("Expected an InvalidJump error"),
+        }
+    }
+
+    #[test]
+    fn test_verify_invalid_catch_entry() {
+        let state = state();
+        let mut code = new_compiled_code(
+            &state,
+            vec![ins(InstructionType::Return, vec![0])],
+        );
+
+        code.catch_table.entries.push(CatchEntry::new(0, 0, 1, 0));
+
+        match verify(&code) {
+            Err(VerifierError::InvalidCatchEntry(_, 1, 0)) => {}
+            _ => panic!("Expected an InvalidCatchEntry error"),
+        }
+    }
+
+    #[test]
+    fn test_verify_invalid_arguments() {
+        let state = state();
+        let mut code = new_compiled_code(
+            &state,
+            vec![ins(InstructionType::Return, vec![0])],
+        );
+
+        for _ in 0..3 {
+            code.arguments.push(state.intern(&"a".to_string()));
+        }
+
+        match verify(&code) {
+            Err(VerifierError::InvalidArguments(_, 1)) => {}
+            _ => panic!("Expected an InvalidArguments error"),
+        }
+    }
+
+    #[test]
+    fn test_verify_nested_code_object() {
+        let state = state();
+        let mut code = new_compiled_code(
+            &state,
+            vec![ins(InstructionType::Return, vec![0])],
+        );
+
+        let child = new_compiled_code(
+            &state,
+            vec![ins(InstructionType::GetNil, vec![5])],
+        );
+
+        code.code_objects.push(child);
+
+        match verify(&code) {
+            Err(VerifierError::InvalidRegister(_, _, 0)) => {}
+            _ => panic!("Expected an InvalidRegister error"),
+        }
+    }
+
+    #[test]
+    fn test_verifier_error_message() {
+        let error =
+            VerifierError::InvalidRegister("test.inko".to_string(), 2, 3);
+
+        assert_eq!(
+            error.message(),
+            "The instruction refers to a register that does not exist\n\
+             Location: test.inko:2\n\
+             Instruction: 3"
+        );
+    }
+}
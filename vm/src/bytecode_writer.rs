@@ -0,0 +1,498 @@
+//! A serializer for Inko bytecode streams
+//!
+//! This module provides functions for turning a CompiledCode back into the
+//! bytecode format read by the `bytecode_parser` module, allowing tools written
+//! in Rust to produce or rewrite bytecode files.
+//!
+//! To serialize a CompiledCode into a Vec of bytes you can use the `write`
+//! function:
+//!
+//!     let bytes = bytecode_writer::write(&code).unwrap();
+//!
+//! Alternatively you can also write a file directly:
+//!
+//!     bytecode_writer::write_file(&code, "path/to/file.inkoc").unwrap();
+
+use std::fs::File;
+use std::io::prelude::*;
+use std::u16;
+
+use bytecode_parser::{
+    LITERAL_BIGINT, LITERAL_FLOAT, LITERAL_INTEGER, LITERAL_STRING,
+    SIGNATURE_BYTES, VERSION,
+};
+use catch_table::CatchEntry;
+use compiled_code::CompiledCode;
+use object_pointer::ObjectPointer;
+use object_value::ObjectValue;
+use vm::instruction::Instruction;
+
+#[derive(Debug)]
+pub enum WriterError {
+    /// The output file could not be written.
+    InvalidFile(String),
+
+    /// A literal of the given file and line can not be stored in bytecode.
+    InvalidLiteral(String, u16),
+
+    /// A catch table entry of the given file and line refers to an
+    /// instruction or register that can not be stored in bytecode.
+    InvalidCatchEntry(String, u16),
+}
+
+pub type WriterResult<T> = Result<T, WriterError>;
+
+/// Writes a CompiledCode to a file.
+///
+/// # Examples
+///
+///     bytecode_writer::write_file(&code, "path/to/file.inkoc").unwrap();
+pub fn write_file(code: &CompiledCode, path: &str) -> WriterResult<()> {
+    let bytes = write(code)?;
+
+    File::create(path)
+        .and_then(|mut file| file.write_all(&bytes))
+        .map_err(|_| WriterError::InvalidFile(path.to_string()))
+}
+
+/// Serializes a CompiledCode, including the bytecode signature and version.
+///
+/// # Examples
+///
+///     let bytes = bytecode_writer::write(&code).unwrap();
+///     let code = bytecode_parser::parse(&state, &mut (&bytes[..]).bytes());
+pub fn write(code: &CompiledCode) -> WriterResult<Vec<u8>> {
+    let mut buffer = Vec::new();
+
+    buffer.extend_from_slice(&SIGNATURE_BYTES);
+    buffer.push(VERSION);
+
+    write_compiled_code(&mut buffer, code)?;
+
+    Ok(buffer)
+}
+
+fn write_string(buffer: &mut Vec<u8>, value: &str) {
+    write_byte_array(buffer, value.as_bytes());
+}
+
+fn write_byte_array(buffer: &mut Vec<u8>, value: &[u8]) {
+    write_u64(buffer, value.len() as u64);
+    buffer.extend_from_slice(value);
+}
+
+fn write_u8(buffer: &mut Vec<u8>, value: u8) {
+    buffer.push(value);
+}
+
+fn write_bool(buffer: &mut Vec<u8>, value: bool) {
+    write_u8(buffer, value as u8);
+}
+
+fn write_u16(buffer: &mut Vec<u8>, value: u16) {
+    buffer.push((value >> 8) as u8);
+    buffer.push(value as u8);
+}
+
+fn write_i64(buffer: &mut Vec<u8>, value: i64) {
+    write_u64(buffer, value as u64);
+}
+
+fn write_u64(buffer: &mut Vec<u8>, value: u64) {
+    for shift in (0..8).rev() {
+        buffer.push((value >> (shift * 8)) as u8);
+    }
+}
+
+fn write_f64(buffer: &mut Vec<u8>, value: f64) {
+    write_u64(buffer, value.to_bits());
+}
+
+fn write_instruction(buffer: &mut Vec<u8>, instruction: &Instruction) {
+    write_u8(buffer, instruction.instruction_type as u8);
+    write_u64(buffer, instruction.arguments.len() as u64);
+
+    for argument in &instruction.arguments {
+        write_u16(buffer, *argument);
+    }
+
+    write_u16(buffer, instruction.line);
+}
+
+fn write_compiled_code(
+    buffer: &mut Vec<u8>,
+    code: &CompiledCode,
+) -> WriterResult<()> {
+    write_string(buffer, string_of(code, &code.name)?);
+    write_string(buffer, string_of(code, &code.file)?);
+    write_u16(buffer, code.line);
+    write_literals_vector(buffer, code, &code.arguments)?;
+    write_u8(buffer, code.required_arguments);
+    write_bool(buffer, code.rest_argument);
+    write_u16(buffer, code.locals);
+    write_u16(buffer, code.registers);
+    write_bool(buffer, code.captures);
+
+    write_u64(buffer, code.instructions.len() as u64);
+
+    for instruction in &code.instructions {
+        write_instruction(buffer, instruction);
+    }
+
+    write_literals_vector(buffer, code, &code.literals)?;
+
+    write_u64(buffer, code.code_objects.len() as u64);
+
+    for child in &code.code_objects {
+        write_compiled_code(buffer, child)?;
+    }
+
+    write_u64(buffer, code.catch_table.entries.len() as u64);
+
+    for entry in &code.catch_table.entries {
+        write_catch_entry(buffer, code, entry)?;
+    }
+
+    Ok(())
+}
+
+fn write_literals_vector(
+    buffer: &mut Vec<u8>,
+    code: &CompiledCode,
+    literals: &[ObjectPointer],
+) -> WriterResult<()> {
+    write_u64(buffer, literals.len() as u64);
+
+    for literal in literals {
+        write_literal(buffer, code, *literal)?;
+    }
+
+    Ok(())
+}
+
+fn write_literal(
+    buffer: &mut Vec<u8>,
+    code: &CompiledCode,
+    literal: ObjectPointer,
+) -> WriterResult<()> {
+    if literal.is_tagged_integer() {
+        write_u8(buffer, LITERAL_INTEGER);
+        write_i64(buffer, literal.integer_value().unwrap_or(0));
+
+        return Ok(());
+    }
+
+    match literal.get().value {
+        ObjectValue::Integer(value) => {
+            write_u8(buffer, LITERAL_INTEGER);
+            write_i64(buffer, value);
+        }
+        ObjectValue::BigInt(ref value) => {
+            write_u8(buffer, LITERAL_BIGINT);
+            write_byte_array(buffer, value.to_str_radix(16).as_bytes());
+        }
+        ObjectValue::Float(value) => {
+            write_u8(buffer, LITERAL_FLOAT);
+            write_f64(buffer, value);
+        }
+        ObjectValue::String(ref value) => {
+            write_u8(buffer, LITERAL_STRING);
+            write_string(buffer, value);
+        }
+        ObjectValue::InternedString(ref value) => {
+            write_u8(buffer, LITERAL_STRING);
+            write_string(buffer, value);
+        }
+        _ => return Err(invalid_literal(code)),
+    }
+
+    Ok(())
+}
+
+fn write_catch_entry(
+    buffer: &mut Vec<u8>,
+    code: &CompiledCode,
+    entry: &CatchEntry,
+) -> WriterResult<()> {
+    let values = [entry.start, entry.end, entry.jump_to, entry.register];
+
+    // The values of a catch entry are stored as u16 values. Truncating larger
+    // values would produce bytecode that silently jumps to the wrong
+    // instruction.
+    if values.iter().any(|value| *value > u16::MAX as usize) {
+        return Err(WriterError::InvalidCatchEntry(file_of(code), code.line));
+    }
+
+    for value in &values {
+        write_u16(buffer, *value as u16);
+    }
+
+    Ok(())
+}
+
+fn string_of<'a>(
+    code: &CompiledCode,
+    pointer: &'a ObjectPointer,
+) -> WriterResult<&'a str> {
+    pointer
+        .string_value()
+        .map(|string| string.as_str())
+        .map_err(|_| invalid_literal(code))
+}
+
+fn invalid_literal(code: &CompiledCode) -> WriterError {
+    WriterError::InvalidLiteral(file_of(code), code.line)
+}
+
+fn file_of(code: &CompiledCode) -> String {
+    code.file
+        .string_value()
+        .map(|string| string.clone())
+        .unwrap_or_else(|_| String::new())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytecode_parser;
+    use catch_table::CatchEntry;
+    use config::Config;
+    use num_bigint::BigInt;
+    use std::f64;
+    use std::i64;
+    use vm::instruction::{InstructionType, INSTRUCTION_TYPES};
+    use vm::state::{RcState, State};
+
+    /// A simple xorshift generator, used for producing random code objects.
+    struct Random {
+        state: u64,
+    }
+
+    impl Random {
+        fn new(seed: u64) -> Self {
+            Random { state: seed }
+        }
+
+        fn next(&mut self) -> u64 {
+            self.state ^= self.state << 13;
+            self.state ^= self.state >> 7;
+            self.state ^= self.state << 17;
+            self.state
+        }
+
+        fn below(&mut self, max: u64) -> u64 {
+            self.next() % max
+        }
+
+        fn string(&mut self) -> String {
+            let chars = ['a', 'z', ' ', '"', '\n', 'é', '€', '🐦'];
+            let length = self.below(8);
+
+            (0..length)
+                .map(|_| chars[self.below(chars.len() as u64) as usize])
+                .collect()
+        }
+
+        fn literal(&mut self, state: &RcState) -> ObjectPointer {
+            match self.below(7) {
+                0 => ObjectPointer::integer(self.below(1000) as i64 - 500),
+                1 => state.allocate_permanent_integer(i64::MAX),
+                2 => state.allocate_permanent_integer(self.next() as i64),
+                3 => {
+                    let base = BigInt::from(self.next());
+
+                    state.allocate_permanent_bigint(base.clone() * base * -1)
+                }
+                4 => {
+                    state.allocate_permanent_float(f64::from_bits(self.next()))
+                }
+                5 => state.allocate_permanent_float(f64::INFINITY),
+                _ => state.intern_owned(self.string()),
+            }
+        }
+
+        fn code(&mut self, state: &RcState, depth: usize) -> CompiledCode {
+            let name = state.intern_owned(self.string());
+            let file = state.intern_owned(self.string());
+            let mut instructions = Vec::new();
+
+            for _ in 0..self.below(10) {
+                let index = self.below(INSTRUCTION_TYPES.len() as u64);
+                let arguments =
+                    (0..self.below(6)).map(|_| self.next() as u16).collect();
+
+                instructions.push(Instruction::new(
+                    INSTRUCTION_TYPES[index as usize],
+                    arguments,
+                    self.next() as u16,
+                ));
+            }
+
+            // The parser requires every code object to end with a return.
+            instructions.push(Instruction::new(
+                InstructionType::Return,
+                Vec::new(),
+                self.next() as u16,
+            ));
+
+            let mut code =
+                CompiledCode::new(name, file, self.next() as u16, instructions);
+
+            for _ in 0..self.below(4) {
+                code.arguments.push(state.intern_owned(self.string()));
+            }
+
+            for _ in 0..self.below(8) {
+                code.literals.push(self.literal(state));
+            }
+
+            for _ in 0..self.below(3) {
+                code.catch_table.entries.push(CatchEntry::new(
+                    self.next() as u16 as usize,
+                    self.next() as u16 as usize,
+                    self.next() as u16 as usize,
+                    self.next() as u16 as usize,
+                ));
+            }
+
+            if depth < 2 {
+                for _ in 0..self.below(3) {
+                    code.code_objects.push(self.code(state, depth + 1));
+                }
+            }
+
+            code.required_arguments = self.next() as u8;
+            code.rest_argument = self.below(2) == 1;
+            code.locals = self.next() as u16;
+            code.registers = self.next() as u16;
+            code.captures = self.below(2) == 1;
+
+            code
+        }
+    }
+
+    fn state() -> RcState {
+        State::new(Config::new())
+    }
+
+    fn parse(state: &RcState, bytes: &[u8]) -> CompiledCode {
+        match bytecode_parser::parse(state, &mut bytes.bytes()) {
+            Ok(code) => code,
+            Err(error) => panic!("Failed to parse input: {:?}", error),
+        }
+    }
+
+    #[test]
+    fn test_write_round_trip() {
+        let state = state();
+        let name = state.intern(&"main".to_string());
+        let file = state.intern(&"main.inko".to_string());
+        let mut code = CompiledCode::new(
+            name,
+            file,
+            4,
+            vec![Instruction::new(InstructionType::Return, vec![1, 2], 5)],
+        );
+
+        code.arguments.push(state.intern(&"self".to_string()));
+        code.required_arguments = 1;
+        code.rest_argument = true;
+        code.locals = 2;
+        code.registers = 3;
+        code.captures = true;
+        code.literals.push(ObjectPointer::integer(-10));
+        code.literals.push(state.allocate_permanent_float(1.5));
+        code.literals.push(state.intern(&"hello".to_string()));
+        code.literals
+            .push(state.allocate_permanent_bigint(BigInt::from(-255)));
+        code.catch_table.entries.push(CatchEntry::new(0, 1, 2, 3));
+        code.code_objects.push(CompiledCode::new(
+            name,
+            file,
+            6,
+            vec![Instruction::new(InstructionType::Return, Vec::new(), 6)],
+        ));
+
+        let parsed = parse(&state, &write(&code).unwrap());
+
+        assert_eq!(parsed.name.string_value().unwrap(), "main");
+        assert_eq!(parsed.file.string_value().unwrap(), "main.inko");
+        assert_eq!(parsed.line, 4);
+        assert_eq!(parsed.arguments[0].string_value().unwrap(), "self");
+        assert_eq!(parsed.required_arguments, 1);
+        assert!(parsed.rest_argument);
+        assert_eq!(parsed.locals, 2);
+        assert_eq!(parsed.registers, 3);
+        assert!(parsed.captures);
+
+        assert_eq!(
+            parsed.instructions[0].instruction_type,
+            InstructionType::Return
+        );
+        assert_eq!(parsed.instructions[0].arguments, vec![1, 2]);
+        assert_eq!(parsed.instructions[0].line, 5);
+
+        assert_eq!(parsed.literals[0].integer_value().unwrap(), -10);
+        assert_eq!(parsed.literals[1].float_value().unwrap(), 1.5);
+        assert_eq!(parsed.literals[2].string_value().unwrap(), "hello");
+        assert_eq!(
+            *parsed.literals[3].bigint_value().unwrap(),
+            BigInt::from(-255)
+        );
+
+        let entry = &parsed.catch_table.entries[0];
+
+        assert_eq!(entry.start, 0);
+        assert_eq!(entry.end, 1);
+        assert_eq!(entry.jump_to, 2);
+        assert_eq!(entry.register, 3);
+
+        assert_eq!(parsed.code_objects[0].line, 6);
+    }
+
+    #[test]
+    fn test_write_round_trip_random() {
+        let state = state();
+        let mut random = Random::new(0x2545_F491_4F6C_DD1D);
+
+        for _ in 0..250 {
+            let code = random.code(&state, 0);
+            let bytes = write(&code).unwrap();
+            let parsed = parse(&state, &bytes);
+
+            assert_eq!(write(&parsed).unwrap(), bytes);
+        }
+    }
+
+    #[test]
+    fn test_write_invalid_literal() {
+        let state = state();
+        let name = state.intern(&"main".to_string());
+        let mut code = CompiledCode::new(name, name, 1, Vec::new());
+
+        code.literals.push(state.true_object);
+
+        match write(&code) {
+            Err(WriterError::InvalidLiteral(_, 1)) => {}
+            _ => panic!("expected an InvalidLiteral error"),
+        }
+    }
+
+    #[test]
+    fn test_write_invalid_catch_entry() {
+        let state = state();
+        let name = state.intern(&"main".to_string());
+        let mut code = CompiledCode::new(name, name, 1, Vec::new());
+
+        let jump_to = u16::MAX as usize + 1;
+
+        code.catch_table
+            .entries
+            .push(CatchEntry::new(0, 1, jump_to, 0));
+
+        match write(&code) {
+            Err(WriterError::InvalidCatchEntry(_, 1)) => {}
+            _ => panic!("expected an InvalidCatchEntry error"),
+        }
+    }
+}
@@ -0,0 +1,324 @@
+//! Spawning and communicating with OS child processes.
+//!
+//! The standard input and output streams of a child process can be connected
+//! to pipes. These pipes are put in non-blocking mode, allowing a process to be
+//! suspended (instead of blocking the OS thread) when reading from or writing
+//! to a pipe would block.
+//!
+//! There is no descriptor to poll for when waiting for a child process to
+//! terminate. Instead, the process waiting for the child is suspended until
+//! the VM receives the SIGCHLD signal, after which it checks again if the
+//! child terminated.
+//!
+//! A child that is dropped before it terminated is reaped by a separate thread
+//! once it terminates, instead of lingering around as a zombie process.
+
+use libc;
+use std::io;
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::os::unix::process::ExitStatusExt;
+use std::process::{Child, ChildStderr, ChildStdin, ChildStdout, Command};
+use std::process::{ExitStatus, Stdio};
+use std::thread;
+
+use io::{read_available, read_once};
+
+/// The stream is inherited from the parent process.
+pub const STDIO_INHERIT: i64 = 0;
+
+/// The stream is connected to /dev/null.
+pub const STDIO_NULL: i64 = 1;
+
+/// The stream is connected to a pipe.
+pub const STDIO_PIPE: i64 = 2;
+
+/// Reads from the standard output stream of a child.
+pub const OUTPUT_STDOUT: i64 = 0;
+
+/// Reads from the standard error stream of a child.
+pub const OUTPUT_STDERR: i64 = 1;
+
+/// An OS child process, spawned by the VM.
+pub struct ChildProcess {
+    pub child: Child,
+}
+
+impl ChildProcess {
+    pub fn new(child: Child) -> Self {
+        ChildProcess { child }
+    }
+}
+
+impl Drop for ChildProcess {
+    fn drop(&mut self) {
+        // The exit status of a child is stored once it has been waited for, in
+        // which case there is nothing left to reap.
+        if let Ok(None) = self.child.try_wait() {
+            let pid = self.child.id() as libc::pid_t;
+
+            // If we can't start the thread there's nothing we can do, other
+            // than leaving the child around as a zombie.
+            let _ = thread::Builder::new()
+                .name("child reaper".to_string())
+                .spawn(move || reap(pid));
+        }
+    }
+}
+
+pub fn stdio_for_integer(mode: i64) -> Result<Stdio, String> {
+    match mode {
+        STDIO_INHERIT => Ok(Stdio::inherit()),
+        STDIO_NULL => Ok(Stdio::null()),
+        STDIO_PIPE => Ok(Stdio::piped()),
+        _ => Err(format!("Invalid standard stream mode: {}", mode)),
+    }
+}
+
+/// A pipe connected to the standard output or error stream of a child.
+pub enum Output<'a> {
+    Stdout(&'a mut ChildStdout),
+    Stderr(&'a mut ChildStderr),
+}
+
+impl<'a> Output<'a> {
+    /// Reads data into a buffer, returning the number of bytes read.
+    ///
+    /// If no size is given, all data that is currently available is read.
+    pub fn read(
+        &mut self,
+        buffer: &mut Vec<u8>,
+        size: Option<usize>,
+    ) -> io::Result<usize> {
+        let stream: &mut io::Read = match *self {
+            Output::Stdout(ref mut stream) => stream,
+            Output::Stderr(ref mut stream) => stream,
+        };
+
+        if let Some(size) = size {
+            read_once(stream, buffer, size)
+        } else {
+            read_available(stream, buffer)
+        }
+    }
+}
+
+impl<'a> AsRawFd for Output<'a> {
+    fn as_raw_fd(&self) -> RawFd {
+        match *self {
+            Output::Stdout(ref stream) => stream.as_raw_fd(),
+            Output::Stderr(ref stream) => stream.as_raw_fd(),
+        }
+    }
+}
+
+/// Spawns a child process, putting any pipes in non-blocking mode.
+pub fn spawn(command: &mut Command) -> io::Result<Child> {
+    let child = command.spawn()?;
+
+    if let Some(ref stdin) = child.stdin {
+        set_nonblocking(stdin.as_raw_fd())?;
+    }
+
+    if let Some(ref stdout) = child.stdout {
+        set_nonblocking(stdout.as_raw_fd())?;
+    }
+
+    if let Some(ref stderr) = child.stderr {
+        set_nonblocking(stderr.as_raw_fd())?;
+    }
+
+    Ok(child)
+}
+
+/// Returns the pipe connected to the standard input stream of a child.
+pub fn input(child: &mut Child) -> Result<&mut ChildStdin, String> {
+    child.stdin.as_mut().ok_or_else(|| not_piped("input"))
+}
+
+/// Closes the standard input stream of a child, if it is connected to a pipe.
+pub fn close_input(child: &mut Child) {
+    drop(child.stdin.take());
+}
+
+/// Returns the pipe connected to the given output stream of a child.
+pub fn output(child: &mut Child, stream: i64) -> Result<Output, String> {
+    match stream {
+        OUTPUT_STDOUT => child
+            .stdout
+            .as_mut()
+            .map(Output::Stdout)
+            .ok_or_else(|| not_piped("output")),
+        OUTPUT_STDERR => child
+            .stderr
+            .as_mut()
+            .map(Output::Stderr)
+            .ok_or_else(|| not_piped("error")),
+        _ => Err(format!("Invalid output stream: {}", stream)),
+    }
+}
+
+/// Returns the exit status of a child, without waiting for it to terminate.
+///
+/// The standard input stream of the child is closed, so a child reading its
+/// input until the end of the stream can terminate.
+pub fn try_wait(child: &mut Child) -> io::Result<Option<ExitStatus>> {
+    close_input(child);
+
+    child.try_wait()
+}
+
+/// Returns the exit code of a child, if it terminated normally.
+pub fn exit_code(status: ExitStatus) -> Option<i64> {
+    status.code().map(i64::from)
+}
+
+/// Returns the signal that terminated a child, if any.
+pub fn exit_signal(status: ExitStatus) -> Option<i64> {
+    status.signal().map(i64::from)
+}
+
+/// Waits for a child to terminate, discarding its exit status.
+fn reap(pid: libc::pid_t) {
+    let mut status = 0;
+
+    while unsafe { libc::waitpid(pid, &mut status, 0) } == -1 {
+        if io::Error::last_os_error().kind() != io::ErrorKind::Interrupted {
+            break;
+        }
+    }
+}
+
+fn not_piped(name: &str) -> String {
+    format!("The standard {} stream is not connected to a pipe", name)
+}
+
+fn set_nonblocking(fd: RawFd) -> io::Result<()> {
+    let flags = unsafe { libc::fcntl(fd, libc::F_GETFL) };
+
+    if flags == -1 {
+        return Err(io::Error::last_os_error());
+    }
+
+    let result =
+        unsafe { libc::fcntl(fd, libc::F_SETFL, flags | libc::O_NONBLOCK) };
+
+    if result == -1 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::time::Duration;
+
+    fn wait(child: &mut Child) -> ExitStatus {
+        loop {
+            if let Some(status) = try_wait(child).unwrap() {
+                return status;
+            }
+
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn test_stdio_for_integer() {
+        assert!(stdio_for_integer(STDIO_INHERIT).is_ok());
+        assert!(stdio_for_integer(STDIO_NULL).is_ok());
+        assert!(stdio_for_integer(STDIO_PIPE).is_ok());
+        assert!(stdio_for_integer(3).is_err());
+    }
+
+    #[test]
+    fn test_spawn_with_pipes() {
+        let mut child = spawn(
+            Command::new("cat")
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped()),
+        )
+        .unwrap();
+
+        let mut buffer = Vec::new();
+
+        assert_eq!(
+            output(&mut child, OUTPUT_STDOUT)
+                .unwrap()
+                .read(&mut buffer, Some(5))
+                .unwrap_err()
+                .kind(),
+            io::ErrorKind::WouldBlock
+        );
+
+        input(&mut child).unwrap().write_all(b"hello").unwrap();
+
+        let status = wait(&mut child);
+
+        output(&mut child, OUTPUT_STDOUT)
+            .unwrap()
+            .read(&mut buffer, None)
+            .unwrap();
+
+        assert!(status.success());
+        assert_eq!(buffer, b"hello".to_vec());
+    }
+
+    #[test]
+    fn test_output_without_pipe() {
+        let mut child =
+            spawn(Command::new("true").stdout(Stdio::null())).unwrap();
+
+        assert!(output(&mut child, OUTPUT_STDOUT).is_err());
+        assert!(output(&mut child, OUTPUT_STDERR).is_err());
+        assert!(output(&mut child, 2).is_err());
+        assert!(input(&mut child).is_err());
+
+        wait(&mut child);
+    }
+
+    #[test]
+    fn test_exit_code() {
+        let mut child =
+            spawn(Command::new("sh").args(&["-c", "exit 3"])).unwrap();
+
+        let status = wait(&mut child);
+
+        assert_eq!(exit_code(status), Some(3));
+        assert_eq!(exit_signal(status), None);
+    }
+
+    #[test]
+    fn test_drop_reaps_child() {
+        let child = spawn(Command::new("sleep").arg("0.05")).unwrap();
+        let pid = child.id() as libc::pid_t;
+
+        drop(ChildProcess::new(child));
+
+        // A zombie process still exists, so we wait until the PID no longer
+        // refers to any process.
+        for _ in 0..1000 {
+            if unsafe { libc::kill(pid, 0) } == -1 {
+                break;
+            }
+
+            thread::sleep(Duration::from_millis(5));
+        }
+
+        assert_eq!(unsafe { libc::kill(pid, 0) }, -1);
+    }
+
+    #[test]
+    fn test_exit_signal() {
+        let mut child = spawn(&mut Command::new("sleep").arg("10")).unwrap();
+
+        child.kill().unwrap();
+
+        let status = wait(&mut child);
+
+        assert_eq!(exit_code(status), None);
+        assert_eq!(exit_signal(status), Some(i64::from(libc::SIGKILL)));
+    }
+}
@@ -11,6 +11,7 @@
 use num_cpus;
 use std::env;
 use std::path::PathBuf;
+use std::u16;
 
 /// Sets a configuration field based on an environment variable.
 macro_rules! set_from_env {
@@ -28,6 +29,9 @@ pub struct Config {
     /// The directories to search in for extra bytecode files to run.
     pub directories: Vec<PathBuf>,
 
+    /// The command-line arguments to pass to the program.
+    pub arguments: Vec<String>,
+
     /// The number of primary process threads to run.
     pub primary_threads: usize,
 
@@ -51,6 +55,9 @@ pub struct Config {
 
     /// The number of milliseconds to wait between checking for suspended
     /// processes.
+    ///
+    /// This setting is deprecated and no longer has any effect, as suspended
+    /// processes are resumed as soon as their timeouts expire.
     pub suspension_check_interval: u64,
 
     /// The amount of memory that can be allocated in the young generation
@@ -78,6 +85,21 @@ pub struct Config {
     /// The percentage of memory in the mailbox heap that should be used before
     /// increasing the size.
     pub mailbox_growth_threshold: f64,
+
+    /// The maximum size in bytes of a string or byte array in a bytecode file.
+    pub bytecode_max_string_length: usize,
+
+    /// The maximum nesting depth of code objects in a bytecode file.
+    pub bytecode_max_code_depth: usize,
+
+    /// The maximum number of literals (or arguments) a single code object in a
+    /// bytecode file can define.
+    pub bytecode_max_literals: usize,
+
+    /// When enabled, a panic in a process other than the main process only
+    /// terminates that process. When disabled, any panic terminates the VM.
+    /// Defaults to false, and can be enabled using `INKO_ISOLATE_PANICS`.
+    pub isolate_panics: bool,
 }
 
 impl Config {
@@ -86,6 +108,7 @@ impl Config {
 
         Config {
             directories: Vec::new(),
+            arguments: Vec::new(),
             primary_threads: cpu_count,
             gc_threads: 2,
             finalizer_threads: 2,
@@ -102,6 +125,12 @@ impl Config {
             mailbox_threshold: 32 * 1024,
             mailbox_growth_factor: 1.5,
             mailbox_growth_threshold: 0.9,
+            bytecode_max_string_length: 16 * 1024 * 1024,
+            bytecode_max_code_depth: 256,
+            // Literals are referred to using 16 bits integers, so more than
+            // this can never be used.
+            bytecode_max_literals: u16::MAX as usize + 1,
+            isolate_panics: false,
         }
     }
 
@@ -153,12 +182,39 @@ impl Config {
             "MAILBOX_GROWTH_THRESHOLD",
             f64
         );
+
+        set_from_env!(
+            self,
+            bytecode_max_string_length,
+            "BYTECODE_MAX_STRING_LENGTH",
+            usize
+        );
+
+        set_from_env!(
+            self,
+            bytecode_max_code_depth,
+            "BYTECODE_MAX_CODE_DEPTH",
+            usize
+        );
+
+        set_from_env!(
+            self,
+            bytecode_max_literals,
+            "BYTECODE_MAX_LITERALS",
+            usize
+        );
+
+        set_from_env!(self, isolate_panics, "ISOLATE_PANICS", bool);
     }
 
     pub fn add_directory(&mut self, path: String) {
         self.directories.push(PathBuf::from(path));
     }
 
+    pub fn add_argument(&mut self, argument: String) {
+        self.arguments.push(argument);
+    }
+
     pub fn set_primary_threads(&mut self, threads: usize) {
         if threads == 0 {
             self.primary_threads = 1;
@@ -189,6 +245,10 @@ impl Config {
         }
     }
 
+    /// Sets the suspension check interval.
+    ///
+    /// This method is deprecated, and only exists for backwards compatibility.
+    /// The interval is no longer used by the VM.
     pub fn set_suspension_check_interval(&mut self, interval: u64) {
         if interval > 0 {
             self.suspension_check_interval = interval;
@@ -209,12 +269,14 @@ mod tests {
         assert!(config.primary_threads >= 1);
         assert!(config.gc_threads >= 1);
         assert_eq!(config.reductions, 1000);
+        assert_eq!(config.isolate_panics, false);
     }
 
     #[test]
     fn test_populate_from_env() {
         env::set_var("INKO_PRIMARY_THREADS", "42");
         env::set_var("INKO_HEAP_GROWTH_FACTOR", "4.2");
+        env::set_var("INKO_ISOLATE_PANICS", "true");
 
         let mut config = Config::new();
 
@@ -223,9 +285,11 @@ mod tests {
         // Unset before any assertions may fail.
         env::remove_var("INKO_PROCESS_THREADS");
         env::remove_var("INKO_HEAP_GROWTH_FACTOR");
+        env::remove_var("INKO_ISOLATE_PANICS");
 
         assert_eq!(config.primary_threads, 42);
         assert_eq!(config.heap_growth_factor, 4.2);
+        assert!(config.isolate_panics);
     }
 
     #[test]
@@ -237,6 +301,15 @@ mod tests {
         assert_eq!(config.directories.len(), 1);
     }
 
+    #[test]
+    fn test_add_argument() {
+        let mut config = Config::new();
+
+        config.add_argument("foo".to_string());
+
+        assert_eq!(config.arguments, vec!["foo".to_string()]);
+    }
+
     #[test]
     fn test_set_primary_threads() {
         let mut config = Config::new();
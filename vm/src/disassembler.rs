@@ -0,0 +1,294 @@
+//! Human readable listings of parsed bytecode.
+//!
+//! This module turns a CompiledCode (and all the code objects stored in it)
+//! into a textual listing, making it possible to inspect the output of the
+//! compiler without having to decode bytecode files by hand.
+//!
+//! To disassemble a bytecode file you can use the `disassemble_file` function:
+//!
+//!     let state = State::new(Config::new());
+//!     let listing = disassembler::disassemble_file(&state, "foo.inkoc");
+//!
+//!     println!("{}", listing.unwrap());
+use std::fmt::Write;
+
+use bytecode_parser::{self, ParserResult};
+use bytecode_verifier::{self, Operand};
+use compiled_code::CompiledCode;
+use object_pointer::ObjectPointer;
+use object_value::ObjectValue;
+use vm::instruction::Instruction;
+use vm::state::RcState;
+
+/// The number of spaces to indent nested sections with.
+const INDENT: usize = 2;
+
+/// Parses a bytecode file and returns its disassembled listing.
+pub fn disassemble_file(state: &RcState, path: &str) -> ParserResult<String> {
+    let code = bytecode_parser::parse_file(state, path)?;
+
+    Ok(disassemble(&code))
+}
+
+/// Returns a listing of a CompiledCode and all the code objects stored in it.
+pub fn disassemble(code: &CompiledCode) -> String {
+    let mut buffer = String::new();
+
+    write_code(&mut buffer, code, 0);
+
+    buffer
+}
+
+fn write_code(buffer: &mut String, code: &CompiledCode, depth: usize) {
+    let pad = " ".repeat(depth * INDENT);
+    let field_pad = " ".repeat((depth + 1) * INDENT);
+    let item_pad = " ".repeat((depth + 2) * INDENT);
+
+    let arguments: Vec<String> =
+        code.arguments.iter().map(|arg| string_of(*arg)).collect();
+
+    let _ = writeln!(
+        buffer,
+        "{}CompiledCode {} ({}:{})",
+        pad,
+        string_of(code.name),
+        string_of(code.file),
+        code.line
+    );
+
+    write_field(buffer, &field_pad, "arguments", arguments.join(", "));
+    write_field(
+        buffer,
+        &field_pad,
+        "required arguments",
+        code.required_arguments,
+    );
+    write_field(buffer, &field_pad, "rest argument", code.rest_argument);
+    write_field(buffer, &field_pad, "locals", code.locals);
+    write_field(buffer, &field_pad, "registers", code.registers);
+    write_field(buffer, &field_pad, "captures", code.captures);
+
+    let _ = writeln!(buffer, "{}literals:", field_pad);
+
+    for (index, literal) in code.literals.iter().enumerate() {
+        let _ = writeln!(
+            buffer,
+            "{}{}: {}",
+            item_pad,
+            index,
+            format_literal(*literal)
+        );
+    }
+
+    let _ = writeln!(buffer, "{}instructions:", field_pad);
+
+    for (index, instruction) in code.instructions.iter().enumerate() {
+        let _ = writeln!(
+            buffer,
+            "{}{}: {}",
+            item_pad,
+            index,
+            format_instruction(code, instruction)
+        );
+    }
+
+    let _ = writeln!(buffer, "{}catch table:", field_pad);
+
+    for (index, entry) in code.catch_table.entries.iter().enumerate() {
+        let _ = writeln!(
+            buffer,
+            "{}{}: start {}, end {}, jump to {}, register r{}",
+            item_pad,
+            index,
+            entry.start,
+            entry.end,
+            entry.jump_to,
+            entry.register
+        );
+    }
+
+    let _ = writeln!(buffer, "{}code objects:", field_pad);
+
+    for child in &code.code_objects {
+        write_code(buffer, child, depth + 2);
+    }
+}
+
+fn write_field<T: ToString>(
+    buffer: &mut String,
+    pad: &str,
+    name: &str,
+    value: T,
+) {
+    let value = value.to_string();
+
+    if value.is_empty() {
+        let _ = writeln!(buffer, "{}{}:", pad, name);
+    } else {
+        let _ = writeln!(buffer, "{}{}: {}", pad, name, value);
+    }
+}
+
+fn format_instruction(
+    code: &CompiledCode,
+    instruction: &Instruction,
+) -> String {
+    let arguments: Vec<String> = bytecode_verifier::operand_types(instruction)
+        .into_iter()
+        .zip(&instruction.arguments)
+        .map(|(operand, value)| format_argument(code, operand, *value))
+        .collect();
+
+    if arguments.is_empty() {
+        format!(
+            "{:?} (line {})",
+            instruction.instruction_type, instruction.line
+        )
+    } else {
+        format!(
+            "{:?} {} (line {})",
+            instruction.instruction_type,
+            arguments.join(", "),
+            instruction.line
+        )
+    }
+}
+
+/// Formats an instruction argument according to the type of value it refers
+/// to, such as `r0` for the first register.
+fn format_argument(
+    code: &CompiledCode,
+    operand: Operand,
+    value: u16,
+) -> String {
+    let index = value as usize;
+
+    match operand {
+        Operand::Register => format!("r{}", value),
+        Operand::Local => format!("local[{}]", value),
+        Operand::Literal => match code.literals.get(index) {
+            Some(literal) => {
+                format!("lit[{}] = {}", value, format_literal(*literal))
+            }
+            None => format!("lit[{}]", value),
+        },
+        Operand::CodeObject => match code.code_objects.get(index) {
+            Some(child) => {
+                format!("code[{}] = {}", value, string_of(child.name))
+            }
+            None => format!("code[{}]", value),
+        },
+        Operand::Jump => format!("@{}", value),
+        Operand::Value => value.to_string(),
+    }
+}
+
+fn format_literal(pointer: ObjectPointer) -> String {
+    if pointer.is_tagged_integer() {
+        return pointer.integer_value().unwrap_or(0).to_string();
+    }
+
+    match pointer.get().value {
+        ObjectValue::Integer(value) => value.to_string(),
+        ObjectValue::BigInt(ref value) => value.to_string(),
+        ObjectValue::Float(value) => format!("{:?}", value),
+        ObjectValue::String(ref value) => format!("{:?}", value.as_str()),
+        ObjectValue::InternedString(ref value) => {
+            format!("{:?}", value.as_str())
+        }
+        _ => "<unknown literal>".to_string(),
+    }
+}
+
+fn string_of(pointer: ObjectPointer) -> String {
+    pointer
+        .string_value()
+        .map(|string| string.clone())
+        .unwrap_or_else(|_| String::new())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use catch_table::CatchEntry;
+    use config::Config;
+    use num_bigint::BigInt;
+    use vm::instruction::InstructionType;
+    use vm::state::State;
+
+    #[test]
+    fn test_disassemble() {
+        let state = State::new(Config::new());
+        let name = state.intern(&"main".to_string());
+        let file = state.intern(&"foo.inko".to_string());
+        let child_name = state.intern(&"<block>".to_string());
+
+        let mut code = CompiledCode::new(
+            name,
+            file,
+            1,
+            vec![
+                Instruction::new(InstructionType::SetLiteral, vec![0, 0], 2),
+                Instruction::new(InstructionType::SetLocal, vec![0, 0], 2),
+                Instruction::new(InstructionType::SetBlock, vec![0, 0], 3),
+                Instruction::new(InstructionType::Goto, vec![4], 3),
+                Instruction::new(InstructionType::Return, vec![0, 0], 3),
+            ],
+        );
+
+        let child = CompiledCode::new(
+            child_name,
+            file,
+            4,
+            vec![Instruction::new(InstructionType::Return, vec![], 5)],
+        );
+
+        code.arguments.push(state.intern(&"self".to_string()));
+        code.locals = 1;
+        code.registers = 1;
+        code.literals.push(ObjectPointer::integer(10));
+        code.literals.push(state.allocate_permanent_float(1.5));
+        code.literals.push(state.intern(&"hello".to_string()));
+        code.literals
+            .push(state.allocate_permanent_bigint(BigInt::from(42)));
+        code.catch_table.entries.push(CatchEntry::new(0, 1, 1, 0));
+        code.code_objects.push(child);
+
+        let expected = "CompiledCode main (foo.inko:1)
+  arguments: self
+  required arguments: 0
+  rest argument: false
+  locals: 1
+  registers: 1
+  captures: false
+  literals:
+    0: 10
+    1: 1.5
+    2: \"hello\"
+    3: 42
+  instructions:
+    0: SetLiteral r0, lit[0] = 10 (line 2)
+    1: SetLocal local[0], r0 (line 2)
+    2: SetBlock r0, code[0] = <block> (line 3)
+    3: Goto @4 (line 3)
+    4: Return 0, r0 (line 3)
+  catch table:
+    0: start 0, end 1, jump to 1, register r0
+  code objects:
+    CompiledCode <block> (foo.inko:4)
+      arguments:
+      required arguments: 0
+      rest argument: false
+      locals: 0
+      registers: 0
+      captures: false
+      literals:
+      instructions:
+        0: Return (line 5)
+      catch table:
+      code objects:
+";
+
+        assert_eq!(disassemble(&code), expected);
+    }
+}
@@ -0,0 +1,108 @@
+//! Helpers for reading and changing environment variables.
+//!
+//! The functions in `std::env` panic when given a name or value that can't be
+//! stored in the environment. Since these names and values come from Inko
+//! code, the functions in this module validate them first.
+//!
+//! Changing the environment is not thread-safe: the C library reads it without
+//! any synchronisation, for example when spawning a child process or when
+//! obtaining the local time. Changes should only be made when no other
+//! processes are running.
+
+use std::env;
+
+const INVALID_NAME: &str =
+    "Environment variable names can not be empty or contain \"=\" or NUL bytes.";
+
+const INVALID_VALUE: &str =
+    "Environment variable values can not contain NUL bytes.";
+
+/// Returns the value of an environment variable.
+///
+/// Names that can't be stored in the environment are never set, so `None` is
+/// returned for these names.
+pub fn get(name: &str) -> Option<String> {
+    if !is_valid_name(name) {
+        return None;
+    }
+
+    env::var_os(name).map(|value| value.to_string_lossy().to_string())
+}
+
+/// Sets an environment variable to the given value.
+pub fn set(name: &str, value: &str) -> Result<(), String> {
+    if !is_valid_name(name) {
+        return Err(INVALID_NAME.to_string());
+    }
+
+    if value.contains('\0') {
+        return Err(INVALID_VALUE.to_string());
+    }
+
+    env::set_var(name, value);
+
+    Ok(())
+}
+
+/// Removes an environment variable.
+pub fn remove(name: &str) -> Result<(), String> {
+    if !is_valid_name(name) {
+        return Err(INVALID_NAME.to_string());
+    }
+
+    env::remove_var(name);
+
+    Ok(())
+}
+
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty() && !name.contains('=') && !name.contains('\0')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_invalid_name() {
+        assert!(get("").is_none());
+        assert!(get("FOO=BAR").is_none());
+        assert!(get("FOO\0BAR").is_none());
+    }
+
+    #[test]
+    fn test_set_and_get() {
+        assert!(set("INKO_TEST_ENVIRONMENT_SET", "foo").is_ok());
+        assert_eq!(get("INKO_TEST_ENVIRONMENT_SET"), Some("foo".to_string()));
+
+        env::remove_var("INKO_TEST_ENVIRONMENT_SET");
+    }
+
+    #[test]
+    fn test_set_invalid_name() {
+        assert!(set("", "foo").is_err());
+        assert!(set("FOO=BAR", "foo").is_err());
+        assert!(set("FOO\0BAR", "foo").is_err());
+    }
+
+    #[test]
+    fn test_set_invalid_value() {
+        assert!(set("INKO_TEST_ENVIRONMENT_SET_INVALID", "f\0o").is_err());
+        assert!(env::var_os("INKO_TEST_ENVIRONMENT_SET_INVALID").is_none());
+    }
+
+    #[test]
+    fn test_remove() {
+        env::set_var("INKO_TEST_ENVIRONMENT_REMOVE", "foo");
+
+        assert!(remove("INKO_TEST_ENVIRONMENT_REMOVE").is_ok());
+        assert!(env::var_os("INKO_TEST_ENVIRONMENT_REMOVE").is_none());
+    }
+
+    #[test]
+    fn test_remove_invalid_name() {
+        assert!(remove("").is_err());
+        assert!(remove("FOO=BAR").is_err());
+        assert!(remove("FOO\0BAR").is_err());
+    }
+}
@@ -44,6 +44,10 @@ impl Hasher {
         value.hash(&mut self.hasher);
     }
 
+    pub fn write_reference(&mut self, value: u64) {
+        value.hash(&mut self.hasher);
+    }
+
     pub fn finish(&mut self) -> i64 {
         let hash = self.hasher.finish();
 
@@ -106,6 +110,21 @@ mod tests {
         assert_eq!(hash1, hash2);
     }
 
+    #[test]
+    fn test_write_reference() {
+        let mut hasher = Hasher::new();
+
+        hasher.write_reference(4);
+
+        let hash1 = hasher.finish();
+
+        hasher.write_reference(4);
+
+        let hash2 = hasher.finish();
+
+        assert_eq!(hash1, hash2);
+    }
+
     #[test]
     fn test_finish() {
         let mut hasher = Hasher::new();
@@ -4,6 +4,7 @@
 //! objects into a heap.
 
 use block::Block;
+use error_messages;
 use object::{AttributesMap, Object};
 use object_pointer::ObjectPointer;
 use object_value;
@@ -13,12 +14,24 @@ pub trait CopyObject: Sized {
     /// Allocates a copied object.
     fn allocate_copy(&mut self, Object) -> ObjectPointer;
 
+    /// Performs a deep copy of the given pointer, panicking if the object can
+    /// not be copied.
+    fn copy_object(&mut self, to_copy_ptr: ObjectPointer) -> ObjectPointer {
+        self.try_copy_object(to_copy_ptr)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
     /// Performs a deep copy of the given pointer.
     ///
-    /// The copy of the input object is allocated on the current heap.
-    fn copy_object(&mut self, to_copy_ptr: ObjectPointer) -> ObjectPointer {
+    /// The copy of the input object is allocated on the current heap. An error
+    /// is returned if the object contains a value that can't be copied, such
+    /// as a file or a child process, or if a socket could not be cloned.
+    fn try_copy_object(
+        &mut self,
+        to_copy_ptr: ObjectPointer,
+    ) -> Result<ObjectPointer, String> {
         if to_copy_ptr.is_permanent() {
-            return to_copy_ptr;
+            return Ok(to_copy_ptr);
         }
 
         let to_copy = to_copy_ptr.get();
@@ -38,23 +51,26 @@ pub trait CopyObject: Sized {
                 object_value::interned_string(*string.clone())
             }
             ObjectValue::Array(ref raw_vec) => {
-                let new_map =
-                    raw_vec.iter().map(|val_ptr| self.copy_object(*val_ptr));
+                let new_map = raw_vec
+                    .iter()
+                    .map(|val_ptr| self.try_copy_object(*val_ptr));
 
-                object_value::array(new_map.collect::<Vec<_>>())
+                object_value::array(new_map.collect::<Result<Vec<_>, _>>()?)
             }
             ObjectValue::File(_) => {
-                panic!("ObjectValue::File can not be cloned");
+                return Err(
+                    "Files can not be sent between processes".to_string()
+                );
             }
             ObjectValue::Block(ref block) => {
-                let new_binding = block.binding.clone_to(self);
+                let new_binding = block.binding.clone_to(self)?;
                 let new_scope = block.global_scope;
                 let new_block = Block::new(block.code, new_binding, new_scope);
 
                 object_value::block(new_block)
             }
             ObjectValue::Binding(ref binding) => {
-                let new_binding = binding.clone_to(self);
+                let new_binding = binding.clone_to(self)?;
 
                 object_value::binding(new_binding)
             }
@@ -64,10 +80,59 @@ pub trait CopyObject: Sized {
             ObjectValue::ByteArray(ref byte_array) => {
                 ObjectValue::ByteArray(byte_array.clone())
             }
+            ObjectValue::Reference(id) => object_value::reference(id),
+            ObjectValue::TcpListener(ref listener) => {
+                let clone = listener
+                    .try_clone()
+                    .map_err(|error| error_messages::from_io_error(&error))?;
+
+                object_value::tcp_listener(clone)
+            }
+            ObjectValue::TcpStream(ref stream) => {
+                let clone = stream
+                    .try_clone()
+                    .map_err(|error| error_messages::from_io_error(&error))?;
+
+                object_value::tcp_stream(clone)
+            }
+            ObjectValue::UdpSocket(ref socket) => {
+                let clone = socket
+                    .try_clone()
+                    .map_err(|error| error_messages::from_io_error(&error))?;
+
+                object_value::udp_socket(clone)
+            }
+            ObjectValue::UnixListener(ref listener) => {
+                let clone = listener
+                    .try_clone()
+                    .map_err(|error| error_messages::from_io_error(&error))?;
+
+                object_value::unix_listener(clone)
+            }
+            ObjectValue::UnixStream(ref stream) => {
+                let clone = stream
+                    .try_clone()
+                    .map_err(|error| error_messages::from_io_error(&error))?;
+
+                object_value::unix_stream(clone)
+            }
+            ObjectValue::UnixDatagram(ref socket) => {
+                let clone = socket
+                    .try_clone()
+                    .map_err(|error| error_messages::from_io_error(&error))?;
+
+                object_value::unix_datagram(clone)
+            }
+            ObjectValue::ChildProcess(_) => {
+                return Err(
+                    "Child processes can not be sent between processes"
+                        .to_string(),
+                );
+            }
         };
 
         let mut copy = if let Some(proto_ptr) = to_copy.prototype() {
-            let proto_copy = self.copy_object(proto_ptr);
+            let proto_copy = self.try_copy_object(proto_ptr)?;
 
             Object::with_prototype(value_copy, proto_copy)
         } else {
@@ -78,8 +143,8 @@ pub trait CopyObject: Sized {
             let mut map_copy = AttributesMap::default();
 
             for (key, val) in map.iter() {
-                let key_copy = self.copy_object(*key);
-                let val_copy = self.copy_object(*val);
+                let key_copy = self.try_copy_object(*key)?;
+                let val_copy = self.try_copy_object(*val)?;
 
                 map_copy.insert(key_copy, val_copy);
             }
@@ -87,7 +152,7 @@ pub trait CopyObject: Sized {
             copy.set_attributes_map(map_copy);
         }
 
-        self.allocate_copy(copy)
+        Ok(self.allocate_copy(copy))
     }
 
     /// Performs a deep move of the given pointer.
@@ -161,9 +226,13 @@ mod tests {
     use global_scope::{GlobalScope, GlobalScopePointer};
     use immix::global_allocator::GlobalAllocator;
     use immix::local_allocator::LocalAllocator;
+    use net::Socket;
     use object::Object;
     use object_pointer::ObjectPointer;
     use object_value;
+    use std::net::{TcpListener, TcpStream};
+    use std::os::unix::net::UnixStream;
+    use std::process::Command;
     use vm::state::{RcState, State};
 
     struct DummyAllocator {
@@ -266,6 +335,77 @@ mod tests {
         assert_eq!(copy.string_value().unwrap(), &"a".to_string());
     }
 
+    #[test]
+    fn test_copy_reference() {
+        let mut dummy = DummyAllocator::new();
+        let pointer = dummy
+            .allocator
+            .allocate_without_prototype(object_value::reference(4));
+
+        let copy = dummy.copy_object(pointer);
+
+        assert!(copy.get().value.is_reference());
+        assert_eq!(copy.reference_value().unwrap(), 4);
+    }
+
+    #[test]
+    fn test_copy_tcp_stream() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let stream =
+            TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+
+        let mut dummy = DummyAllocator::new();
+        let pointer = dummy
+            .allocator
+            .allocate_without_prototype(object_value::tcp_stream(stream));
+
+        let copy = dummy.copy_object(pointer);
+
+        assert_eq!(
+            copy.tcp_stream_value().unwrap().local_addr().unwrap(),
+            pointer.tcp_stream_value().unwrap().local_addr().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_copy_unix_stream() {
+        let (first, second) = UnixStream::pair().unwrap();
+
+        let mut dummy = DummyAllocator::new();
+        let pointer = dummy
+            .allocator
+            .allocate_without_prototype(object_value::unix_stream(first));
+
+        let copy = dummy.copy_object(pointer);
+        let mut buffer = Vec::new();
+
+        drop(pointer.get_mut().value.take());
+
+        copy.socket_value().unwrap().write(b"hello").unwrap();
+        second.set_nonblocking(true).unwrap();
+
+        assert_eq!(
+            Socket::UnixStream(&second).read(&mut buffer, None).unwrap(),
+            5
+        );
+    }
+
+    #[test]
+    fn test_try_copy_child_process() {
+        let mut dummy = DummyAllocator::new();
+        let child = Command::new("true").spawn().unwrap();
+        let pointer = dummy
+            .allocator
+            .allocate_without_prototype(object_value::child_process(child));
+
+        let array = dummy
+            .allocator
+            .allocate_without_prototype(object_value::array(vec![pointer]));
+
+        assert!(dummy.try_copy_object(pointer).is_err());
+        assert!(dummy.try_copy_object(array).is_err());
+    }
+
     #[test]
     fn test_copy_array() {
         let mut dummy = DummyAllocator::new();
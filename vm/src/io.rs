@@ -1,5 +1,8 @@
 use object_pointer::ObjectPointer;
-use std::io::{Error as IOError, Read};
+use std::io::{Error as IOError, ErrorKind, Read, Result as IOResult};
+
+/// The number of bytes to read at once when reading all available data.
+const READ_CHUNK_SIZE: usize = 8 * 1024;
 
 pub enum ReadResult<T> {
     /// The value to return in case of a successful operation.
@@ -44,3 +47,86 @@ pub fn read_from_stream(
         Err(err) => ReadResult::Err(err),
     }
 }
+
+/// Reads up to the given number of bytes from a stream into a byte array,
+/// using a single read.
+///
+/// Unlike `read_from_stream` this doesn't wait for the end of the stream,
+/// making it suitable for non-blocking streams such as sockets.
+pub fn read_once(
+    stream: &mut Read,
+    buffer: &mut Vec<u8>,
+    amount: usize,
+) -> IOResult<usize> {
+    let start = buffer.len();
+
+    buffer.resize(start + amount, 0);
+
+    let result = stream.read(&mut buffer[start..]);
+    let read = *result.as_ref().unwrap_or(&0);
+
+    buffer.truncate(start + read);
+    buffer.shrink_to_fit();
+
+    result
+}
+
+/// Reads all data that is currently available from a non-blocking stream.
+///
+/// The error of the stream is returned if no data could be read at all, such
+/// as when no data is available yet.
+pub fn read_available(
+    stream: &mut Read,
+    buffer: &mut Vec<u8>,
+) -> IOResult<usize> {
+    let mut total = 0;
+
+    loop {
+        match read_once(stream, buffer, READ_CHUNK_SIZE) {
+            Ok(0) => return Ok(total),
+            Ok(read) => total += read,
+            // Any error is reported the next time we read from the stream, so
+            // the data read so far isn't lost.
+            Err(_) if total > 0 => return Ok(total),
+            Err(ref error) if error.kind() == ErrorKind::Interrupted => {}
+            Err(error) => return Err(error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_read_once() {
+        let mut stream = Cursor::new(vec![1, 2, 3]);
+        let mut buffer = vec![0];
+
+        assert_eq!(read_once(&mut stream, &mut buffer, 2).unwrap(), 2);
+        assert_eq!(buffer, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_read_once_at_end_of_stream() {
+        let mut stream = Cursor::new(Vec::new());
+        let mut buffer = Vec::new();
+
+        assert_eq!(read_once(&mut stream, &mut buffer, 2).unwrap(), 0);
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn test_read_available() {
+        let mut stream = Cursor::new(vec![1; READ_CHUNK_SIZE + 1]);
+        let mut buffer = Vec::new();
+
+        assert_eq!(
+            read_available(&mut stream, &mut buffer).unwrap(),
+            READ_CHUNK_SIZE + 1
+        );
+
+        assert_eq!(buffer.len(), READ_CHUNK_SIZE + 1);
+    }
+}
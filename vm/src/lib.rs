@@ -1,5 +1,5 @@
 #![cfg_attr(feature = "prefetch", feature(core_intrinsics))]
-#![feature(alloc, alloc_system, allocator_api, raw_vec_internals)]
+#![feature(alloc, alloc_system, allocator_api, raw_vec_internals, try_from)]
 
 extern crate alloc_system;
 
@@ -10,6 +10,7 @@ extern crate alloc;
 extern crate colored;
 extern crate float_cmp;
 extern crate fnv;
+extern crate libc;
 extern crate num_cpus;
 extern crate num_integer;
 extern crate parking_lot;
@@ -21,16 +22,23 @@ extern crate time;
 pub mod macros;
 
 pub mod arc_without_weak;
+pub mod assembler;
 pub mod binding;
 pub mod block;
+pub mod bundle;
 pub mod byte_array;
 pub mod bytecode_parser;
+pub mod bytecode_verifier;
+pub mod bytecode_writer;
 pub mod catch_table;
+pub mod child_process;
 pub mod chunk;
 pub mod compiled_code;
 pub mod config;
 pub mod date_time;
 pub mod deref_pointer;
+pub mod disassembler;
+pub mod environment;
 pub mod error_messages;
 pub mod execution_context;
 pub mod filesystem;
@@ -43,6 +51,8 @@ pub mod io;
 pub mod mailbox;
 pub mod module;
 pub mod module_registry;
+pub mod net;
+pub mod network_poller;
 pub mod numeric;
 pub mod object;
 pub mod object_pointer;
@@ -50,14 +60,17 @@ pub mod object_value;
 pub mod pool;
 pub mod pools;
 pub mod process;
+pub mod process_registry;
 pub mod process_table;
 pub mod queue;
 pub mod register;
 pub mod runtime_panic;
+pub mod signal_handler;
 pub mod slicing;
 pub mod stacktrace;
 pub mod string_pool;
 pub mod suspension_list;
 pub mod tagged_pointer;
 pub mod timer;
+pub mod timer_list;
 pub mod vm;
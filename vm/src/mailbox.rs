@@ -1,5 +1,6 @@
 use parking_lot::Mutex;
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 use config::Config;
 use gc::work_list::WorkList;
@@ -8,12 +9,80 @@ use immix::global_allocator::RcGlobalAllocator;
 use immix::mailbox_allocator::MailboxAllocator;
 use object_pointer::ObjectPointer;
 
+/// The policy to apply when a message is sent to a full mailbox.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// The sender is suspended until space is available.
+    Suspend,
+
+    /// The message that is sent is discarded.
+    DropNewest,
+
+    /// The oldest message in the mailbox is discarded.
+    DropOldest,
+
+    /// An error is thrown in the sender.
+    Throw,
+}
+
+impl OverflowPolicy {
+    pub fn from_integer(value: i64) -> Option<Self> {
+        match value {
+            0 => Some(OverflowPolicy::Suspend),
+            1 => Some(OverflowPolicy::DropNewest),
+            2 => Some(OverflowPolicy::DropOldest),
+            3 => Some(OverflowPolicy::Throw),
+            _ => None,
+        }
+    }
+}
+
+/// The result of sending a message to a bounded mailbox.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SendResult {
+    /// The message was added to the mailbox.
+    Sent,
+
+    /// The mailbox is full, and the message was discarded.
+    Dropped,
+
+    /// The mailbox is full, and the sender should be suspended until space is
+    /// available.
+    Suspend,
+
+    /// The mailbox is full, and an error should be thrown in the sender.
+    Full,
+}
+
 pub struct Mailbox {
     pub external: VecDeque<ObjectPointer>,
     pub internal: VecDeque<ObjectPointer>,
     pub locals: VecDeque<ObjectPointer>,
+
+    /// Messages skipped by a selective receive, in the order they were
+    /// received. These messages reside on the process heap.
+    pub skipped: VecDeque<ObjectPointer>,
+
+    /// The message that is being tested by a selective receive.
+    pub candidate: Option<ObjectPointer>,
+
     pub allocator: MailboxAllocator,
     pub write_lock: Mutex<()>,
+
+    /// The number of messages in the mailbox, including the messages set
+    /// aside by a selective receive.
+    pub length: AtomicUsize,
+
+    /// The maximum number of messages the mailbox can hold, or 0 if the
+    /// mailbox is unbounded.
+    pub capacity: usize,
+
+    /// The policy to apply when a message is sent while the mailbox is full.
+    pub overflow: OverflowPolicy,
+
+    /// The messages of pending timers, mapped to the IDs of these timers.
+    /// These messages reside on the mailbox heap.
+    pub timers: HashMap<u64, ObjectPointer>,
 }
 
 impl Mailbox {
@@ -22,8 +91,14 @@ impl Mailbox {
             external: VecDeque::new(),
             internal: VecDeque::new(),
             locals: VecDeque::new(),
+            skipped: VecDeque::new(),
+            candidate: None,
             allocator: MailboxAllocator::new(global_allocator, config),
             write_lock: Mutex::new(()),
+            length: AtomicUsize::new(0),
+            capacity: 0,
+            overflow: OverflowPolicy::Suspend,
+            timers: HashMap::new(),
         }
     }
 
@@ -32,35 +107,232 @@ impl Mailbox {
 
         self.external
             .push_back(self.allocator.copy_object(original));
+
+        self.length.fetch_add(1, Ordering::AcqRel);
+    }
+
+    /// Sends a message from another process, taking the capacity of the
+    /// mailbox into account.
+    ///
+    /// An error is returned if the message can't be copied into the mailbox.
+    pub fn try_send_from_external(
+        &mut self,
+        original: ObjectPointer,
+    ) -> Result<SendResult, String> {
+        let _lock = self.write_lock.lock();
+        let mut drop_oldest = false;
+
+        if self.capacity > 0 && self.len() >= self.capacity {
+            match self.overflow {
+                OverflowPolicy::Suspend => return Ok(SendResult::Suspend),
+                OverflowPolicy::DropNewest => return Ok(SendResult::Dropped),
+                OverflowPolicy::Throw => return Ok(SendResult::Full),
+                OverflowPolicy::DropOldest => drop_oldest = true,
+            }
+        }
+
+        // The message is copied before discarding the oldest message, so a
+        // message that can't be copied doesn't cause another one to be lost.
+        let message = self.allocator.try_copy_object(original)?;
+
+        if drop_oldest {
+            let oldest =
+                pop(&mut self.locals, &mut self.internal, &mut self.external);
+
+            // Messages set aside by a selective receive can't be discarded,
+            // in which case we discard the new message instead.
+            if oldest.1.is_none() {
+                return Ok(SendResult::Dropped);
+            }
+
+            self.length.fetch_sub(1, Ordering::AcqRel);
+        }
+
+        self.external.push_back(message);
+        self.length.fetch_add(1, Ordering::AcqRel);
+
+        Ok(SendResult::Sent)
+    }
+
+    /// Stores the message of a timer, until the timer fires or is cancelled.
+    ///
+    /// An error is returned if the message can't be copied into the mailbox.
+    pub fn add_timer(
+        &mut self,
+        id: u64,
+        original: ObjectPointer,
+    ) -> Result<(), String> {
+        let _lock = self.write_lock.lock();
+        let message = self.allocator.try_copy_object(original)?;
+
+        self.timers.insert(id, message);
+
+        Ok(())
+    }
+
+    /// Delivers the message of a timer.
+    ///
+    /// The message of a periodic timer is copied, while the message of any
+    /// other timer is removed. False is returned if the timer was cancelled,
+    /// if the `finished` closure returns true while holding on to the write
+    /// lock, or if the message of a periodic timer could not be copied.
+    pub fn fire_timer<F>(
+        &mut self,
+        id: u64,
+        periodic: bool,
+        finished: F,
+    ) -> bool
+    where
+        F: Fn() -> bool,
+    {
+        let _lock = self.write_lock.lock();
+
+        // The mailbox of a finished process may be reclaimed at any point, so
+        // we must not add any messages to it.
+        if finished() {
+            return false;
+        }
+
+        let message = if periodic {
+            if let Some(message) = self.timers.get(&id) {
+                // Copying can fail if we run out of file descriptors when
+                // cloning a socket, in which case we skip this interval.
+                match self.allocator.try_copy_object(*message) {
+                    Ok(copy) => copy,
+                    Err(_) => return false,
+                }
+            } else {
+                return false;
+            }
+        } else if let Some(message) = self.timers.remove(&id) {
+            message
+        } else {
+            return false;
+        };
+
+        self.external.push_back(message);
+        self.length.fetch_add(1, Ordering::AcqRel);
+
+        true
+    }
+
+    /// Removes the message of a timer, returning true if the timer existed.
+    pub fn cancel_timer(&mut self, id: u64) -> bool {
+        let _lock = self.write_lock.lock();
+
+        self.timers.remove(&id).is_some()
+    }
+
+    /// Removes the messages of all timers.
+    pub fn clear_timers(&mut self) {
+        let _lock = self.write_lock.lock();
+
+        self.timers.clear();
     }
 
     pub fn send_from_self(&mut self, pointer: ObjectPointer) {
+        let _lock = self.write_lock.lock();
+
         self.locals.push_back(pointer);
+        self.length.fetch_add(1, Ordering::AcqRel);
     }
 
     pub fn receive(&mut self) -> (bool, Option<ObjectPointer>) {
-        if let Some(pointer) = self.locals.pop_front() {
-            return (false, Some(pointer));
+        let _lock = self.write_lock.lock();
+        let result =
+            pop(&mut self.locals, &mut self.internal, &mut self.external);
+
+        if result.1.is_some() {
+            self.length.fetch_sub(1, Ordering::AcqRel);
         }
 
-        if self.internal.is_empty() {
-            let _lock = self.write_lock.lock();
+        result
+    }
+
+    /// Receives a message to test using the condition of a selective receive.
+    ///
+    /// The message still counts towards the capacity of the mailbox, until it
+    /// is either accepted or put back.
+    pub fn receive_candidate(&mut self) -> (bool, Option<ObjectPointer>) {
+        let _lock = self.write_lock.lock();
+
+        pop(&mut self.locals, &mut self.internal, &mut self.external)
+    }
+
+    /// Removes the message accepted by a selective receive from the mailbox.
+    pub fn accept_message(&mut self) {
+        let _lock = self.write_lock.lock();
+
+        self.length.fetch_sub(1, Ordering::AcqRel);
+    }
+
+    /// Returns the number of messages in the mailbox.
+    pub fn len(&self) -> usize {
+        self.length.load(Ordering::Acquire)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns true if a message can be sent without exceeding the capacity.
+    pub fn has_space(&self) -> bool {
+        let _lock = self.write_lock.lock();
+
+        self.capacity == 0 || self.len() < self.capacity
+    }
+
+    /// Sets the capacity of the mailbox and the policy to apply when it is
+    /// full. A capacity of 0 removes the limit.
+    pub fn set_capacity(&mut self, capacity: usize, overflow: OverflowPolicy) {
+        let _lock = self.write_lock.lock();
+
+        self.capacity = capacity;
+        self.overflow = overflow;
+    }
+
+    /// Returns true if the mailbox can only hold a limited number of
+    /// messages.
+    pub fn is_bounded(&self) -> bool {
+        let _lock = self.write_lock.lock();
+
+        self.capacity > 0
+    }
+
+    /// Puts the skipped messages and the candidate (if any) back in front of
+    /// the mailbox, in the order they were originally received.
+    ///
+    /// These messages are still counted as part of the mailbox, so the length
+    /// stays the same.
+    pub fn restore_skipped(&mut self) {
+        let _lock = self.write_lock.lock();
 
-            self.internal
-                .append(&mut self.external.drain(0..).collect());
+        if let Some(pointer) = self.candidate.take() {
+            self.skipped.push_back(pointer);
         }
 
-        (true, self.internal.pop_front())
+        while let Some(pointer) = self.skipped.pop_back() {
+            self.locals.push_front(pointer);
+        }
     }
 
     pub fn has_local_pointers(&self) -> bool {
+        let _lock = self.write_lock.lock();
+
         !self.locals.is_empty()
+            || !self.skipped.is_empty()
+            || self.candidate.is_some()
     }
 
     pub fn mailbox_pointers(&self) -> WorkList {
         let mut pointers = WorkList::new();
 
-        for pointer in self.internal.iter().chain(self.external.iter()) {
+        for pointer in self
+            .internal
+            .iter()
+            .chain(self.external.iter())
+            .chain(self.timers.values())
+        {
             pointers.push(pointer.pointer());
         }
 
@@ -68,9 +340,14 @@ impl Mailbox {
     }
 
     pub fn local_pointers(&self) -> WorkList {
+        let _lock = self.write_lock.lock();
         let mut pointers = WorkList::new();
 
-        for pointer in &self.locals {
+        for pointer in self.locals.iter().chain(self.skipped.iter()) {
+            pointers.push(pointer.pointer());
+        }
+
+        if let Some(ref pointer) = self.candidate {
             pointers.push(pointer.pointer());
         }
 
@@ -82,12 +359,324 @@ impl Mailbox {
     /// This method should only be called when the owning processes is suspended
     /// as otherwise the counts returned could be inaccurate.
     pub fn has_messages(&self) -> bool {
-        if !self.locals.is_empty() || !self.internal.is_empty() {
-            return true;
+        let _lock = self.write_lock.lock();
+
+        !self.locals.is_empty()
+            || !self.internal.is_empty()
+            || !self.external.is_empty()
+    }
+}
+
+/// Removes the message that would be received next.
+///
+/// The write lock of the mailbox must be acquired before calling this
+/// function.
+fn pop(
+    locals: &mut VecDeque<ObjectPointer>,
+    internal: &mut VecDeque<ObjectPointer>,
+    external: &mut VecDeque<ObjectPointer>,
+) -> (bool, Option<ObjectPointer>) {
+    if let Some(pointer) = locals.pop_front() {
+        return (false, Some(pointer));
+    }
+
+    if internal.is_empty() {
+        internal.append(external);
+    }
+
+    (true, internal.pop_front())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use immix::global_allocator::GlobalAllocator;
+    use immix::local_allocator::LocalAllocator;
+    use object_value;
+    use std::process::Command;
+
+    fn mailbox() -> Mailbox {
+        Mailbox::new(GlobalAllocator::new(), &Config::new())
+    }
+
+    fn receive_integer(mailbox: &mut Mailbox) -> Option<i64> {
+        mailbox
+            .receive()
+            .1
+            .map(|pointer| pointer.integer_value().unwrap())
+    }
+
+    #[test]
+    fn test_overflow_policy_from_integer() {
+        assert_eq!(
+            OverflowPolicy::from_integer(0),
+            Some(OverflowPolicy::Suspend)
+        );
+
+        assert_eq!(
+            OverflowPolicy::from_integer(3),
+            Some(OverflowPolicy::Throw)
+        );
+
+        assert!(OverflowPolicy::from_integer(4).is_none());
+    }
+
+    #[test]
+    fn test_len() {
+        let mut mailbox = mailbox();
+
+        mailbox.send_from_self(ObjectPointer::integer(1));
+        mailbox.send_from_external(ObjectPointer::integer(2));
+
+        assert_eq!(mailbox.len(), 2);
+
+        mailbox.receive();
+
+        assert_eq!(mailbox.len(), 1);
+    }
+
+    #[test]
+    fn test_try_send_from_external_without_capacity() {
+        let mut mailbox = mailbox();
+
+        for number in 0..10 {
+            assert_eq!(
+                mailbox
+                    .try_send_from_external(ObjectPointer::integer(number))
+                    .unwrap(),
+                SendResult::Sent
+            );
         }
 
-        let _lock = self.write_lock.lock();
+        assert_eq!(mailbox.len(), 10);
+    }
+
+    #[test]
+    fn test_try_send_from_external_with_suspend_policy() {
+        let mut mailbox = mailbox();
+
+        mailbox.set_capacity(1, OverflowPolicy::Suspend);
+        mailbox
+            .try_send_from_external(ObjectPointer::integer(1))
+            .unwrap();
+
+        assert!(!mailbox.has_space());
+        assert_eq!(
+            mailbox
+                .try_send_from_external(ObjectPointer::integer(2))
+                .unwrap(),
+            SendResult::Suspend
+        );
+
+        assert_eq!(mailbox.len(), 1);
+    }
+
+    #[test]
+    fn test_try_send_from_external_with_drop_newest_policy() {
+        let mut mailbox = mailbox();
+
+        mailbox.set_capacity(1, OverflowPolicy::DropNewest);
+        mailbox
+            .try_send_from_external(ObjectPointer::integer(1))
+            .unwrap();
+
+        assert_eq!(
+            mailbox
+                .try_send_from_external(ObjectPointer::integer(2))
+                .unwrap(),
+            SendResult::Dropped
+        );
+
+        assert_eq!(receive_integer(&mut mailbox), Some(1));
+        assert!(receive_integer(&mut mailbox).is_none());
+    }
+
+    #[test]
+    fn test_try_send_from_external_with_drop_oldest_policy() {
+        let mut mailbox = mailbox();
+
+        mailbox.set_capacity(2, OverflowPolicy::DropOldest);
+
+        for number in 1..5 {
+            assert_eq!(
+                mailbox
+                    .try_send_from_external(ObjectPointer::integer(number))
+                    .unwrap(),
+                SendResult::Sent
+            );
+        }
+
+        assert_eq!(mailbox.len(), 2);
+        assert_eq!(receive_integer(&mut mailbox), Some(3));
+        assert_eq!(receive_integer(&mut mailbox), Some(4));
+        assert!(receive_integer(&mut mailbox).is_none());
+        assert_eq!(mailbox.len(), 0);
+    }
+
+    #[test]
+    fn test_try_send_from_external_with_drop_oldest_policy_and_skipped() {
+        let mut mailbox = mailbox();
+
+        mailbox.set_capacity(1, OverflowPolicy::DropOldest);
+        mailbox
+            .try_send_from_external(ObjectPointer::integer(1))
+            .unwrap();
+
+        let candidate = mailbox.receive_candidate().1.unwrap();
+
+        mailbox.skipped.push_back(candidate);
+
+        assert_eq!(
+            mailbox
+                .try_send_from_external(ObjectPointer::integer(2))
+                .unwrap(),
+            SendResult::Dropped
+        );
+
+        mailbox.restore_skipped();
+
+        assert_eq!(mailbox.len(), 1);
+        assert_eq!(receive_integer(&mut mailbox), Some(1));
+        assert!(receive_integer(&mut mailbox).is_none());
+    }
+
+    #[test]
+    fn test_try_send_from_external_with_drop_oldest_policy_and_invalid() {
+        let mut mailbox = mailbox();
+        let mut allocator =
+            LocalAllocator::new(GlobalAllocator::new(), &Config::new());
+
+        let child = Command::new("true").spawn().unwrap();
+        let message = allocator
+            .allocate_without_prototype(object_value::child_process(child));
+
+        mailbox.set_capacity(1, OverflowPolicy::DropOldest);
+        mailbox
+            .try_send_from_external(ObjectPointer::integer(1))
+            .unwrap();
+
+        assert!(mailbox.try_send_from_external(message).is_err());
+        assert_eq!(mailbox.len(), 1);
+        assert_eq!(receive_integer(&mut mailbox), Some(1));
+    }
+
+    #[test]
+    fn test_try_send_from_external_with_throw_policy() {
+        let mut mailbox = mailbox();
+
+        mailbox.set_capacity(1, OverflowPolicy::Throw);
+        mailbox
+            .try_send_from_external(ObjectPointer::integer(1))
+            .unwrap();
+
+        assert_eq!(
+            mailbox
+                .try_send_from_external(ObjectPointer::integer(2))
+                .unwrap(),
+            SendResult::Full
+        );
+    }
+
+    #[test]
+    fn test_receive_candidate() {
+        let mut mailbox = mailbox();
+
+        mailbox.set_capacity(1, OverflowPolicy::Throw);
+        mailbox
+            .try_send_from_external(ObjectPointer::integer(1))
+            .unwrap();
+
+        assert!(mailbox.receive_candidate().1.is_some());
+        assert_eq!(mailbox.len(), 1);
+        assert_eq!(
+            mailbox
+                .try_send_from_external(ObjectPointer::integer(2))
+                .unwrap(),
+            SendResult::Full
+        );
+
+        mailbox.accept_message();
+
+        assert_eq!(mailbox.len(), 0);
+        assert!(mailbox.has_space());
+    }
+
+    #[test]
+    fn test_restore_skipped() {
+        let mut mailbox = mailbox();
+
+        mailbox.send_from_self(ObjectPointer::integer(1));
+        mailbox.send_from_self(ObjectPointer::integer(2));
+        mailbox.send_from_self(ObjectPointer::integer(3));
+
+        let first = mailbox.receive_candidate().1.unwrap();
+        let second = mailbox.receive_candidate().1.unwrap();
+
+        mailbox.skipped.push_back(first);
+        mailbox.candidate = Some(second);
+        mailbox.restore_skipped();
+
+        assert_eq!(mailbox.len(), 3);
+        assert_eq!(receive_integer(&mut mailbox), Some(1));
+        assert_eq!(receive_integer(&mut mailbox), Some(2));
+        assert_eq!(receive_integer(&mut mailbox), Some(3));
+    }
+
+    #[test]
+    fn test_fire_timer() {
+        let mut mailbox = mailbox();
+
+        mailbox.add_timer(1, ObjectPointer::integer(10)).unwrap();
+
+        assert!(mailbox.fire_timer(1, false, || false));
+        assert!(!mailbox.fire_timer(1, false, || false));
+        assert_eq!(mailbox.len(), 1);
+        assert_eq!(receive_integer(&mut mailbox), Some(10));
+    }
+
+    #[test]
+    fn test_fire_timer_periodic() {
+        let mut mailbox = mailbox();
+
+        mailbox.add_timer(1, ObjectPointer::integer(10)).unwrap();
+
+        assert!(mailbox.fire_timer(1, true, || false));
+        assert!(mailbox.fire_timer(1, true, || false));
+        assert_eq!(mailbox.len(), 2);
+        assert_eq!(mailbox.timers.len(), 1);
+    }
+
+    #[test]
+    fn test_fire_timer_when_finished() {
+        let mut mailbox = mailbox();
+
+        mailbox.add_timer(1, ObjectPointer::integer(10)).unwrap();
+
+        assert!(!mailbox.fire_timer(1, false, || true));
+        assert!(mailbox.is_empty());
+    }
+
+    #[test]
+    fn test_clear_timers() {
+        let mut mailbox = mailbox();
+
+        mailbox.add_timer(1, ObjectPointer::integer(10)).unwrap();
+        mailbox.add_timer(2, ObjectPointer::integer(20)).unwrap();
+        mailbox.clear_timers();
+
+        assert!(mailbox.timers.is_empty());
+        assert!(!mailbox.fire_timer(1, false, || false));
+    }
+
+    #[test]
+    fn test_cancel_timer() {
+        let mut mailbox = mailbox();
+
+        mailbox.add_timer(1, ObjectPointer::integer(10)).unwrap();
 
-        !self.external.is_empty()
+        assert!(mailbox.cancel_timer(1));
+        assert!(!mailbox.cancel_timer(1));
+        assert!(!mailbox.fire_timer(1, true, || false));
+        assert!(mailbox.is_empty());
     }
 }
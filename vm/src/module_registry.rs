@@ -3,7 +3,9 @@ use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::{Arc, RwLock};
 
+use bundle::Bundle;
 use bytecode_parser;
+use bytecode_verifier;
 use module::Module;
 use vm::state::RcState;
 
@@ -13,6 +15,9 @@ pub enum ModuleError {
     /// The module did exist but could not be parsed.
     FailedToParse(String, bytecode_parser::ParserError),
 
+    /// The module could be parsed, but its bytecode is invalid.
+    FailedToVerify(String, bytecode_verifier::VerifierError),
+
     /// A given module did not exist.
     ModuleDoesNotExist(String),
 }
@@ -20,6 +25,9 @@ pub enum ModuleError {
 pub struct ModuleRegistry {
     state: RcState,
     parsed: HashMap<String, Module>,
+
+    /// A bundle to load modules from before searching the filesystem.
+    bundle: Option<Bundle>,
 }
 
 pub struct LookupResult<'a> {
@@ -33,9 +41,16 @@ impl ModuleError {
     /// Returns a human friendly error message.
     pub fn message(&self) -> String {
         match *self {
-            ModuleError::FailedToParse(ref path, ref error) => {
-                format!("Failed to parse {}: {:?}", path, error)
-            }
+            ModuleError::FailedToParse(ref path, ref error) => format!(
+                "Failed to parse {}:\n{}",
+                path,
+                indent(&error.message())
+            ),
+            ModuleError::FailedToVerify(ref path, ref error) => format!(
+                "Failed to verify {}:\n{}",
+                path,
+                indent(&error.message())
+            ),
             ModuleError::ModuleDoesNotExist(ref path) => {
                 format!("Module does not exist: {}", path)
             }
@@ -43,6 +58,14 @@ impl ModuleError {
     }
 }
 
+/// Indents every line of a multi-line message.
+fn indent(message: &str) -> String {
+    let lines: Vec<String> =
+        message.lines().map(|line| format!("  {}", line)).collect();
+
+    lines.join("\n")
+}
+
 impl<'a> LookupResult<'a> {
     pub fn new(module: &'a Module, parsed: bool) -> Self {
         LookupResult { module, parsed }
@@ -58,9 +81,18 @@ impl ModuleRegistry {
         ModuleRegistry {
             state,
             parsed: HashMap::new(),
+            bundle: None,
         }
     }
 
+    /// Sets the bundle to load modules from.
+    ///
+    /// Modules in the bundle take precedence over modules found in the
+    /// directories to search for bytecode files.
+    pub fn set_bundle(&mut self, bundle: Bundle) {
+        self.bundle = Some(bundle);
+    }
+
     /// Returns true if the given module has been parsed.
     #[cfg_attr(feature = "cargo-clippy", allow(ptr_arg))]
     pub fn contains_path(&self, path: &String) -> bool {
@@ -72,7 +104,11 @@ impl ModuleRegistry {
         &mut self,
         path: &str,
     ) -> Result<LookupResult, ModuleError> {
-        let full_path = self.find_path(path)?;
+        let full_path = if self.in_bundle(path) {
+            path.to_string()
+        } else {
+            self.find_path(path)?
+        };
 
         if !self.parsed.contains_key(&full_path) {
             self.parse_module(&full_path)
@@ -82,6 +118,13 @@ impl ModuleRegistry {
         }
     }
 
+    /// Returns true if the given path can be loaded from the bundle.
+    fn in_bundle(&self, path: &str) -> bool {
+        self.bundle
+            .as_ref()
+            .map_or(false, |bundle| bundle.contains(path))
+    }
+
     /// Returns the full path for a relative path.
     fn find_path(&self, path: &str) -> Result<String, ModuleError> {
         let mut input_path = PathBuf::from(path);
@@ -108,10 +151,20 @@ impl ModuleRegistry {
         Ok(input_path.to_str().unwrap().to_string())
     }
 
-    /// Parses a full file path pointing to a module.
+    /// Parses a module using its bundle path or full file path.
     pub fn parse_module(&mut self, path: &str) -> Result<&Module, ModuleError> {
-        let code = bytecode_parser::parse_file(&self.state, path)
-            .map_err(|err| ModuleError::FailedToParse(path.to_string(), err))?;
+        let bytes = self.bundle.as_ref().and_then(|bundle| bundle.module(path));
+
+        let code = if let Some(bytes) = bytes {
+            bytecode_parser::parse_slice(&self.state, bytes)
+        } else {
+            bytecode_parser::parse_file(&self.state, path)
+        }
+        .map_err(|err| ModuleError::FailedToParse(path.to_string(), err))?;
+
+        bytecode_verifier::verify(&code).map_err(|err| {
+            ModuleError::FailedToVerify(path.to_string(), err)
+        })?;
 
         self.add_module(path, Module::new(code));
 
@@ -126,6 +179,8 @@ impl ModuleRegistry {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use assembler;
+    use bundle;
     use config::Config;
     use vm::state::State;
 
@@ -154,4 +209,50 @@ mod tests {
 
         assert_eq!(result.ok().unwrap(), "/bin/ls".to_string());
     }
+
+    #[test]
+    fn test_parse_module_invalid_bytecode() {
+        let state = State::new(new_config());
+        let mut reg = ModuleRegistry::new(state);
+        let error = reg.parse_module("/bin/ls").err().unwrap();
+
+        assert_eq!(
+            error.message(),
+            "Failed to parse /bin/ls:\n  \
+             The file is not an Inko bytecode file\n  \
+             Location: the signature\n  \
+             Byte offset: 0"
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn test_get_or_set_from_bundle() {
+        let state = State::new(new_config());
+        let bytes = assembler::assemble_bytecode(
+            &state,
+            ".code main main.inko 1\n.registers 1\nReturn 0, 0\n.end",
+        )
+        .unwrap();
+
+        let modules = vec![
+            ("main.inkoc".to_string(), bytes.clone()),
+            ("ls".to_string(), bytes),
+        ];
+
+        let bundle =
+            Bundle::from_bytes(bundle::write("main.inkoc", &modules)).unwrap();
+
+        let mut reg = ModuleRegistry::new(state);
+
+        reg.set_bundle(bundle);
+
+        assert!(reg.get_or_set("main.inkoc").ok().unwrap().parsed);
+        assert!(!reg.get_or_set("main.inkoc").ok().unwrap().parsed);
+
+        // Modules in the bundle take precedence over those on disk.
+        assert!(reg.get_or_set("ls").is_ok());
+        assert!(reg.contains_path(&"ls".to_string()));
+        assert!(!reg.contains_path(&"/bin/ls".to_string()));
+    }
 }
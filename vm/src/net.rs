@@ -0,0 +1,992 @@
+//! Non-blocking socket operations.
+//!
+//! Sockets used by processes are always in non-blocking mode. Operations that
+//! would block instead suspend the process until the socket is ready, allowing
+//! the OS thread to run other processes in the mean time.
+//!
+//! The standard library only supports connecting sockets in a blocking manner,
+//! so connecting is done by starting the connection using a non-blocking
+//! socket, then waiting until the connection has been established.
+//!
+//! Operations that are the same for different types of sockets (e.g. reading
+//! from a TCP or Unix stream) are implemented by the `Socket` type.
+
+use libc;
+use std::io::{self, Write};
+use std::mem;
+use std::net::{
+    IpAddr, Ipv4Addr, Ipv6Addr, Shutdown, SocketAddr, TcpListener, TcpStream,
+    UdpSocket,
+};
+use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
+use std::os::unix::net::{
+    SocketAddr as UnixAddr, UnixDatagram, UnixListener, UnixStream,
+};
+use std::u32;
+
+use io::{read_available, read_once};
+use object_value::{self, ObjectValue};
+
+/// Shuts down the reading half of a socket.
+pub const SHUTDOWN_READ: i64 = 0;
+
+/// Shuts down the writing half of a socket.
+pub const SHUTDOWN_WRITE: i64 = 1;
+
+/// Shuts down both the reading and writing halves of a socket.
+pub const SHUTDOWN_BOTH: i64 = 2;
+
+/// Permits sending datagrams to a broadcast address (UDP only).
+pub const OPTION_BROADCAST: i64 = 0;
+
+/// Delivers multicast datagrams to the sending socket (UDP only).
+pub const OPTION_MULTICAST_LOOP: i64 = 1;
+
+/// The time-to-live of outgoing IPv4 multicast datagrams (UDP only).
+pub const OPTION_MULTICAST_TTL: i64 = 2;
+
+/// The time-to-live of outgoing packets (TCP and UDP).
+pub const OPTION_TTL: i64 = 3;
+
+/// Disables Nagle's algorithm (TCP streams only).
+pub const OPTION_NO_DELAY: i64 = 4;
+
+/// Creates a connected pair of Unix stream sockets.
+pub const PAIR_STREAM: i64 = 0;
+
+/// Creates a connected pair of Unix datagram sockets.
+pub const PAIR_DATAGRAM: i64 = 1;
+
+/// The number of bytes to receive when no size is given for a datagram.
+///
+/// This is the maximum size of a UDP datagram, so no data is discarded when
+/// receiving a datagram without specifying a size.
+const DATAGRAM_SIZE: usize = 64 * 1024;
+
+pub fn shutdown_for_integer(how: i64) -> Result<Shutdown, String> {
+    match how {
+        SHUTDOWN_READ => Ok(Shutdown::Read),
+        SHUTDOWN_WRITE => Ok(Shutdown::Write),
+        SHUTDOWN_BOTH => Ok(Shutdown::Both),
+        _ => Err(format!("Invalid socket shutdown mode: {}", how)),
+    }
+}
+
+/// Returns true if the socket option uses boolean values.
+pub fn is_boolean_option(option: i64) -> bool {
+    match option {
+        OPTION_BROADCAST | OPTION_MULTICAST_LOOP | OPTION_NO_DELAY => true,
+        _ => false,
+    }
+}
+
+/// A reference to a socket of any supported type.
+#[derive(Clone, Copy)]
+pub enum Socket<'a> {
+    TcpListener(&'a TcpListener),
+    TcpStream(&'a TcpStream),
+    UdpSocket(&'a UdpSocket),
+    UnixListener(&'a UnixListener),
+    UnixStream(&'a UnixStream),
+    UnixDatagram(&'a UnixDatagram),
+}
+
+impl<'a> Socket<'a> {
+    /// Accepts a new connection, returning a non-blocking stream.
+    pub fn accept(self) -> io::Result<ObjectValue> {
+        match self {
+            Socket::TcpListener(listener) => {
+                let (stream, _) = listener.accept()?;
+
+                stream.set_nonblocking(true)?;
+
+                Ok(object_value::tcp_stream(stream))
+            }
+            Socket::UnixListener(listener) => {
+                let (stream, _) = listener.accept()?;
+
+                stream.set_nonblocking(true)?;
+
+                Ok(object_value::unix_stream(stream))
+            }
+            _ => Err(unsupported("accepting connections")),
+        }
+    }
+
+    /// Returns true if a stream is connected, and false if the connection is
+    /// still being established.
+    ///
+    /// If establishing the connection failed, the error is returned.
+    pub fn is_connected(self) -> io::Result<bool> {
+        let (error, peer) = match self {
+            Socket::TcpStream(stream) => {
+                (stream.take_error()?, stream.peer_addr().map(|_| ()))
+            }
+            Socket::UnixStream(stream) => {
+                (stream.take_error()?, stream.peer_addr().map(|_| ()))
+            }
+            _ => return Err(unsupported("establishing connections")),
+        };
+
+        if let Some(error) = error {
+            return Err(error);
+        }
+
+        match peer {
+            Ok(_) => Ok(true),
+            Err(ref error) if error.kind() == io::ErrorKind::NotConnected => {
+                Ok(false)
+            }
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Connects a datagram socket to the given address.
+    ///
+    /// Connecting a datagram socket only sets the default address to send
+    /// datagrams to, and never blocks.
+    pub fn connect(self, address: &str) -> io::Result<()> {
+        match self {
+            Socket::UdpSocket(socket) => {
+                socket.connect(parse_address(address)?)
+            }
+            Socket::UnixDatagram(socket) => socket.connect(address),
+            _ => Err(unsupported("connecting to an address")),
+        }
+    }
+
+    /// Reads data into a buffer, returning the number of bytes read.
+    ///
+    /// For streams all available data is read if no size is given. For
+    /// datagram sockets a single datagram is read.
+    pub fn read(
+        self,
+        buffer: &mut Vec<u8>,
+        size: Option<usize>,
+    ) -> io::Result<usize> {
+        match self {
+            Socket::TcpStream(mut stream) => {
+                read_stream(&mut stream, buffer, size)
+            }
+            Socket::UnixStream(mut stream) => {
+                read_stream(&mut stream, buffer, size)
+            }
+            Socket::UdpSocket(socket) => {
+                receive(buffer, size, |buf| socket.recv(buf).map(|n| (n, ())))
+                    .map(|(amount, _)| amount)
+            }
+            Socket::UnixDatagram(socket) => {
+                receive(buffer, size, |buf| socket.recv(buf).map(|n| (n, ())))
+                    .map(|(amount, _)| amount)
+            }
+            _ => Err(unsupported("reading")),
+        }
+    }
+
+    /// Writes data, returning the number of bytes written.
+    ///
+    /// Datagram sockets must be connected before they can be written to.
+    pub fn write(self, data: &[u8]) -> io::Result<usize> {
+        match self {
+            Socket::TcpStream(mut stream) => stream.write(data),
+            Socket::UnixStream(mut stream) => stream.write(data),
+            Socket::UdpSocket(socket) => socket.send(data),
+            Socket::UnixDatagram(socket) => socket.send(data),
+            _ => Err(unsupported("writing")),
+        }
+    }
+
+    /// Sends a datagram to the given address, returning the number of bytes
+    /// sent.
+    pub fn send_to(self, data: &[u8], address: &str) -> io::Result<usize> {
+        match self {
+            Socket::UdpSocket(socket) => {
+                socket.send_to(data, parse_address(address)?)
+            }
+            Socket::UnixDatagram(socket) => socket.send_to(data, address),
+            _ => Err(unsupported("sending datagrams")),
+        }
+    }
+
+    /// Receives a single datagram into a buffer, returning the number of bytes
+    /// read and the address of the sender.
+    pub fn receive_from(
+        self,
+        buffer: &mut Vec<u8>,
+        size: Option<usize>,
+    ) -> io::Result<(usize, String)> {
+        match self {
+            Socket::UdpSocket(socket) => receive(buffer, size, |buf| {
+                socket
+                    .recv_from(buf)
+                    .map(|(amount, address)| (amount, address.to_string()))
+            }),
+            Socket::UnixDatagram(socket) => receive(buffer, size, |buf| {
+                socket.recv_from(buf).map(|(amount, address)| {
+                    (amount, unix_address_to_string(&address))
+                })
+            }),
+            _ => Err(unsupported("receiving datagrams")),
+        }
+    }
+
+    pub fn shutdown(self, how: Shutdown) -> io::Result<()> {
+        match self {
+            Socket::TcpStream(stream) => stream.shutdown(how),
+            Socket::UnixStream(stream) => stream.shutdown(how),
+            Socket::UnixDatagram(socket) => socket.shutdown(how),
+            _ => Err(unsupported("shutting down")),
+        }
+    }
+
+    /// Returns the local address of the socket.
+    ///
+    /// The address of a Unix socket is the path it is bound to, or an empty
+    /// string for unbound sockets.
+    pub fn local_address(self) -> io::Result<String> {
+        match self {
+            Socket::TcpListener(listener) => {
+                listener.local_addr().map(|addr| addr.to_string())
+            }
+            Socket::TcpStream(stream) => {
+                stream.local_addr().map(|addr| addr.to_string())
+            }
+            Socket::UdpSocket(socket) => {
+                socket.local_addr().map(|addr| addr.to_string())
+            }
+            Socket::UnixListener(listener) => listener
+                .local_addr()
+                .map(|addr| unix_address_to_string(&addr)),
+            Socket::UnixStream(stream) => stream
+                .local_addr()
+                .map(|addr| unix_address_to_string(&addr)),
+            Socket::UnixDatagram(socket) => socket
+                .local_addr()
+                .map(|addr| unix_address_to_string(&addr)),
+        }
+    }
+
+    /// Returns the address of the remote end of the socket.
+    pub fn peer_address(self) -> io::Result<String> {
+        match self {
+            Socket::TcpStream(stream) => {
+                stream.peer_addr().map(|addr| addr.to_string())
+            }
+            Socket::UnixStream(stream) => {
+                stream.peer_addr().map(|addr| unix_address_to_string(&addr))
+            }
+            Socket::UnixDatagram(socket) => {
+                socket.peer_addr().map(|addr| unix_address_to_string(&addr))
+            }
+            _ => Err(unsupported("retrieving the peer address")),
+        }
+    }
+
+    /// Sets a socket option.
+    ///
+    /// Boolean options are enabled using a non-zero value.
+    pub fn set_option(self, option: i64, value: i64) -> io::Result<()> {
+        match (option, self) {
+            (OPTION_BROADCAST, Socket::UdpSocket(socket)) => {
+                socket.set_broadcast(value != 0)
+            }
+            (OPTION_MULTICAST_LOOP, Socket::UdpSocket(socket)) => {
+                if socket.local_addr()?.is_ipv4() {
+                    socket.set_multicast_loop_v4(value != 0)
+                } else {
+                    socket.set_multicast_loop_v6(value != 0)
+                }
+            }
+            (OPTION_MULTICAST_TTL, Socket::UdpSocket(socket)) => {
+                socket.set_multicast_ttl_v4(u32_for_integer(value)?)
+            }
+            (OPTION_TTL, Socket::TcpListener(listener)) => {
+                listener.set_ttl(u32_for_integer(value)?)
+            }
+            (OPTION_TTL, Socket::TcpStream(stream)) => {
+                stream.set_ttl(u32_for_integer(value)?)
+            }
+            (OPTION_TTL, Socket::UdpSocket(socket)) => {
+                socket.set_ttl(u32_for_integer(value)?)
+            }
+            (OPTION_NO_DELAY, Socket::TcpStream(stream)) => {
+                stream.set_nodelay(value != 0)
+            }
+            _ => Err(unsupported_option(option)),
+        }
+    }
+
+    /// Returns the value of a socket option.
+    ///
+    /// Boolean options produce 1 when enabled, and 0 when disabled.
+    pub fn get_option(self, option: i64) -> io::Result<i64> {
+        match (option, self) {
+            (OPTION_BROADCAST, Socket::UdpSocket(socket)) => {
+                socket.broadcast().map(|value| value as i64)
+            }
+            (OPTION_MULTICAST_LOOP, Socket::UdpSocket(socket)) => {
+                if socket.local_addr()?.is_ipv4() {
+                    socket.multicast_loop_v4().map(|value| value as i64)
+                } else {
+                    socket.multicast_loop_v6().map(|value| value as i64)
+                }
+            }
+            (OPTION_MULTICAST_TTL, Socket::UdpSocket(socket)) => {
+                socket.multicast_ttl_v4().map(i64::from)
+            }
+            (OPTION_TTL, Socket::TcpListener(listener)) => {
+                listener.ttl().map(i64::from)
+            }
+            (OPTION_TTL, Socket::TcpStream(stream)) => {
+                stream.ttl().map(i64::from)
+            }
+            (OPTION_TTL, Socket::UdpSocket(socket)) => {
+                socket.ttl().map(i64::from)
+            }
+            (OPTION_NO_DELAY, Socket::TcpStream(stream)) => {
+                stream.nodelay().map(|value| value as i64)
+            }
+            _ => Err(unsupported_option(option)),
+        }
+    }
+
+    /// Joins a multicast group.
+    ///
+    /// For IPv4 groups the interface is the IPv4 address of the interface to
+    /// use, such as "0.0.0.0". For IPv6 groups the interface is the index of
+    /// the interface, with 0 being the default interface.
+    pub fn join_multicast(
+        self,
+        group: &str,
+        interface: &str,
+    ) -> io::Result<()> {
+        let socket = self.udp_socket("joining multicast groups")?;
+
+        match MulticastGroup::parse(group, interface)? {
+            MulticastGroup::V4(group, interface) => {
+                socket.join_multicast_v4(&group, &interface)
+            }
+            MulticastGroup::V6(group, interface) => {
+                socket.join_multicast_v6(&group, interface)
+            }
+        }
+    }
+
+    /// Leaves a multicast group joined using `join_multicast()`.
+    pub fn leave_multicast(
+        self,
+        group: &str,
+        interface: &str,
+    ) -> io::Result<()> {
+        let socket = self.udp_socket("leaving multicast groups")?;
+
+        match MulticastGroup::parse(group, interface)? {
+            MulticastGroup::V4(group, interface) => {
+                socket.leave_multicast_v4(&group, &interface)
+            }
+            MulticastGroup::V6(group, interface) => {
+                socket.leave_multicast_v6(&group, interface)
+            }
+        }
+    }
+
+    fn udp_socket(self, operation: &str) -> io::Result<&'a UdpSocket> {
+        if let Socket::UdpSocket(socket) = self {
+            Ok(socket)
+        } else {
+            Err(unsupported(operation))
+        }
+    }
+}
+
+impl<'a> AsRawFd for Socket<'a> {
+    fn as_raw_fd(&self) -> RawFd {
+        match *self {
+            Socket::TcpListener(listener) => listener.as_raw_fd(),
+            Socket::TcpStream(stream) => stream.as_raw_fd(),
+            Socket::UdpSocket(socket) => socket.as_raw_fd(),
+            Socket::UnixListener(listener) => listener.as_raw_fd(),
+            Socket::UnixStream(stream) => stream.as_raw_fd(),
+            Socket::UnixDatagram(socket) => socket.as_raw_fd(),
+        }
+    }
+}
+
+/// A multicast group address and the interface to use for it.
+enum MulticastGroup {
+    V4(Ipv4Addr, Ipv4Addr),
+    V6(Ipv6Addr, u32),
+}
+
+impl MulticastGroup {
+    fn parse(group: &str, interface: &str) -> io::Result<Self> {
+        let group = group.parse::<IpAddr>().map_err(|_| {
+            invalid_input(format!("{} is not a valid IP address", group))
+        })?;
+
+        let invalid_interface =
+            || invalid_input(format!("{} is not a valid interface", interface));
+
+        match group {
+            IpAddr::V4(group) => Ok(MulticastGroup::V4(
+                group,
+                interface.parse().map_err(|_| invalid_interface())?,
+            )),
+            IpAddr::V6(group) => Ok(MulticastGroup::V6(
+                group,
+                interface.parse().map_err(|_| invalid_interface())?,
+            )),
+        }
+    }
+}
+
+/// Binds a non-blocking TCP listener to the given address.
+pub fn tcp_listen(address: &str) -> io::Result<TcpListener> {
+    let listener = TcpListener::bind(parse_address(address)?)?;
+
+    listener.set_nonblocking(true)?;
+
+    Ok(listener)
+}
+
+/// Starts connecting a non-blocking TCP stream to the given address.
+///
+/// The connection is likely still in progress when this function returns, use
+/// `Socket::is_connected()` to determine if the connection has been
+/// established.
+pub fn tcp_connect(address: &str) -> io::Result<TcpStream> {
+    let address = parse_address(address)?;
+
+    let domain = match address {
+        SocketAddr::V4(_) => libc::AF_INET,
+        SocketAddr::V6(_) => libc::AF_INET6,
+    };
+
+    let (raw_address, length) = raw_socket_address(&address);
+
+    let fd = connect(
+        domain,
+        &raw_address as *const libc::sockaddr_storage as *const libc::sockaddr,
+        length,
+    )?;
+
+    Ok(unsafe { TcpStream::from_raw_fd(fd) })
+}
+
+/// Binds a non-blocking UDP socket to the given address.
+pub fn udp_bind(address: &str) -> io::Result<UdpSocket> {
+    let socket = UdpSocket::bind(parse_address(address)?)?;
+
+    socket.set_nonblocking(true)?;
+
+    Ok(socket)
+}
+
+/// Binds a non-blocking Unix listener to the given path.
+pub fn unix_listen(path: &str) -> io::Result<UnixListener> {
+    let listener = UnixListener::bind(path)?;
+
+    listener.set_nonblocking(true)?;
+
+    Ok(listener)
+}
+
+/// Connects a non-blocking Unix stream to the given path.
+///
+/// Unlike TCP connections, connecting to a Unix socket completes right away.
+/// If the listener's backlog is full, an error of kind `WouldBlock` is
+/// returned.
+pub fn unix_connect(path: &str) -> io::Result<UnixStream> {
+    let (raw_address, length) = raw_unix_address(path)?;
+
+    let fd = connect(
+        libc::AF_UNIX,
+        &raw_address as *const libc::sockaddr_un as *const libc::sockaddr,
+        length,
+    )?;
+
+    Ok(unsafe { UnixStream::from_raw_fd(fd) })
+}
+
+/// Creates a non-blocking Unix datagram socket.
+///
+/// If a path is given the socket is bound to this path, otherwise the socket
+/// is left unbound.
+pub fn unix_datagram(path: Option<&str>) -> io::Result<UnixDatagram> {
+    let socket = if let Some(path) = path {
+        UnixDatagram::bind(path)?
+    } else {
+        UnixDatagram::unbound()?
+    };
+
+    socket.set_nonblocking(true)?;
+
+    Ok(socket)
+}
+
+/// Creates a pair of connected, non-blocking Unix stream sockets.
+pub fn unix_stream_pair() -> io::Result<(ObjectValue, ObjectValue)> {
+    let (first, second) = UnixStream::pair()?;
+
+    first.set_nonblocking(true)?;
+    second.set_nonblocking(true)?;
+
+    Ok((
+        object_value::unix_stream(first),
+        object_value::unix_stream(second),
+    ))
+}
+
+/// Creates a pair of connected, non-blocking Unix datagram sockets.
+pub fn unix_datagram_pair() -> io::Result<(ObjectValue, ObjectValue)> {
+    let (first, second) = UnixDatagram::pair()?;
+
+    first.set_nonblocking(true)?;
+    second.set_nonblocking(true)?;
+
+    Ok((
+        object_value::unix_datagram(first),
+        object_value::unix_datagram(second),
+    ))
+}
+
+/// Parses a socket address, such as "127.0.0.1:80".
+///
+/// Host names are not supported, as resolving them would block the calling
+/// thread.
+fn parse_address(address: &str) -> io::Result<SocketAddr> {
+    address.parse().map_err(|_| {
+        invalid_input(format!("{} is not a valid socket address", address))
+    })
+}
+
+fn invalid_input(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+fn unsupported(operation: &str) -> io::Error {
+    invalid_input(format!("The socket does not support {}", operation))
+}
+
+fn unsupported_option(option: i64) -> io::Error {
+    invalid_input(format!("The socket does not support option {}", option))
+}
+
+fn u32_for_integer(value: i64) -> io::Result<u32> {
+    if value < 0 || value > i64::from(u32::MAX) {
+        Err(invalid_input(format!(
+            "{} is not a valid option value",
+            value
+        )))
+    } else {
+        Ok(value as u32)
+    }
+}
+
+fn unix_address_to_string(address: &UnixAddr) -> String {
+    address
+        .as_pathname()
+        .map(|path| path.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+fn read_stream(
+    stream: &mut io::Read,
+    buffer: &mut Vec<u8>,
+    size: Option<usize>,
+) -> io::Result<usize> {
+    if let Some(size) = size {
+        read_once(stream, buffer, size)
+    } else {
+        read_available(stream, buffer)
+    }
+}
+
+/// Receives a single datagram, appending it to the buffer.
+///
+/// Any data of the datagram that does not fit in `size` bytes is discarded.
+fn receive<T, F>(
+    buffer: &mut Vec<u8>,
+    size: Option<usize>,
+    receive: F,
+) -> io::Result<(usize, T)>
+where
+    F: FnOnce(&mut [u8]) -> io::Result<(usize, T)>,
+{
+    let start = buffer.len();
+
+    buffer.resize(start + size.unwrap_or(DATAGRAM_SIZE), 0);
+
+    let result = receive(&mut buffer[start..]);
+    let amount = result.as_ref().map(|&(amount, _)| amount).unwrap_or(0);
+
+    buffer.truncate(start + amount);
+
+    result
+}
+
+/// Creates a non-blocking socket and starts connecting it to an address.
+fn connect(
+    domain: libc::c_int,
+    address: *const libc::sockaddr,
+    length: libc::socklen_t,
+) -> io::Result<RawFd> {
+    let flags = libc::SOCK_STREAM | libc::SOCK_NONBLOCK | libc::SOCK_CLOEXEC;
+    let fd = unsafe { libc::socket(domain, flags, 0) };
+
+    if fd == -1 {
+        return Err(io::Error::last_os_error());
+    }
+
+    if unsafe { libc::connect(fd, address, length) } == -1 {
+        let error = io::Error::last_os_error();
+
+        if error.raw_os_error() != Some(libc::EINPROGRESS) {
+            unsafe {
+                libc::close(fd);
+            }
+
+            return Err(error);
+        }
+    }
+
+    Ok(fd)
+}
+
+fn raw_socket_address(
+    address: &SocketAddr,
+) -> (libc::sockaddr_storage, libc::socklen_t) {
+    let mut storage: libc::sockaddr_storage = unsafe { mem::zeroed() };
+
+    let length = match *address {
+        SocketAddr::V4(ref address) => {
+            let raw = unsafe {
+                &mut *(&mut storage as *mut libc::sockaddr_storage
+                    as *mut libc::sockaddr_in)
+            };
+
+            raw.sin_family = libc::AF_INET as libc::sa_family_t;
+            raw.sin_port = address.port().to_be();
+            raw.sin_addr.s_addr = u32::from(*address.ip()).to_be();
+
+            mem::size_of::<libc::sockaddr_in>()
+        }
+        SocketAddr::V6(ref address) => {
+            let raw = unsafe {
+                &mut *(&mut storage as *mut libc::sockaddr_storage
+                    as *mut libc::sockaddr_in6)
+            };
+
+            raw.sin6_family = libc::AF_INET6 as libc::sa_family_t;
+            raw.sin6_port = address.port().to_be();
+            raw.sin6_flowinfo = address.flowinfo();
+            raw.sin6_addr.s6_addr = address.ip().octets();
+            raw.sin6_scope_id = address.scope_id();
+
+            mem::size_of::<libc::sockaddr_in6>()
+        }
+    };
+
+    (storage, length as libc::socklen_t)
+}
+
+fn raw_unix_address(
+    path: &str,
+) -> io::Result<(libc::sockaddr_un, libc::socklen_t)> {
+    let mut raw: libc::sockaddr_un = unsafe { mem::zeroed() };
+    let bytes = path.as_bytes();
+
+    // The path must be terminated by a NULL byte, which we get for free as
+    // the structure is zeroed.
+    if bytes.len() >= raw.sun_path.len() || bytes.contains(&0) {
+        return Err(invalid_input(format!(
+            "{} is not a valid socket path",
+            path
+        )));
+    }
+
+    raw.sun_family = libc::AF_UNIX as libc::sa_family_t;
+
+    for (target, byte) in raw.sun_path.iter_mut().zip(bytes) {
+        *target = *byte as libc::c_char;
+    }
+
+    let length = mem::size_of::<libc::sa_family_t>() + bytes.len() + 1;
+
+    Ok((raw, length as libc::socklen_t))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::fs;
+    use std::path::PathBuf;
+    use std::process;
+    use std::thread;
+    use std::time::Duration;
+
+    fn socket_path(name: &str) -> PathBuf {
+        let path =
+            env::temp_dir().join(format!("inko-{}-{}", process::id(), name));
+
+        let _ = fs::remove_file(&path);
+
+        path
+    }
+
+    #[test]
+    fn test_parse_address() {
+        assert!(parse_address("127.0.0.1:80").is_ok());
+        assert!(parse_address("[::1]:80").is_ok());
+        assert!(parse_address("localhost").is_err());
+    }
+
+    #[test]
+    fn test_shutdown_for_integer() {
+        assert_eq!(shutdown_for_integer(0).unwrap(), Shutdown::Read);
+        assert_eq!(shutdown_for_integer(1).unwrap(), Shutdown::Write);
+        assert_eq!(shutdown_for_integer(2).unwrap(), Shutdown::Both);
+        assert!(shutdown_for_integer(3).is_err());
+    }
+
+    #[test]
+    fn test_is_boolean_option() {
+        assert!(is_boolean_option(OPTION_BROADCAST));
+        assert!(is_boolean_option(OPTION_NO_DELAY));
+        assert_eq!(is_boolean_option(OPTION_TTL), false);
+    }
+
+    #[test]
+    fn test_tcp_listen() {
+        let listener = tcp_listen("127.0.0.1:0").unwrap();
+
+        assert_eq!(
+            Socket::TcpListener(&listener)
+                .accept()
+                .err()
+                .map(|error| error.kind()),
+            Some(io::ErrorKind::WouldBlock)
+        );
+    }
+
+    #[test]
+    fn test_tcp_listen_with_invalid_address() {
+        assert_eq!(
+            tcp_listen("localhost").unwrap_err().kind(),
+            io::ErrorKind::InvalidInput
+        );
+    }
+
+    #[test]
+    fn test_tcp_connect() {
+        let listener = tcp_listen("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let stream = tcp_connect(&address.to_string()).unwrap();
+
+        while !Socket::TcpStream(&stream).is_connected().unwrap() {
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        assert_eq!(stream.peer_addr().unwrap(), address);
+    }
+
+    #[test]
+    fn test_tcp_connect_refused() {
+        let address = {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+
+            listener.local_addr().unwrap()
+        };
+
+        let stream = tcp_connect(&address.to_string()).unwrap();
+
+        loop {
+            match Socket::TcpStream(&stream).is_connected() {
+                Ok(false) => thread::sleep(Duration::from_millis(1)),
+                Ok(true) => panic!("The connection should have been refused"),
+                Err(error) => {
+                    assert_eq!(error.kind(), io::ErrorKind::ConnectionRefused);
+                    break;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_tcp_options() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let stream =
+            TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let socket = Socket::TcpStream(&stream);
+
+        socket.set_option(OPTION_NO_DELAY, 1).unwrap();
+        socket.set_option(OPTION_TTL, 32).unwrap();
+
+        assert_eq!(socket.get_option(OPTION_NO_DELAY).unwrap(), 1);
+        assert_eq!(socket.get_option(OPTION_TTL).unwrap(), 32);
+        assert!(socket.set_option(OPTION_BROADCAST, 1).is_err());
+        assert!(socket.set_option(OPTION_TTL, -1).is_err());
+    }
+
+    #[test]
+    fn test_udp_send_to_and_receive_from() {
+        let first = udp_bind("127.0.0.1:0").unwrap();
+        let second = udp_bind("127.0.0.1:0").unwrap();
+        let address = second.local_addr().unwrap().to_string();
+        let mut buffer = Vec::new();
+
+        assert_eq!(
+            Socket::UdpSocket(&first)
+                .send_to(b"hello", &address)
+                .unwrap(),
+            5
+        );
+
+        let (amount, sender) = loop {
+            match Socket::UdpSocket(&second).receive_from(&mut buffer, None) {
+                Err(ref error) if error.kind() == io::ErrorKind::WouldBlock => {
+                    thread::sleep(Duration::from_millis(1));
+                }
+                result => break result.unwrap(),
+            }
+        };
+
+        assert_eq!(amount, 5);
+        assert_eq!(buffer, b"hello".to_vec());
+        assert_eq!(sender, first.local_addr().unwrap().to_string());
+    }
+
+    #[test]
+    fn test_udp_read_without_data() {
+        let socket = udp_bind("127.0.0.1:0").unwrap();
+        let mut buffer = Vec::new();
+
+        assert_eq!(
+            Socket::UdpSocket(&socket)
+                .read(&mut buffer, Some(8))
+                .unwrap_err()
+                .kind(),
+            io::ErrorKind::WouldBlock
+        );
+
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn test_udp_options() {
+        let socket = udp_bind("127.0.0.1:0").unwrap();
+        let socket = Socket::UdpSocket(&socket);
+
+        socket.set_option(OPTION_BROADCAST, 1).unwrap();
+        socket.set_option(OPTION_MULTICAST_LOOP, 0).unwrap();
+        socket.set_option(OPTION_MULTICAST_TTL, 4).unwrap();
+
+        assert_eq!(socket.get_option(OPTION_BROADCAST).unwrap(), 1);
+        assert_eq!(socket.get_option(OPTION_MULTICAST_LOOP).unwrap(), 0);
+        assert_eq!(socket.get_option(OPTION_MULTICAST_TTL).unwrap(), 4);
+        assert!(socket.get_option(OPTION_NO_DELAY).is_err());
+    }
+
+    #[test]
+    fn test_udp_join_multicast_with_invalid_group() {
+        let socket = udp_bind("127.0.0.1:0").unwrap();
+
+        assert_eq!(
+            Socket::UdpSocket(&socket)
+                .join_multicast("foo", "0.0.0.0")
+                .unwrap_err()
+                .kind(),
+            io::ErrorKind::InvalidInput
+        );
+    }
+
+    #[test]
+    fn test_unix_listen_and_connect() {
+        let path = socket_path("listen.sock");
+        let path_str = path.to_str().unwrap();
+        let listener = unix_listen(path_str).unwrap();
+        let stream = unix_connect(path_str).unwrap();
+
+        assert!(Socket::UnixStream(&stream).is_connected().unwrap());
+        assert!(Socket::UnixListener(&listener).accept().is_ok());
+        assert_eq!(
+            Socket::UnixStream(&stream).peer_address().unwrap(),
+            path_str
+        );
+        assert_eq!(Socket::UnixStream(&stream).local_address().unwrap(), "");
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_unix_connect_with_invalid_path() {
+        let path = "a".repeat(256);
+
+        assert_eq!(
+            unix_connect(&path).unwrap_err().kind(),
+            io::ErrorKind::InvalidInput
+        );
+    }
+
+    #[test]
+    fn test_unix_datagram_send_to_and_receive_from() {
+        let path = socket_path("datagram.sock");
+        let path_str = path.to_str().unwrap();
+        let receiver = unix_datagram(Some(path_str)).unwrap();
+        let sender = unix_datagram(None).unwrap();
+        let mut buffer = Vec::new();
+
+        Socket::UnixDatagram(&sender)
+            .send_to(b"hello", path_str)
+            .unwrap();
+
+        let (amount, address) = Socket::UnixDatagram(&receiver)
+            .receive_from(&mut buffer, Some(3))
+            .unwrap();
+
+        assert_eq!(amount, 3);
+        assert_eq!(buffer, b"hel".to_vec());
+        assert_eq!(address, "");
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_unix_stream_pair() {
+        let (first, second) = unix_stream_pair().unwrap();
+        let first = first.as_socket().unwrap();
+        let second = second.as_socket().unwrap();
+        let mut buffer = Vec::new();
+
+        assert_eq!(first.write(b"hello").unwrap(), 5);
+        assert_eq!(second.read(&mut buffer, None).unwrap(), 5);
+        assert_eq!(buffer, b"hello".to_vec());
+    }
+
+    #[test]
+    fn test_unix_datagram_pair() {
+        let (first, second) = unix_datagram_pair().unwrap();
+        let first = first.as_socket().unwrap();
+        let second = second.as_socket().unwrap();
+        let mut buffer = Vec::new();
+
+        assert_eq!(first.write(b"hello").unwrap(), 5);
+        assert_eq!(second.read(&mut buffer, None).unwrap(), 5);
+        assert_eq!(buffer, b"hello".to_vec());
+    }
+
+    #[test]
+    fn test_unsupported_operation() {
+        let listener = tcp_listen("127.0.0.1:0").unwrap();
+        let mut buffer = Vec::new();
+
+        assert_eq!(
+            Socket::TcpListener(&listener)
+                .read(&mut buffer, None)
+                .unwrap_err()
+                .kind(),
+            io::ErrorKind::InvalidInput
+        );
+    }
+}
@@ -0,0 +1,246 @@
+//! Polling of non-blocking sockets using epoll.
+//!
+//! When a process tries to use a socket that isn't ready (e.g. when reading
+//! from a socket without any data), the socket is registered with the
+//! NetworkPoller and the process is suspended. A dedicated thread waits for
+//! registered sockets to become ready, and resumes the processes waiting for
+//! them.
+//!
+//! Sockets are registered using EPOLLONESHOT, meaning a socket is only
+//! reported once for every time it is registered. The data of every event is
+//! the PID of the process waiting for the socket.
+
+#![cfg_attr(feature = "cargo-clippy", allow(new_without_default_derive))]
+
+use libc;
+use std::io;
+use std::os::unix::io::RawFd;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::u64;
+
+use process_table::PID;
+use vm::state::RcState;
+
+/// The maximum number of events to process for every call to epoll_wait().
+const EVENTS: usize = 64;
+
+/// The event data used for the descriptor that wakes up the poller.
+const WAKEUP: u64 = u64::MAX;
+
+/// The type of operation a process is waiting to perform on a socket.
+#[derive(Clone, Copy)]
+pub enum Interest {
+    Read,
+    Write,
+}
+
+impl Interest {
+    fn events(self) -> u32 {
+        let events = match self {
+            Interest::Read => libc::EPOLLIN | libc::EPOLLRDHUP,
+            Interest::Write => libc::EPOLLOUT,
+        };
+
+        (events | libc::EPOLLONESHOT) as u32
+    }
+}
+
+pub struct NetworkPoller {
+    /// The file descriptor of the epoll instance.
+    epoll: RawFd,
+
+    /// An eventfd used for waking up the thread waiting for events.
+    wakeup: RawFd,
+
+    /// Boolean that indicates if we should keep polling.
+    poll: AtomicBool,
+}
+
+impl NetworkPoller {
+    pub fn new() -> Self {
+        let epoll = unsafe { libc::epoll_create1(libc::EPOLL_CLOEXEC) };
+
+        if epoll == -1 {
+            panic!(
+                "Failed to create the network poller: {}",
+                io::Error::last_os_error()
+            );
+        }
+
+        let wakeup =
+            unsafe { libc::eventfd(0, libc::EFD_CLOEXEC | libc::EFD_NONBLOCK) };
+
+        if wakeup == -1 {
+            panic!(
+                "Failed to create the network poller: {}",
+                io::Error::last_os_error()
+            );
+        }
+
+        let poller = NetworkPoller {
+            epoll,
+            wakeup,
+            poll: AtomicBool::new(true),
+        };
+
+        let mut event = libc::epoll_event {
+            events: libc::EPOLLIN as u32,
+            u64: WAKEUP,
+        };
+
+        poller
+            .control(libc::EPOLL_CTL_ADD, wakeup, &mut event)
+            .expect("Failed to register the wakeup descriptor");
+
+        poller
+    }
+
+    /// Registers a file descriptor, resuming the process with the given PID
+    /// once the descriptor is ready.
+    pub fn register(
+        &self,
+        fd: RawFd,
+        pid: PID,
+        interest: Interest,
+    ) -> io::Result<()> {
+        let mut event = libc::epoll_event {
+            events: interest.events(),
+            u64: pid as u64,
+        };
+
+        match self.control(libc::EPOLL_CTL_ADD, fd, &mut event) {
+            // A descriptor that was registered before stays registered, but is
+            // disabled after its event is reported.
+            Err(ref error) if error.raw_os_error() == Some(libc::EEXIST) => {
+                self.control(libc::EPOLL_CTL_MOD, fd, &mut event)
+            }
+            result => result,
+        }
+    }
+
+    /// Resumes processes as the sockets they are waiting for become ready,
+    /// until the poller is terminated.
+    pub fn poll(&self, state: &RcState) {
+        let mut events = vec![libc::epoll_event { events: 0, u64: 0 }; EVENTS];
+
+        while self.should_poll() {
+            let amount = unsafe {
+                libc::epoll_wait(
+                    self.epoll,
+                    events.as_mut_ptr(),
+                    EVENTS as libc::c_int,
+                    -1,
+                )
+            };
+
+            if amount == -1 {
+                let error = io::Error::last_os_error();
+
+                if error.kind() == io::ErrorKind::Interrupted {
+                    continue;
+                }
+
+                panic!("Failed to poll for network events: {}", error);
+            }
+
+            for event in &events[0..amount as usize] {
+                let data = event.u64;
+
+                if data != WAKEUP {
+                    state.suspension_list.resume_for_io(state, data as PID);
+                }
+            }
+        }
+    }
+
+    pub fn terminate(&self) {
+        self.poll.store(false, Ordering::Release);
+
+        let value = 1_u64;
+
+        unsafe {
+            libc::write(
+                self.wakeup,
+                &value as *const u64 as *const libc::c_void,
+                8,
+            );
+        }
+    }
+
+    pub fn should_poll(&self) -> bool {
+        self.poll.load(Ordering::Acquire)
+    }
+
+    fn control(
+        &self,
+        operation: libc::c_int,
+        fd: RawFd,
+        event: &mut libc::epoll_event,
+    ) -> io::Result<()> {
+        let result =
+            unsafe { libc::epoll_ctl(self.epoll, operation, fd, event) };
+
+        if result == -1 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl Drop for NetworkPoller {
+    fn drop(&mut self) {
+        unsafe {
+            libc::close(self.wakeup);
+            libc::close(self.epoll);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::{TcpListener, TcpStream};
+    use std::os::unix::io::AsRawFd;
+
+    #[test]
+    fn test_register() {
+        let poller = NetworkPoller::new();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+
+        assert!(poller
+            .register(listener.as_raw_fd(), 1, Interest::Read)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_register_twice() {
+        let poller = NetworkPoller::new();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let stream =
+            TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+
+        assert!(poller
+            .register(stream.as_raw_fd(), 1, Interest::Read)
+            .is_ok());
+        assert!(poller
+            .register(stream.as_raw_fd(), 1, Interest::Write)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_register_invalid_descriptor() {
+        let poller = NetworkPoller::new();
+
+        assert!(poller.register(-1, 1, Interest::Read).is_err());
+    }
+
+    #[test]
+    fn test_terminate() {
+        let poller = NetworkPoller::new();
+
+        poller.terminate();
+
+        assert_eq!(poller.should_poll(), false);
+    }
+}
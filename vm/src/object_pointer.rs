@@ -7,6 +7,8 @@ use std::fs;
 use std::hash::{Hash, Hasher as HasherTrait};
 use std::i32;
 use std::i64;
+use std::net;
+use std::process;
 use std::u32;
 use std::usize;
 
@@ -17,6 +19,7 @@ use immix::bitmap::Bitmap;
 use immix::block;
 use immix::bucket::{MAILBOX, MATURE, PERMANENT};
 use immix::local_allocator::YOUNG_MAX_AGE;
+use net::Socket;
 use object::{Object, ObjectStatus};
 use object_value::ObjectValue;
 use process::RcProcess;
@@ -526,6 +529,7 @@ impl ObjectPointer {
                 ObjectValue::InternedString(ref val) => {
                     hasher.write_string(val)
                 }
+                ObjectValue::Reference(val) => hasher.write_reference(val),
                 _ => {
                     if !self.is_permanent() {
                         return Err(
@@ -558,6 +562,21 @@ impl ObjectPointer {
     def_value_getter!(block_value, get, as_block, &Box<Block>);
     def_value_getter!(binding_value, get, as_binding, RcBinding);
     def_value_getter!(bigint_value, get, as_bigint, &BigInt);
+    def_value_getter!(reference_value, get, as_reference, u64);
+    def_value_getter!(
+        tcp_listener_value,
+        get,
+        as_tcp_listener,
+        &net::TcpListener
+    );
+    def_value_getter!(tcp_stream_value, get, as_tcp_stream, &net::TcpStream);
+    def_value_getter!(socket_value, get, as_socket, Socket);
+    def_value_getter!(
+        child_process_value_mut,
+        get_mut,
+        as_child_process_mut,
+        &mut process::Child
+    );
     def_value_getter!(hasher_value_mut, get_mut, as_hasher_mut, &mut Hasher);
 
     def_value_getter!(byte_array_value, get, as_byte_array, &Vec<u8>);
@@ -1149,4 +1168,22 @@ mod tests {
         assert_eq!(small.i32_value().unwrap(), 5);
         assert!(large.i32_value().is_err());
     }
+
+    #[test]
+    fn test_hash_object_with_reference() {
+        let mut alloc = local_allocator();
+        let mut hasher = Hasher::new();
+        let ref1 = alloc.allocate_without_prototype(object_value::reference(1));
+        let ref2 = alloc.allocate_without_prototype(object_value::reference(1));
+
+        ref1.hash_object(&mut hasher).unwrap();
+
+        let hash1 = hasher.finish();
+
+        ref2.hash_object(&mut hasher).unwrap();
+
+        let hash2 = hasher.finish();
+
+        assert_eq!(hash1, hash2);
+    }
 }
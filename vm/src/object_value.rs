@@ -7,11 +7,16 @@
 use num_bigint::BigInt;
 use std::fs;
 use std::mem;
+use std::net;
+use std::os::unix::net as unix;
+use std::process;
 
 use arc_without_weak::ArcWithoutWeak;
 use binding::RcBinding;
 use block::Block;
+use child_process::ChildProcess;
 use hasher::Hasher;
+use net::Socket;
 use object_pointer::ObjectPointer;
 
 /// Enum for storing different values in an Object.
@@ -44,6 +49,31 @@ pub enum ObjectValue {
 
     /// An Array of bytes, typically produced by reading from a stream of sorts.
     ByteArray(Box<Vec<u8>>),
+
+    /// A unique reference generated by the VM, typically used for matching a
+    /// reply with the request it belongs to.
+    Reference(u64),
+
+    /// A non-blocking TCP socket listening for incoming connections.
+    TcpListener(Box<net::TcpListener>),
+
+    /// A non-blocking TCP socket connected to a remote address.
+    TcpStream(Box<net::TcpStream>),
+
+    /// A non-blocking UDP socket.
+    UdpSocket(Box<net::UdpSocket>),
+
+    /// A non-blocking Unix socket listening for incoming connections.
+    UnixListener(Box<unix::UnixListener>),
+
+    /// A non-blocking Unix stream socket.
+    UnixStream(Box<unix::UnixStream>),
+
+    /// A non-blocking Unix datagram socket.
+    UnixDatagram(Box<unix::UnixDatagram>),
+
+    /// An OS child process, spawned by the VM.
+    ChildProcess(Box<ChildProcess>),
 }
 
 impl ObjectValue {
@@ -121,6 +151,13 @@ impl ObjectValue {
         }
     }
 
+    pub fn is_reference(&self) -> bool {
+        match *self {
+            ObjectValue::Reference(_) => true,
+            _ => false,
+        }
+    }
+
     pub fn as_float(&self) -> Result<f64, String> {
         match *self {
             ObjectValue::Float(val) => Ok(val),
@@ -221,6 +258,55 @@ impl ObjectValue {
         }
     }
 
+    pub fn as_reference(&self) -> Result<u64, String> {
+        match *self {
+            ObjectValue::Reference(val) => Ok(val),
+            _ => Err("ObjectValue::as_reference() called on a non reference"
+                .to_string()),
+        }
+    }
+
+    pub fn as_tcp_listener(&self) -> Result<&net::TcpListener, String> {
+        match *self {
+            ObjectValue::TcpListener(ref val) => Ok(val),
+            _ => Err("ObjectValue::as_tcp_listener() called on a non listener"
+                .to_string()),
+        }
+    }
+
+    pub fn as_tcp_stream(&self) -> Result<&net::TcpStream, String> {
+        match *self {
+            ObjectValue::TcpStream(ref val) => Ok(val),
+            _ => Err("ObjectValue::as_tcp_stream() called on a non TCP stream"
+                .to_string()),
+        }
+    }
+
+    pub fn as_socket(&self) -> Result<Socket, String> {
+        match *self {
+            ObjectValue::TcpListener(ref val) => Ok(Socket::TcpListener(val)),
+            ObjectValue::TcpStream(ref val) => Ok(Socket::TcpStream(val)),
+            ObjectValue::UdpSocket(ref val) => Ok(Socket::UdpSocket(val)),
+            ObjectValue::UnixListener(ref val) => Ok(Socket::UnixListener(val)),
+            ObjectValue::UnixStream(ref val) => Ok(Socket::UnixStream(val)),
+            ObjectValue::UnixDatagram(ref val) => Ok(Socket::UnixDatagram(val)),
+            _ => Err("ObjectValue::as_socket() called on a non socket"
+                .to_string()),
+        }
+    }
+
+    pub fn as_child_process_mut(
+        &mut self,
+    ) -> Result<&mut process::Child, String> {
+        match *self {
+            ObjectValue::ChildProcess(ref mut val) => Ok(&mut val.child),
+            _ => Err(
+                "ObjectValue::as_child_process_mut() called on a non process"
+                    .to_string(),
+            ),
+        }
+    }
+
     pub fn as_hasher_mut(&mut self) -> Result<&mut Hasher, String> {
         match *self {
             ObjectValue::Hasher(ref mut val) => Ok(val),
@@ -247,7 +333,8 @@ impl ObjectValue {
             | ObjectValue::Integer(_)
             | ObjectValue::String(_)
             | ObjectValue::BigInt(_)
-            | ObjectValue::InternedString(_) => true,
+            | ObjectValue::InternedString(_)
+            | ObjectValue::Reference(_) => true,
             _ => false,
         }
     }
@@ -301,6 +388,38 @@ pub fn byte_array(value: Vec<u8>) -> ObjectValue {
     ObjectValue::ByteArray(Box::new(value))
 }
 
+pub fn reference(value: u64) -> ObjectValue {
+    ObjectValue::Reference(value)
+}
+
+pub fn tcp_listener(value: net::TcpListener) -> ObjectValue {
+    ObjectValue::TcpListener(Box::new(value))
+}
+
+pub fn tcp_stream(value: net::TcpStream) -> ObjectValue {
+    ObjectValue::TcpStream(Box::new(value))
+}
+
+pub fn udp_socket(value: net::UdpSocket) -> ObjectValue {
+    ObjectValue::UdpSocket(Box::new(value))
+}
+
+pub fn unix_listener(value: unix::UnixListener) -> ObjectValue {
+    ObjectValue::UnixListener(Box::new(value))
+}
+
+pub fn unix_stream(value: unix::UnixStream) -> ObjectValue {
+    ObjectValue::UnixStream(Box::new(value))
+}
+
+pub fn unix_datagram(value: unix::UnixDatagram) -> ObjectValue {
+    ObjectValue::UnixDatagram(Box::new(value))
+}
+
+pub fn child_process(value: process::Child) -> ObjectValue {
+    ObjectValue::ChildProcess(Box::new(ChildProcess::new(value)))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -413,6 +532,12 @@ mod tests {
         assert_eq!(ObjectValue::None.is_binding(), false);
     }
 
+    #[test]
+    fn test_is_reference() {
+        assert!(ObjectValue::Reference(1).is_reference());
+        assert_eq!(ObjectValue::None.is_reference(), false);
+    }
+
     #[test]
     fn test_as_float_without_float() {
         assert!(ObjectValue::None.as_float().is_err());
@@ -619,6 +744,7 @@ mod tests {
         assert!(string("a".to_string()).is_immutable());
         assert!(float(10.5).is_immutable());
         assert!(interned_string("a".to_string()).is_immutable());
+        assert!(reference(1).is_immutable());
     }
 
     #[test]
@@ -650,4 +776,62 @@ mod tests {
         assert!(result.is_ok());
         assert_eq!(result.unwrap().len(), 1);
     }
+
+    #[test]
+    fn test_as_reference_without_reference() {
+        assert!(ObjectValue::None.as_reference().is_err());
+    }
+
+    #[test]
+    fn test_as_reference_with_reference() {
+        let value = ObjectValue::Reference(4);
+
+        assert_eq!(value.as_reference().unwrap(), 4);
+    }
+
+    #[test]
+    fn test_as_tcp_listener() {
+        let listener = net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let value = tcp_listener(listener);
+
+        assert!(value.as_tcp_listener().is_ok());
+        assert!(value.as_tcp_stream().is_err());
+    }
+
+    #[test]
+    fn test_as_tcp_stream() {
+        let listener = net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let value = tcp_stream(net::TcpStream::connect(address).unwrap());
+
+        assert!(value.as_tcp_stream().is_ok());
+        assert!(value.as_tcp_listener().is_err());
+    }
+
+    #[test]
+    fn test_as_socket_without_socket() {
+        assert!(ObjectValue::None.as_socket().is_err());
+    }
+
+    #[test]
+    fn test_as_socket_with_socket() {
+        let (first, _) = unix::UnixDatagram::pair().unwrap();
+        let value = unix_datagram(first);
+
+        assert!(value.as_socket().is_ok());
+        assert!(value.as_tcp_stream().is_err());
+    }
+
+    #[test]
+    fn test_as_child_process_mut_without_child_process() {
+        assert!(ObjectValue::None.as_child_process_mut().is_err());
+    }
+
+    #[test]
+    fn test_as_child_process_mut_with_child_process() {
+        let child = process::Command::new("true").spawn().unwrap();
+        let mut value = child_process(child);
+
+        assert!(value.as_child_process_mut().unwrap().wait().is_ok());
+    }
 }
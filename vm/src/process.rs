@@ -1,9 +1,13 @@
 use num_bigint::BigInt;
 use std::cell::UnsafeCell;
+use std::collections::HashSet;
 use std::hash::{Hash, Hasher};
 use std::i64;
 use std::mem;
+use std::ptr;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use binding::RcBinding;
 use block::Block;
@@ -16,7 +20,7 @@ use immix::block_list::BlockList;
 use immix::copy_object::CopyObject;
 use immix::global_allocator::RcGlobalAllocator;
 use immix::local_allocator::LocalAllocator;
-use mailbox::Mailbox;
+use mailbox::{Mailbox, OverflowPolicy, SendResult};
 use object_pointer::ObjectPointer;
 use object_value;
 use process_table::PID;
@@ -44,6 +48,77 @@ pub enum ProcessStatus {
 
     /// The process has finished execution.
     Finished,
+
+    /// The process is waiting for a socket to become ready.
+    WaitingForIo,
+}
+
+/// The reason a process finished execution.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExitReason {
+    /// The process finished normally.
+    Normal,
+
+    /// The process panicked. The first value is the panic message, the second
+    /// value the stack trace at the time of the panic (the most recent call
+    /// comes last).
+    Panic(String, Vec<String>),
+
+    /// The process was terminated by another process, with the given reason.
+    Terminated(String),
+}
+
+impl ExitReason {
+    /// Returns the integer used to represent this reason in exit messages.
+    pub fn code(&self) -> i64 {
+        match *self {
+            ExitReason::Normal => 0,
+            ExitReason::Panic(_, _) => 1,
+            ExitReason::Terminated(_) => 2,
+        }
+    }
+
+    /// Returns true if the process did not finish normally.
+    pub fn is_abnormal(&self) -> bool {
+        match *self {
+            ExitReason::Normal => false,
+            _ => true,
+        }
+    }
+}
+
+/// The processes to notify when a process finishes.
+pub struct Watchers {
+    /// The PIDs of the processes monitoring the process.
+    pub monitors: HashSet<PID>,
+
+    /// The PIDs of the processes linked to the process.
+    pub links: HashSet<PID>,
+
+    /// Set to true once the process finished, after which no new watchers can
+    /// be added.
+    pub closed: bool,
+}
+
+#[cfg_attr(feature = "cargo-clippy", allow(new_without_default_derive))]
+impl Watchers {
+    pub fn new() -> Self {
+        Watchers {
+            monitors: HashSet::new(),
+            links: HashSet::new(),
+            closed: false,
+        }
+    }
+}
+
+/// The state of a selective receive that is in progress.
+pub struct SelectiveReceive {
+    /// The execution context that started the receive. If this context is
+    /// popped before the receive completes, the receive is aborted.
+    pub context: *const ExecutionContext,
+
+    /// The time at which the receive times out, if a timeout is given.
+    pub deadline: Option<Instant>,
 }
 
 impl ProcessStatus {
@@ -70,6 +145,9 @@ pub struct LocalData {
     /// is stored directly in a Process.
     pub mailbox: Mailbox,
 
+    /// The selective receive that is in progress, if any.
+    pub selective_receive: Option<SelectiveReceive>,
+
     /// The number of young garbage collections that have been performed.
     pub young_collections: usize,
 
@@ -90,6 +168,24 @@ pub struct Process {
     /// The status of this process.
     pub status: Mutex<ProcessStatus>,
 
+    /// The reason this process finished, if it finished at all.
+    pub exit_reason: Mutex<Option<ExitReason>>,
+
+    /// The reason to terminate this process with, set when another process
+    /// requests the termination of this process.
+    pub pending_exit: Mutex<Option<ExitReason>>,
+
+    /// Set to true when the termination of this process is requested, allowing
+    /// safepoints to check for this without acquiring a lock.
+    pub terminating: AtomicBool,
+
+    /// The processes monitoring or linked to this process.
+    pub watchers: Mutex<Watchers>,
+
+    /// When set to true, the failure of a linked process results in an exit
+    /// message instead of this process being terminated.
+    pub trap_exits: AtomicBool,
+
     /// Data stored in a process that should only be modified by a single thread
     /// at once.
     pub local_data: UnsafeCell<LocalData>,
@@ -111,6 +207,7 @@ impl Process {
             allocator: LocalAllocator::new(global_allocator.clone(), config),
             context: Box::new(context),
             mailbox: Mailbox::new(global_allocator, config),
+            selective_receive: None,
             young_collections: 0,
             mature_collections: 0,
             mailbox_collections: 0,
@@ -120,6 +217,11 @@ impl Process {
         let process = Process {
             pid,
             status: Mutex::new(ProcessStatus::Scheduled),
+            exit_reason: Mutex::new(None),
+            pending_exit: Mutex::new(None),
+            terminating: AtomicBool::new(false),
+            watchers: Mutex::new(Watchers::new()),
+            trap_exits: AtomicBool::new(false),
             local_data: UnsafeCell::new(local_data),
         };
 
@@ -173,6 +275,7 @@ impl Process {
             ProcessStatus::SuspendForGc => 3,
             ProcessStatus::WaitingForMessage => 4,
             ProcessStatus::Finished => 5,
+            ProcessStatus::WaitingForIo => 6,
         }
     }
 
@@ -183,6 +286,19 @@ impl Process {
     pub fn pop_context(&self) -> bool {
         let local_data = self.local_data_mut();
 
+        // Popping the context that started a selective receive (e.g. when a
+        // value is thrown by the condition) aborts the receive.
+        let aborts_receive = local_data
+            .selective_receive
+            .as_ref()
+            .map_or(false, |receive| {
+                ptr::eq(receive.context, &*local_data.context)
+            });
+
+        if aborts_receive {
+            self.finish_selective_receive();
+        }
+
         if let Some(parent) = local_data.context.parent.take() {
             local_data.context = parent;
 
@@ -313,10 +429,124 @@ impl Process {
         }
     }
 
+    /// Sends a message to the current process on behalf of the VM, instead of
+    /// another process.
+    ///
+    /// The capacity of the mailbox is ignored, as the VM can't be suspended.
+    pub fn send_message_from_vm(&self, message: ObjectPointer) {
+        self.local_data_mut().mailbox.send_from_external(message);
+    }
+
+    /// Sends a message to the current process, taking the capacity of its
+    /// mailbox into account.
+    ///
+    /// A process sending a message to itself ignores the capacity, as it would
+    /// otherwise be able to suspend itself indefinitely.
+    ///
+    /// An error is returned if the message can't be sent to another process,
+    /// for example because it contains a child process.
+    pub fn try_send_message(
+        &self,
+        sender: &RcProcess,
+        message: ObjectPointer,
+    ) -> Result<SendResult, String> {
+        let mailbox = &mut self.local_data_mut().mailbox;
+
+        if sender.pid == self.pid {
+            mailbox.send_from_self(message);
+
+            Ok(SendResult::Sent)
+        } else {
+            mailbox.try_send_from_external(message)
+        }
+    }
+
+    /// Returns the number of messages in the mailbox.
+    pub fn mailbox_length(&self) -> usize {
+        self.local_data().mailbox.len()
+    }
+
+    /// Limits the number of messages the mailbox can hold. A capacity of 0
+    /// removes the limit.
+    pub fn set_mailbox_capacity(
+        &self,
+        capacity: usize,
+        overflow: OverflowPolicy,
+    ) {
+        self.local_data_mut()
+            .mailbox
+            .set_capacity(capacity, overflow);
+    }
+
+    pub fn mailbox_has_space(&self) -> bool {
+        self.local_data().mailbox.has_space()
+    }
+
+    /// Returns true if the mailbox can only hold a limited number of
+    /// messages.
+    pub fn has_bounded_mailbox(&self) -> bool {
+        self.local_data().mailbox.is_bounded()
+    }
+
+    /// Stores the message to send to this process when a timer fires.
+    pub fn add_timer(
+        &self,
+        id: u64,
+        message: ObjectPointer,
+    ) -> Result<(), String> {
+        self.local_data_mut().mailbox.add_timer(id, message)
+    }
+
+    /// Sends the message of a timer to this process, returning false if the
+    /// timer was cancelled.
+    pub fn fire_timer(&self, id: u64, periodic: bool) -> bool {
+        self.local_data_mut()
+            .mailbox
+            .fire_timer(id, periodic, || self.is_finished())
+    }
+
+    pub fn cancel_timer(&self, id: u64) -> bool {
+        self.local_data_mut().mailbox.cancel_timer(id)
+    }
+
+    /// Removes the messages of all timers that would send a message to this
+    /// process.
+    pub fn clear_timers(&self) {
+        self.local_data_mut().mailbox.clear_timers();
+    }
+
     /// Returns a message from the mailbox.
     pub fn receive_message(&self) -> Option<ObjectPointer> {
+        let result = self.local_data_mut().mailbox.receive();
+
+        self.move_received_message(result)
+    }
+
+    /// Returns a message to test using the condition of a selective receive.
+    ///
+    /// The message remains part of the mailbox until it is accepted using
+    /// `accept_message`, or put back when the receive finishes.
+    pub fn receive_candidate(&self) -> Option<ObjectPointer> {
+        let local_data = self.local_data_mut();
+        let result = local_data.mailbox.receive_candidate();
+        let message = self.move_received_message(result);
+
+        local_data.mailbox.candidate = message;
+
+        message
+    }
+
+    /// Removes the message that matched the condition of a selective receive
+    /// from the mailbox.
+    pub fn accept_message(&self) {
+        self.local_data_mut().mailbox.accept_message();
+    }
+
+    fn move_received_message(
+        &self,
+        (should_copy, pointer_opt): (bool, Option<ObjectPointer>),
+    ) -> Option<ObjectPointer> {
         let local_data = self.local_data_mut();
-        let (should_copy, pointer_opt) = local_data.mailbox.receive();
 
         if let Some(mailbox_pointer) = pointer_opt {
             let pointer = if should_copy {
@@ -338,6 +568,73 @@ impl Process {
         }
     }
 
+    /// Starts a selective receive for the given context, unless one is already
+    /// in progress.
+    pub fn start_selective_receive(&self, context: *const ExecutionContext) {
+        let local_data = self.local_data_mut();
+
+        if local_data.selective_receive.is_none() {
+            local_data.selective_receive = Some(SelectiveReceive {
+                context,
+                deadline: None,
+            });
+        }
+    }
+
+    /// Returns the message that was tested by a selective receive, if any.
+    pub fn take_receive_candidate(&self) -> Option<ObjectPointer> {
+        self.local_data_mut().mailbox.candidate.take()
+    }
+
+    /// Sets aside a message that did not match the condition of a selective
+    /// receive.
+    pub fn skip_message(&self, message: ObjectPointer) {
+        self.local_data_mut().mailbox.skipped.push_back(message);
+    }
+
+    /// Finishes a selective receive, putting all skipped messages back into
+    /// the mailbox.
+    pub fn finish_selective_receive(&self) {
+        let local_data = self.local_data_mut();
+
+        local_data.mailbox.restore_skipped();
+        local_data.selective_receive = None;
+    }
+
+    /// Returns the number of milliseconds remaining before the current
+    /// selective receive times out, or 0 if it timed out.
+    ///
+    /// The deadline is set the first time this method is called for a receive,
+    /// ensuring the process doesn't wait longer than the timeout when it
+    /// receives messages that don't match.
+    pub fn selective_receive_timeout(
+        &self,
+        timeout: Option<u64>,
+    ) -> Option<u64> {
+        let receive = self.local_data_mut().selective_receive.as_mut()?;
+
+        if receive.deadline.is_none() {
+            receive.deadline = timeout
+                .map(|time| Instant::now() + Duration::from_millis(time));
+        }
+
+        let now = Instant::now();
+
+        receive.deadline.map(|deadline| {
+            if deadline > now {
+                let remaining = deadline - now;
+
+                // Rounding up ensures we don't wake up right before the
+                // deadline, only to suspend again.
+                remaining.as_secs() * 1000
+                    + (u64::from(remaining.subsec_nanos()) + 999_999)
+                        / 1_000_000
+            } else {
+                0
+            }
+        })
+    }
+
     pub fn advance_instruction_index(&self) {
         self.local_data_mut().context.instruction_index += 1;
     }
@@ -385,6 +682,100 @@ impl Process {
         self.set_status(ProcessStatus::Finished);
     }
 
+    pub fn is_finished(&self) -> bool {
+        match *lock!(self.status) {
+            ProcessStatus::Finished => true,
+            _ => false,
+        }
+    }
+
+    pub fn set_exit_reason(&self, reason: ExitReason) {
+        *lock!(self.exit_reason) = Some(reason);
+    }
+
+    pub fn exit_reason(&self) -> Option<ExitReason> {
+        lock!(self.exit_reason).clone()
+    }
+
+    /// Requests the termination of this process.
+    ///
+    /// The process is terminated the next time it's executed. If a termination
+    /// was already requested, the original reason is retained.
+    pub fn request_termination(&self, reason: ExitReason) {
+        let mut pending = lock!(self.pending_exit);
+
+        if pending.is_none() {
+            *pending = Some(reason);
+
+            self.terminating.store(true, Ordering::Release);
+        }
+    }
+
+    pub fn pending_termination(&self) -> Option<ExitReason> {
+        lock!(self.pending_exit).clone()
+    }
+
+    pub fn is_termination_requested(&self) -> bool {
+        self.terminating.load(Ordering::Acquire)
+    }
+
+    /// Adds a monitoring process, returning false if this process already
+    /// finished.
+    pub fn add_monitor(&self, pid: PID) -> bool {
+        let mut watchers = lock!(self.watchers);
+
+        if watchers.closed {
+            return false;
+        }
+
+        watchers.monitors.insert(pid);
+
+        true
+    }
+
+    pub fn remove_monitor(&self, pid: PID) {
+        lock!(self.watchers).monitors.remove(&pid);
+    }
+
+    /// Adds a linked process, returning false if this process already
+    /// finished.
+    pub fn add_link(&self, pid: PID) -> bool {
+        let mut watchers = lock!(self.watchers);
+
+        if watchers.closed {
+            return false;
+        }
+
+        watchers.links.insert(pid);
+
+        true
+    }
+
+    pub fn remove_link(&self, pid: PID) {
+        lock!(self.watchers).links.remove(&pid);
+    }
+
+    /// Returns all watchers of this process, preventing new ones from being
+    /// added.
+    pub fn close_watchers(&self) -> Watchers {
+        let mut watchers = lock!(self.watchers);
+        let mut closed = Watchers::new();
+
+        closed.closed = true;
+
+        mem::swap(&mut *watchers, &mut closed);
+
+        closed
+    }
+
+    pub fn set_trap_exits(&self, value: bool) {
+        self.trap_exits.store(value, Ordering::Release);
+    }
+
+    pub fn traps_exits(&self) -> bool {
+        self.trap_exits.load(Ordering::Acquire)
+    }
+
     pub fn scheduled(&self) {
         self.set_status(ProcessStatus::Scheduled);
     }
@@ -408,12 +799,29 @@ impl Process {
         }
     }
 
+    pub fn waiting_for_io(&self) {
+        self.set_status(ProcessStatus::WaitingForIo);
+    }
+
+    pub fn is_waiting_for_io(&self) -> bool {
+        match *lock!(self.status) {
+            ProcessStatus::WaitingForIo => true,
+            _ => false,
+        }
+    }
+
     pub fn wakeup_after_suspension_timeout(&self) {
         if self.is_waiting_for_message() {
             // When a timeout expires we don't want to retry the last
             // instruction as otherwise we'd end up in an infinite loop if
             // no message is received.
             self.advance_instruction_index();
+
+            // Messages skipped by a selective receive have to be made
+            // available again, as the receive won't be retried.
+            if self.local_data().selective_receive.is_some() {
+                self.finish_selective_receive();
+            }
         }
     }
 
@@ -535,12 +943,74 @@ impl Hash for Process {
 
 #[cfg(test)]
 mod tests {
+    use super::ExitReason;
     use object_value;
     use std::f64;
     use std::i32;
     use std::i64;
     use vm::test::setup;
 
+    #[test]
+    fn test_exit_reason() {
+        let (_machine, _block, process) = setup();
+
+        assert!(process.exit_reason().is_none());
+
+        process.set_exit_reason(ExitReason::Panic(
+            "oops".to_string(),
+            vec!["\"foo.inko\", line 1, in \"main\"".to_string()],
+        ));
+
+        assert_eq!(
+            process.exit_reason(),
+            Some(ExitReason::Panic(
+                "oops".to_string(),
+                vec!["\"foo.inko\", line 1, in \"main\"".to_string()]
+            ))
+        );
+    }
+
+    #[test]
+    fn test_request_termination() {
+        let (_machine, _block, process) = setup();
+
+        assert!(!process.is_termination_requested());
+
+        process.request_termination(ExitReason::Panic(
+            "foo".to_string(),
+            Vec::new(),
+        ));
+
+        process.request_termination(ExitReason::Normal);
+
+        assert!(process.is_termination_requested());
+        assert_eq!(
+            process.pending_termination(),
+            Some(ExitReason::Panic("foo".to_string(), Vec::new()))
+        );
+    }
+
+    #[test]
+    fn test_close_watchers() {
+        let (_machine, _block, process) = setup();
+
+        assert!(process.add_monitor(1));
+        assert!(process.add_link(2));
+        assert!(process.add_link(3));
+
+        process.remove_link(3);
+
+        let watchers = process.close_watchers();
+
+        assert!(watchers.monitors.contains(&1));
+        assert!(watchers.links.contains(&2));
+        assert!(!watchers.links.contains(&3));
+
+        assert_eq!(process.add_monitor(4), false);
+        assert_eq!(process.add_link(5), false);
+        assert!(process.close_watchers().monitors.is_empty());
+    }
+
     #[test]
     fn test_contexts() {
         let (_machine, _block, process) = setup();
@@ -0,0 +1,144 @@
+//! Registry for mapping names to PIDs.
+//!
+//! A ProcessRegistry allows processes to be addressed using a name instead of
+//! a PID, making it easier to use well-known processes such as loggers. A name
+//! can only be mapped to a single PID, and a PID can only have a single name.
+//!
+//! For example:
+//!
+//!     let mut registry = ProcessRegistry::new();
+//!
+//!     registry.register("logger", 4);
+//!     registry.whereis(&"logger"); // => Some(4)
+//!
+//! Names are removed from the registry when the process they belong to
+//! finishes, so a name never maps to a process that no longer exists.
+
+#![cfg_attr(feature = "cargo-clippy", allow(new_without_default_derive))]
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use process_table::PID;
+
+pub struct ProcessRegistry<T: Eq + Hash + Copy> {
+    /// The registered names and the PIDs they map to.
+    names: HashMap<T, PID>,
+
+    /// The PIDs that have a name, and the name of every PID.
+    pids: HashMap<PID, T>,
+}
+
+impl<T: Eq + Hash + Copy> ProcessRegistry<T> {
+    pub fn new() -> Self {
+        ProcessRegistry {
+            names: HashMap::new(),
+            pids: HashMap::new(),
+        }
+    }
+
+    /// Maps a name to a PID.
+    ///
+    /// False is returned if the name is already in use, or if the PID already
+    /// has a name.
+    pub fn register(&mut self, name: T, pid: PID) -> bool {
+        if self.names.contains_key(&name) || self.pids.contains_key(&pid) {
+            return false;
+        }
+
+        self.names.insert(name, pid);
+        self.pids.insert(pid, name);
+
+        true
+    }
+
+    /// Removes a name, returning the PID it was mapped to.
+    pub fn unregister(&mut self, name: &T) -> Option<PID> {
+        let pid = self.names.remove(name)?;
+
+        self.pids.remove(&pid);
+
+        Some(pid)
+    }
+
+    /// Returns the PID a name is mapped to.
+    pub fn whereis(&self, name: &T) -> Option<PID> {
+        self.names.get(name).cloned()
+    }
+
+    /// Returns all registered names.
+    pub fn names(&self) -> Vec<T> {
+        self.names.keys().cloned().collect()
+    }
+
+    /// Removes the name of the given PID, if it has any.
+    pub fn release(&mut self, pid: PID) {
+        if let Some(name) = self.pids.remove(&pid) {
+            self.names.remove(&name);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_register() {
+        let mut registry = ProcessRegistry::new();
+
+        assert!(registry.register("foo", 1));
+        assert!(!registry.register("foo", 2));
+        assert!(!registry.register("bar", 1));
+        assert!(registry.register("bar", 2));
+    }
+
+    #[test]
+    fn test_unregister() {
+        let mut registry = ProcessRegistry::new();
+
+        registry.register("foo", 1);
+
+        assert_eq!(registry.unregister(&"foo"), Some(1));
+        assert!(registry.unregister(&"foo").is_none());
+
+        // The PID can be registered again once its name has been removed.
+        assert!(registry.register("bar", 1));
+    }
+
+    #[test]
+    fn test_whereis() {
+        let mut registry = ProcessRegistry::new();
+
+        registry.register("foo", 1);
+
+        assert_eq!(registry.whereis(&"foo"), Some(1));
+        assert!(registry.whereis(&"bar").is_none());
+    }
+
+    #[test]
+    fn test_names() {
+        let mut registry = ProcessRegistry::new();
+
+        registry.register("foo", 1);
+        registry.register("bar", 2);
+
+        let mut names = registry.names();
+
+        names.sort();
+
+        assert_eq!(names, vec!["bar", "foo"]);
+    }
+
+    #[test]
+    fn test_release() {
+        let mut registry = ProcessRegistry::new();
+
+        registry.register("foo", 1);
+        registry.release(1);
+        registry.release(2);
+
+        assert!(registry.whereis(&"foo").is_none());
+        assert!(registry.register("foo", 2));
+    }
+}
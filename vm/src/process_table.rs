@@ -25,6 +25,12 @@
 //! architecture. In most cases PID recycling will only occur very rarely, if
 //! ever at all.
 //!
+//! A PID consists of an index and an epoch. The index identifies a slot in the
+//! table, while the epoch is incremented every time the indexes wrap around.
+//! When an index is recycled, the resulting PID uses a different epoch. This
+//! way a PID held on to by a process after the original process finished will
+//! never refer to the process that reuses its index.
+//!
 //! ## PID Availability
 //!
 //! It's possible (though very unlikely) for a ProcessTable to run out of
@@ -34,32 +40,60 @@
 #![cfg_attr(feature = "cargo-clippy", allow(new_without_default_derive))]
 
 use std::collections::HashMap;
-use std::usize;
 
 /// The type of a PID.
 pub type PID = usize;
 
-/// The maximum PID value.
-pub const MAX_PID: PID = usize::MAX;
+/// The number of bits used for the index of a PID.
+const INDEX_BITS: usize = 32;
+
+/// The number of bits used for the epoch of a PID.
+///
+/// PIDs are passed around as tagged integers. These are signed 63 bits
+/// integers, leaving 62 bits for positive values. 32 index bits and 30 epoch
+/// bits use exactly these 62 bits, which is why the epoch doesn't use all
+/// remaining bits of a PID.
+const EPOCH_BITS: usize = 30;
+
+/// The maximum index of a PID.
+pub const MAX_INDEX: usize = (1 << INDEX_BITS) - 1;
+
+/// The maximum epoch of a PID.
+pub const MAX_EPOCH: usize = (1 << EPOCH_BITS) - 1;
+
+/// Returns the index of a PID.
+pub fn index(pid: PID) -> usize {
+    pid & MAX_INDEX
+}
+
+/// Returns the epoch of a PID.
+pub fn epoch(pid: PID) -> usize {
+    pid >> INDEX_BITS
+}
 
 pub struct ProcessTable<T: Clone> {
-    /// The PID to use for the next process.
-    next_pid: PID,
+    /// The index to use for the next process.
+    next_index: usize,
+
+    /// The epoch to use for new PIDs, incremented whenever the indexes wrap
+    /// around.
+    epoch: usize,
 
-    /// When set to true, previously used PIDs may be recycled.
+    /// When set to true, previously used indexes may be recycled.
     recycle: bool,
 
-    /// PIDs of existing processes, and their corresponding processes.
+    /// The indexes of existing processes, and their PIDs and processes.
     ///
-    /// An entry's value may be set to None, indicating that the PID has been
+    /// An entry's process may be set to None, indicating that the PID has been
     /// reserved but a process has yet to be inserted.
-    processes: HashMap<PID, Option<T>>,
+    processes: HashMap<usize, (PID, Option<T>)>,
 }
 
 impl<T: Clone> ProcessTable<T> {
     pub fn new() -> Self {
         ProcessTable {
-            next_pid: 0,
+            next_index: 0,
+            epoch: 0,
             recycle: false,
             processes: HashMap::new(),
         }
@@ -69,14 +103,14 @@ impl<T: Clone> ProcessTable<T> {
     ///
     /// If no PID could be reserved a None value is returned.
     pub fn reserve(&mut self) -> Option<PID> {
-        while self.processes.len() < MAX_PID {
+        while self.processes.len() <= MAX_INDEX {
             let pid = self.next_pid();
 
-            if self.recycle && self.processes.contains_key(&pid) {
+            if self.recycle && self.processes.contains_key(&index(pid)) {
                 continue;
             }
 
-            self.processes.insert(pid, None);
+            self.processes.insert(index(pid), (pid, None));
 
             return Some(pid);
         }
@@ -86,17 +120,31 @@ impl<T: Clone> ProcessTable<T> {
 
     /// Maps a process to the given PID.
     pub fn map(&mut self, pid: PID, process: T) {
-        self.processes.insert(pid, Some(process));
+        self.processes.insert(index(pid), (pid, Some(process)));
     }
 
     /// Releases a PID.
+    ///
+    /// A stale PID (one that uses an index that has been recycled since) is
+    /// ignored.
     pub fn release(&mut self, pid: PID) {
-        self.processes.remove(&pid);
+        let index = index(pid);
+        let current = self.processes.get(&index).map(|entry| entry.0);
+
+        if current == Some(pid) {
+            self.processes.remove(&index);
+        }
     }
 
     /// Returns the process for a given PID.
+    ///
+    /// None is returned for stale PIDs, even if their index has been reused.
     pub fn get(&self, pid: PID) -> Option<T> {
-        if let Some(slot) = self.processes.get(&pid) {
+        if let Some(&(entry_pid, ref slot)) = self.processes.get(&index(pid)) {
+            if entry_pid != pid {
+                return None;
+            }
+
             match *slot {
                 Some(ref process) => Some(process.clone()),
                 None => None,
@@ -107,13 +155,18 @@ impl<T: Clone> ProcessTable<T> {
     }
 
     fn next_pid(&mut self) -> PID {
-        let pid = self.next_pid;
+        let pid = (self.epoch << INDEX_BITS) | self.next_index;
 
-        if pid == MAX_PID {
-            self.next_pid = 0;
+        if self.next_index == MAX_INDEX {
+            self.next_index = 0;
             self.recycle = true;
+            self.epoch = if self.epoch == MAX_EPOCH {
+                0
+            } else {
+                self.epoch + 1
+            };
         } else {
-            self.next_pid += 1;
+            self.next_index += 1;
         }
 
         pid
@@ -123,12 +176,23 @@ impl<T: Clone> ProcessTable<T> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use object_pointer::ObjectPointer;
+
+    #[test]
+    fn test_max_pid_fits_in_tagged_integer() {
+        let pid = (MAX_EPOCH << INDEX_BITS) | MAX_INDEX;
+
+        assert_eq!(index(pid), MAX_INDEX);
+        assert_eq!(epoch(pid), MAX_EPOCH);
+        assert_eq!(ObjectPointer::integer_too_large(pid as i64), false);
+    }
 
     #[test]
     fn test_new() {
         let table = ProcessTable::<()>::new();
 
-        assert_eq!(table.next_pid, 0);
+        assert_eq!(table.next_index, 0);
+        assert_eq!(table.epoch, 0);
         assert_eq!(table.recycle, false);
         assert_eq!(table.processes.len(), 0);
     }
@@ -152,7 +216,7 @@ mod tests {
         let mut table = ProcessTable::<()>::new();
 
         table.reserve();
-        table.next_pid = 0;
+        table.next_index = 0;
         table.recycle = true;
 
         let pid2 = table.reserve();
@@ -160,6 +224,60 @@ mod tests {
         assert_eq!(pid2.unwrap(), 1);
     }
 
+    #[test]
+    fn test_reserve_after_wrapping_around() {
+        let mut table = ProcessTable::<()>::new();
+
+        table.next_index = MAX_INDEX;
+
+        let pid1 = table.reserve().unwrap();
+        let pid2 = table.reserve().unwrap();
+
+        assert_eq!(index(pid1), MAX_INDEX);
+        assert_eq!(epoch(pid1), 0);
+        assert_eq!(index(pid2), 0);
+        assert_eq!(epoch(pid2), 1);
+    }
+
+    #[test]
+    fn test_get_with_stale_pid() {
+        let mut table = ProcessTable::new();
+        let old_pid = table.reserve().unwrap();
+
+        table.map(old_pid, 10);
+        table.release(old_pid);
+
+        table.next_index = MAX_INDEX;
+        table.reserve();
+
+        let new_pid = table.reserve().unwrap();
+
+        table.map(new_pid, 20);
+
+        assert_eq!(index(new_pid), index(old_pid));
+        assert!(new_pid != old_pid);
+        assert!(table.get(old_pid).is_none());
+        assert_eq!(table.get(new_pid).unwrap(), 20);
+    }
+
+    #[test]
+    fn test_release_with_stale_pid() {
+        let mut table = ProcessTable::new();
+        let old_pid = table.reserve().unwrap();
+
+        table.release(old_pid);
+
+        table.next_index = MAX_INDEX;
+        table.reserve();
+
+        let new_pid = table.reserve().unwrap();
+
+        table.map(new_pid, 20);
+        table.release(old_pid);
+
+        assert_eq!(table.get(new_pid).unwrap(), 20);
+    }
+
     #[test]
     fn test_map() {
         let mut table = ProcessTable::new();
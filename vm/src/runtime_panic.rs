@@ -1,5 +1,5 @@
 use colored::*;
-use process::RcProcess;
+use process::{ExitReason, RcProcess};
 
 /// Prints a runtime panic to STDERR.
 pub fn display_panic(process: &RcProcess, message: &str) {
@@ -34,3 +34,24 @@ pub fn display_panic(process: &RcProcess, message: &str) {
         message.bold()
     );
 }
+
+/// Returns the exit reason to use for a process that panicked.
+///
+/// The stack trace stored in the exit reason is in the same format as the one
+/// displayed by `display_panic`, without any colors.
+pub fn exit_reason(process: &RcProcess, message: &str) -> ExitReason {
+    let mut frames = Vec::new();
+
+    for context in process.context().contexts() {
+        frames.push(format!(
+            "{:?}, line {}, in {:?}",
+            context.code.file.string_value().unwrap(),
+            context.line,
+            context.code.name.string_value().unwrap()
+        ));
+    }
+
+    frames.reverse();
+
+    ExitReason::Panic(message.to_string(), frames)
+}
@@ -0,0 +1,515 @@
+//! Delivering OS signals to processes as messages.
+//!
+//! Processes can subscribe to a small set of signals, such as SIGINT and
+//! SIGTERM. When a process subscribes to a signal, a signal handler is
+//! installed that writes the signal number to a pipe. A dedicated thread reads
+//! from this pipe, and sends the signal number as a message to every process
+//! subscribed to the signal. This way the signal handler itself only performs
+//! async-signal-safe operations.
+//!
+//! When none of the subscribed processes are alive any more, the default
+//! action of the signal is restored and the signal is raised again. For
+//! example, a SIGINT received after the only process subscribed to it has
+//! finished terminates the VM, just as it would without any subscribers.
+//!
+//! Once the last process unsubscribes from a signal, the default action of the
+//! signal is restored as well.
+//!
+//! The VM itself uses SIGCHLD to resume processes waiting for an OS child
+//! process to terminate. Once the VM starts doing so, the signal handler for
+//! SIGCHLD stays installed, regardless of any processes subscribing to it.
+
+#![cfg_attr(feature = "cargo-clippy", allow(new_without_default_derive))]
+
+use libc::{self, c_int};
+use std::collections::HashMap;
+use std::io;
+use std::mem;
+use std::os::unix::io::RawFd;
+use std::ptr;
+use std::sync::atomic::{AtomicBool, AtomicIsize, Ordering};
+use std::sync::Mutex;
+
+use object_pointer::ObjectPointer;
+use process::RcProcess;
+use process_table::PID;
+use vm::state::RcState;
+
+/// The signals processes can subscribe to.
+pub const SIGNALS: [c_int; 6] = [
+    libc::SIGHUP,
+    libc::SIGINT,
+    libc::SIGTERM,
+    libc::SIGUSR1,
+    libc::SIGUSR2,
+    libc::SIGCHLD,
+];
+
+/// The byte written to the pipe to wake up the thread reading from it. No
+/// signal uses this number.
+const WAKEUP: u8 = 0;
+
+/// The write end of the pipe signals are written to, or -1 if no signal
+/// handlers are installed.
+static SIGNAL_PIPE: AtomicIsize = AtomicIsize::new(-1);
+
+/// Returns the signal for the given signal number, if processes can
+/// subscribe to it.
+pub fn signal_for_integer(value: i64) -> Result<c_int, String> {
+    SIGNALS
+        .iter()
+        .find(|signal| i64::from(**signal) == value)
+        .cloned()
+        .ok_or_else(|| format!("Signal {} is not supported", value))
+}
+
+pub struct SignalHandler {
+    /// The read end of the pipe signals are written to.
+    read: RawFd,
+
+    /// The write end of the pipe signals are written to.
+    write: RawFd,
+
+    /// The processes subscribed to every signal. A signal only has an entry
+    /// if its signal handler is installed.
+    subscribers: Mutex<HashMap<c_int, Vec<RcProcess>>>,
+
+    /// Boolean that indicates if we should keep reading signals.
+    run: AtomicBool,
+
+    /// Set to true once processes wait for child processes using SIGCHLD.
+    /// This value is only changed while holding on to the subscribers lock.
+    children: AtomicBool,
+}
+
+impl SignalHandler {
+    pub fn new() -> Self {
+        let mut fds = [0; 2];
+
+        if unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC) } == -1 {
+            panic!(
+                "Failed to create the signal handler: {}",
+                io::Error::last_os_error()
+            );
+        }
+
+        // The signal handler must never block, so a signal that arrives while
+        // the pipe is full is discarded. Pending signals are merged by the OS
+        // in a similar way.
+        let flags = unsafe { libc::fcntl(fds[1], libc::F_GETFL) };
+        let result = unsafe {
+            libc::fcntl(fds[1], libc::F_SETFL, flags | libc::O_NONBLOCK)
+        };
+
+        if result == -1 {
+            panic!(
+                "Failed to create the signal handler: {}",
+                io::Error::last_os_error()
+            );
+        }
+
+        SignalHandler {
+            read: fds[0],
+            write: fds[1],
+            subscribers: Mutex::new(HashMap::new()),
+            run: AtomicBool::new(true),
+            children: AtomicBool::new(false),
+        }
+    }
+
+    /// Subscribes a process to a signal, installing the signal handler if
+    /// necessary.
+    pub fn subscribe(
+        &self,
+        signal: c_int,
+        process: RcProcess,
+    ) -> io::Result<()> {
+        let mut subscribers = lock!(self.subscribers);
+
+        if !subscribers.contains_key(&signal) {
+            SIGNAL_PIPE.store(self.write as isize, Ordering::Release);
+
+            set_action(signal, handler())?;
+        }
+
+        let processes = subscribers.entry(signal).or_insert_with(Vec::new);
+
+        if !processes
+            .iter()
+            .any(|subscriber| subscriber.pid == process.pid)
+        {
+            processes.push(process);
+        }
+
+        Ok(())
+    }
+
+    /// Installs the signal handler for SIGCHLD, allowing processes waiting for
+    /// child processes to be resumed when a child terminates.
+    pub fn watch_children(&self) -> io::Result<()> {
+        if self.watches_children() {
+            return Ok(());
+        }
+
+        let subscribers = lock!(self.subscribers);
+
+        if !subscribers.contains_key(&libc::SIGCHLD) {
+            SIGNAL_PIPE.store(self.write as isize, Ordering::Release);
+
+            set_action(libc::SIGCHLD, handler())?;
+        }
+
+        self.children.store(true, Ordering::Release);
+
+        Ok(())
+    }
+
+    pub fn watches_children(&self) -> bool {
+        self.children.load(Ordering::Acquire)
+    }
+
+    /// Unsubscribes a process from a signal.
+    ///
+    /// The default action of the signal is restored if no other processes are
+    /// subscribed to it.
+    pub fn unsubscribe(&self, signal: c_int, pid: PID) -> io::Result<()> {
+        let mut subscribers = lock!(self.subscribers);
+
+        let empty = if let Some(processes) = subscribers.get_mut(&signal) {
+            processes.retain(|process| process.pid != pid);
+            processes.is_empty()
+        } else {
+            false
+        };
+
+        if empty {
+            subscribers.remove(&signal);
+
+            if !self.is_used_by_vm(signal) {
+                set_action(signal, libc::SIG_DFL)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Unsubscribes a process from all signals it is subscribed to.
+    ///
+    /// The default action is restored for every signal that no longer has any
+    /// subscribers. If this fails for a signal, the remaining signals are
+    /// still restored and the first error is returned.
+    pub fn unsubscribe_all(&self, pid: PID) -> io::Result<()> {
+        let mut subscribers = lock!(self.subscribers);
+        let mut empty = Vec::new();
+        let mut result = Ok(());
+
+        for (signal, processes) in subscribers.iter_mut() {
+            processes.retain(|process| process.pid != pid);
+
+            if processes.is_empty() {
+                empty.push(*signal);
+            }
+        }
+
+        for signal in empty {
+            subscribers.remove(&signal);
+
+            if !self.is_used_by_vm(signal) {
+                let restored = set_action(signal, libc::SIG_DFL);
+
+                if result.is_ok() {
+                    result = restored;
+                }
+            }
+        }
+
+        result
+    }
+
+    /// Sends signals to the processes subscribed to them as they arrive, until
+    /// the handler is terminated.
+    pub fn run(&self, state: &RcState) {
+        let mut byte = WAKEUP;
+
+        while self.should_run() {
+            let result = unsafe {
+                libc::read(
+                    self.read,
+                    &mut byte as *mut u8 as *mut libc::c_void,
+                    1,
+                )
+            };
+
+            if result == -1 {
+                let error = io::Error::last_os_error();
+
+                if error.kind() == io::ErrorKind::Interrupted {
+                    continue;
+                }
+
+                panic!("Failed to read signals: {}", error);
+            }
+
+            if result == 1 && byte != WAKEUP {
+                self.deliver(state, c_int::from(byte));
+            }
+        }
+    }
+
+    /// Sends a signal to all processes subscribed to it.
+    ///
+    /// If none of these processes are alive, the signal is raised again using
+    /// its default action. Signals used by the VM itself are never raised
+    /// again.
+    pub fn deliver(&self, state: &RcState, signal: c_int) {
+        let used_by_vm = self.is_used_by_vm(signal);
+
+        if used_by_vm {
+            state.suspension_list.resume_for_child(state);
+        }
+
+        let receivers = {
+            let mut subscribers = lock!(self.subscribers);
+            let receivers = subscribers.get_mut(&signal).map_or_else(
+                Vec::new,
+                |processes| {
+                    processes.retain(|process| !process.is_finished());
+                    processes.clone()
+                },
+            );
+
+            if receivers.is_empty()
+                && subscribers.remove(&signal).is_some()
+                && !used_by_vm
+            {
+                // There is no process to report an error to, and the signal
+                // thread must keep running for the other signals.
+                let _ = set_action(signal, libc::SIG_DFL);
+            }
+
+            receivers
+        };
+
+        if receivers.is_empty() {
+            if used_by_vm {
+                return;
+            }
+
+            unsafe {
+                libc::kill(libc::getpid(), signal);
+            }
+
+            return;
+        }
+
+        let message = ObjectPointer::integer(i64::from(signal));
+
+        for receiver in receivers {
+            receiver.send_message_from_vm(message);
+
+            state.suspension_list.resume_for_message(state, &receiver);
+        }
+    }
+
+    pub fn terminate(&self) {
+        self.run.store(false, Ordering::Release);
+
+        unsafe {
+            libc::write(
+                self.write,
+                &WAKEUP as *const u8 as *const libc::c_void,
+                1,
+            );
+        }
+    }
+
+    pub fn should_run(&self) -> bool {
+        self.run.load(Ordering::Acquire)
+    }
+
+    /// Returns true if the VM itself relies on the signal handler of the
+    /// given signal.
+    fn is_used_by_vm(&self, signal: c_int) -> bool {
+        signal == libc::SIGCHLD && self.watches_children()
+    }
+}
+
+impl Drop for SignalHandler {
+    fn drop(&mut self) {
+        for signal in lock!(self.subscribers).keys() {
+            let _ = set_action(*signal, libc::SIG_DFL);
+        }
+
+        if self.watches_children() {
+            let _ = set_action(libc::SIGCHLD, libc::SIG_DFL);
+        }
+
+        // Another handler may have installed its own pipe in the mean time,
+        // in which case we must leave it as-is.
+        let _ = SIGNAL_PIPE.compare_exchange(
+            self.write as isize,
+            -1,
+            Ordering::AcqRel,
+            Ordering::Acquire,
+        );
+
+        unsafe {
+            libc::close(self.read);
+            libc::close(self.write);
+        }
+    }
+}
+
+/// Writes a signal to the signal pipe.
+///
+/// This function runs in a signal handler, and thus may only use
+/// async-signal-safe functions.
+extern "C" fn handle_signal(signal: c_int) {
+    let fd = SIGNAL_PIPE.load(Ordering::Acquire);
+
+    if fd == -1 {
+        return;
+    }
+
+    let byte = signal as u8;
+
+    unsafe {
+        // write() may change errno, which would be visible to the code that
+        // was interrupted by the signal.
+        let errno = *libc::__errno_location();
+
+        libc::write(fd as RawFd, &byte as *const u8 as *const libc::c_void, 1);
+
+        *libc::__errno_location() = errno;
+    }
+}
+
+/// Returns the signal handler to install for the signals processes subscribe
+/// to.
+fn handler() -> libc::sighandler_t {
+    handle_signal as extern "C" fn(c_int) as libc::sighandler_t
+}
+
+/// Sets the action to perform when receiving a signal.
+fn set_action(signal: c_int, handler: libc::sighandler_t) -> io::Result<()> {
+    let result = unsafe {
+        let mut action: libc::sigaction = mem::zeroed();
+
+        action.sa_sigaction = handler;
+        action.sa_flags = libc::SA_RESTART;
+
+        libc::sigemptyset(&mut action.sa_mask);
+        libc::sigaction(signal, &action, ptr::null_mut())
+    };
+
+    if result == -1 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vm::test::setup;
+
+    fn subscribers(handler: &SignalHandler, signal: c_int) -> usize {
+        lock!(handler.subscribers)
+            .get(&signal)
+            .map_or(0, |processes| processes.len())
+    }
+
+    #[test]
+    fn test_signal_for_integer() {
+        assert_eq!(
+            signal_for_integer(i64::from(libc::SIGINT)),
+            Ok(libc::SIGINT)
+        );
+        assert!(signal_for_integer(i64::from(libc::SIGKILL)).is_err());
+        assert!(signal_for_integer(-1).is_err());
+    }
+
+    #[test]
+    fn test_subscribe() {
+        let (_machine, _block, process) = setup();
+        let handler = SignalHandler::new();
+
+        assert!(handler.subscribe(libc::SIGUSR1, process.clone()).is_ok());
+        assert!(handler.subscribe(libc::SIGUSR1, process.clone()).is_ok());
+
+        assert_eq!(subscribers(&handler, libc::SIGUSR1), 1);
+    }
+
+    #[test]
+    fn test_unsubscribe() {
+        let (_machine, _block, process) = setup();
+        let handler = SignalHandler::new();
+
+        handler.subscribe(libc::SIGUSR1, process.clone()).unwrap();
+
+        assert!(handler.unsubscribe(libc::SIGUSR1, process.pid).is_ok());
+        assert!(handler.unsubscribe(libc::SIGUSR1, process.pid).is_ok());
+
+        assert_eq!(subscribers(&handler, libc::SIGUSR1), 0);
+        assert!(lock!(handler.subscribers).is_empty());
+    }
+
+    #[test]
+    fn test_unsubscribe_all() {
+        let (machine, block, process) = setup();
+        let other = machine.allocate_process(0, &block).unwrap();
+        let handler = SignalHandler::new();
+
+        handler.subscribe(libc::SIGUSR1, process.clone()).unwrap();
+        handler.subscribe(libc::SIGUSR2, process.clone()).unwrap();
+        handler.subscribe(libc::SIGUSR2, other.clone()).unwrap();
+
+        assert!(handler.unsubscribe_all(process.pid).is_ok());
+
+        assert_eq!(subscribers(&handler, libc::SIGUSR1), 0);
+        assert_eq!(subscribers(&handler, libc::SIGUSR2), 1);
+        assert!(!lock!(handler.subscribers).contains_key(&libc::SIGUSR1));
+    }
+
+    #[test]
+    fn test_deliver() {
+        let (machine, _block, process) = setup();
+        let handler = SignalHandler::new();
+
+        handler.subscribe(libc::SIGUSR1, process.clone()).unwrap();
+        handler.deliver(&machine.state, libc::SIGUSR1);
+
+        let message = process.receive_message().unwrap();
+
+        assert_eq!(message.integer_value().unwrap(), i64::from(libc::SIGUSR1));
+    }
+
+    #[test]
+    fn test_watch_children() {
+        let (machine, _block, process) = setup();
+        let handler = SignalHandler::new();
+        let state = &machine.state;
+        let events = state.suspension_list.child_events();
+
+        assert!(handler.watch_children().is_ok());
+        assert!(handler.watches_children());
+
+        handler.subscribe(libc::SIGCHLD, process.clone()).unwrap();
+        handler.unsubscribe(libc::SIGCHLD, process.pid).unwrap();
+
+        // The VM uses SIGCHLD itself, so it must not be raised again.
+        handler.deliver(state, libc::SIGCHLD);
+
+        assert_eq!(state.suspension_list.child_events(), events + 1);
+    }
+
+    #[test]
+    fn test_terminate() {
+        let handler = SignalHandler::new();
+
+        handler.terminate();
+
+        assert_eq!(handler.should_run(), false);
+    }
+}
@@ -2,230 +2,955 @@
 //!
 //! A SuspensionList can be used to track processes that are suspended for a
 //! variety of reasons (e.g. because they're waiting for a message to arrive).
+//!
+//! Processes are resumed directly by whatever they are waiting for: sending a
+//! message to a process waiting for a message resumes it right away, as does
+//! receiving a message when senders are waiting for space in the mailbox.
+//! Processes waiting for an OS child process are resumed when the VM receives
+//! the SIGCHLD signal.
+//!
+//! Processes suspended with a timeout are also stored in a binary heap, ordered
+//! by the time at which their timeouts expire. A single worker thread sleeps
+//! until the first of these deadlines (or the first timer) expires, instead of
+//! periodically checking every suspended process. Entries in this heap are not
+//! removed when a process is resumed before its timeout expires, instead they
+//! are ignored once they reach the top of the heap.
 
 #![cfg_attr(feature = "cargo-clippy", allow(new_without_default_derive))]
 
-use std::cell::UnsafeCell;
-use std::collections::HashSet;
-use std::hash::{Hash, Hasher};
+use std::cmp::Ordering as CmpOrdering;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::io;
+use std::os::unix::io::RawFd;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Condvar, Mutex};
 use std::time::{Duration, Instant};
 
+use network_poller::Interest;
+use object_pointer::ObjectPointer;
 use process::RcProcess;
+use process_table::PID;
+use timer_list::TimerList;
 use vm::state::RcState;
 
+/// The minimum number of timeouts to store before removing the timeouts of
+/// processes that have already been resumed.
+const COMPACT_THRESHOLD: usize = 1024;
+
 pub struct SuspendedProcess {
     /// The process that is suspended.
     pub process: RcProcess,
 
-    /// The time at which the process was suspended.
-    pub suspended_at: Instant,
+    /// The ID of this suspension, used to determine if a timeout belongs to
+    /// the current suspension of the process.
+    pub id: u64,
 
-    /// The (optional) maximum amount of time the process should be suspended.
-    pub timeout: Option<Duration>,
+    /// The PID of the process with a full mailbox that the suspended process
+    /// is trying to send a message to.
+    pub waiting_for_space: Option<PID>,
 }
 
-pub struct SuspensionList {
-    /// The list of processes that should be suspended.
-    pub outer: Mutex<HashSet<SuspendedProcess>>,
+/// The time at which a suspended process should be resumed.
+#[derive(PartialEq, Eq)]
+pub struct Timeout {
+    pub deadline: Instant,
+    pub pid: PID,
+    pub id: u64,
+}
 
-    /// The list of processes that are currently suspended.
-    pub inner: UnsafeCell<HashSet<SuspendedProcess>>,
+impl Ord for Timeout {
+    fn cmp(&self, other: &Timeout) -> CmpOrdering {
+        // BinaryHeap is a max-heap, so the order is reversed to make the
+        // earliest deadline the first value in the heap.
+        other
+            .deadline
+            .cmp(&self.deadline)
+            .then_with(|| other.id.cmp(&self.id))
+    }
+}
 
-    /// Boolean that indicates if we should process the list.
-    pub process: AtomicBool,
+impl PartialOrd for Timeout {
+    fn partial_cmp(&self, other: &Timeout) -> Option<CmpOrdering> {
+        Some(self.cmp(other))
+    }
+}
 
-    /// A condition variable to signal whenever processes are to be
-    /// suspended.
-    pub condvar: Condvar,
+pub struct Suspended {
+    /// The suspended processes, mapped to their PIDs.
+    pub processes: HashMap<PID, SuspendedProcess>,
 
-    /// Set to true when we should wake up forcefully.
-    pub wake_up: AtomicBool,
-}
+    /// The timeouts of suspended processes, earliest deadline first.
+    pub timeouts: BinaryHeap<Timeout>,
 
-unsafe impl Sync for SuspensionList {}
-unsafe impl Send for SuspensionList {}
+    /// The PIDs of processes waiting for space in a mailbox, mapped to the PID
+    /// of the process that owns the mailbox.
+    pub waiting_for_space: HashMap<PID, Vec<PID>>,
 
-impl PartialEq for SuspendedProcess {
-    fn eq(&self, other: &SuspendedProcess) -> bool {
-        self.process == other.process
-    }
-}
+    /// The PIDs of processes waiting for an OS child process to terminate.
+    pub waiting_for_child: HashSet<PID>,
 
-impl Eq for SuspendedProcess {}
+    /// The number of times processes waiting for a child were resumed.
+    pub child_events: u64,
 
-impl Hash for SuspendedProcess {
-    fn hash<H: Hasher>(&self, state: &mut H) {
-        self.process.pid.hash(state);
-    }
+    /// The ID to use for the next suspension.
+    pub next_id: u64,
 }
 
-impl SuspendedProcess {
-    pub fn new(process: RcProcess, timeout: Option<Duration>) -> Self {
-        SuspendedProcess {
-            process,
-            suspended_at: Instant::now(),
-            timeout,
+impl Suspended {
+    pub fn new() -> Self {
+        Suspended {
+            processes: HashMap::new(),
+            timeouts: BinaryHeap::new(),
+            waiting_for_space: HashMap::new(),
+            waiting_for_child: HashSet::new(),
+            child_events: 0,
+            next_id: 0,
         }
     }
 
-    /// Returns `true` if the current entry's process should be rescheduled for
-    /// execution.
-    pub fn should_reschedule(&self) -> bool {
-        let waiting_for_message = self.process.is_waiting_for_message();
+    /// Adds a process, returning true if its timeout expires before the
+    /// timeouts of all other processes.
+    fn insert(
+        &mut self,
+        process: RcProcess,
+        timeout: Option<Duration>,
+        waiting_for_space: Option<PID>,
+    ) -> bool {
+        let id = self.next_id;
+        let pid = process.pid;
+
+        self.next_id += 1;
+
+        self.processes.insert(
+            pid,
+            SuspendedProcess {
+                process,
+                id,
+                waiting_for_space,
+            },
+        );
+
+        if let Some(receiver) = waiting_for_space {
+            self.waiting_for_space
+                .entry(receiver)
+                .or_insert_with(Vec::new)
+                .push(pid);
+        }
 
-        if waiting_for_message && self.process.has_messages() {
-            return true;
+        if let Some(duration) = timeout {
+            let deadline = Instant::now() + duration;
+
+            self.timeouts.push(Timeout { deadline, pid, id });
+
+            self.timeouts.peek().map_or(false, |first| first.id == id)
+        } else {
+            false
         }
+    }
 
-        if let Some(timeout) = self.timeout {
-            let resume_after = self.suspended_at + timeout;
+    /// Removes a process, returning it if it was suspended.
+    fn remove(&mut self, pid: PID) -> Option<RcProcess> {
+        let entry = self.processes.remove(&pid)?;
 
-            if Instant::now() >= resume_after {
-                self.process.wakeup_after_suspension_timeout();
+        if let Some(receiver) = entry.waiting_for_space {
+            let is_empty =
+                if let Some(pids) = self.waiting_for_space.get_mut(&receiver) {
+                    pids.retain(|waiting| *waiting != pid);
+                    pids.is_empty()
+                } else {
+                    false
+                };
 
-                true
-            } else {
-                false
+            if is_empty {
+                self.waiting_for_space.remove(&receiver);
+            }
+        }
+
+        self.waiting_for_child.remove(&pid);
+
+        Some(entry.process)
+    }
+
+    /// Removes the processes of which the timeouts expired at the given time.
+    fn remove_expired(&mut self, now: Instant) -> Vec<RcProcess> {
+        let mut expired = Vec::new();
+
+        loop {
+            let (pid, id) = match self.timeouts.peek() {
+                Some(timeout) if timeout.deadline <= now => {
+                    (timeout.pid, timeout.id)
+                }
+                _ => break,
+            };
+
+            self.timeouts.pop();
+
+            let current = self
+                .processes
+                .get(&pid)
+                .map_or(false, |entry| entry.id == id);
+
+            if current {
+                if let Some(process) = self.remove(pid) {
+                    expired.push(process);
+                }
             }
-        } else {
-            !waiting_for_message
         }
+
+        expired
     }
+
+    /// Removes the timeouts of processes that were resumed before their
+    /// timeouts expired, if there are enough of them.
+    fn compact(&mut self) {
+        let timeouts = self.timeouts.len();
+        let processes = self.processes.len();
+
+        if timeouts < COMPACT_THRESHOLD || timeouts < processes * 2 {
+            return;
+        }
+
+        let processes = &self.processes;
+        let current = self
+            .timeouts
+            .drain()
+            .filter(|timeout| {
+                processes
+                    .get(&timeout.pid)
+                    .map_or(false, |entry| entry.id == timeout.id)
+            }).collect::<Vec<_>>();
+
+        self.timeouts = BinaryHeap::from(current);
+    }
+
+    /// Returns the time at which the first timeout expires.
+    fn next_deadline(&self) -> Option<Instant> {
+        self.timeouts.peek().map(|timeout| timeout.deadline)
+    }
+}
+
+pub struct SuspensionList {
+    /// The processes that are currently suspended.
+    pub suspended: Mutex<Suspended>,
+
+    /// Boolean that indicates if we should process the list.
+    pub process: AtomicBool,
+
+    /// A condition variable to signal whenever the worker should wake up
+    /// before the first timeout expires.
+    pub condvar: Condvar,
+
+    /// The timers that send a message to a process once they expire.
+    pub timers: Mutex<TimerList>,
 }
 
 impl SuspensionList {
     pub fn new() -> Self {
         SuspensionList {
-            inner: UnsafeCell::new(HashSet::new()),
-            outer: Mutex::new(HashSet::new()),
+            suspended: Mutex::new(Suspended::new()),
             process: AtomicBool::new(true),
             condvar: Condvar::new(),
-            wake_up: AtomicBool::new(false),
+            timers: Mutex::new(TimerList::new()),
         }
     }
 
     /// Suspends the given process, optionally with a timeout in milliseconds.
-    pub fn suspend(&self, process: RcProcess, timeout: Option<u64>) {
-        let entry =
-            SuspendedProcess::new(process, timeout.map(Duration::from_millis));
+    ///
+    /// A process that isn't waiting for a message and doesn't have a timeout
+    /// is rescheduled right away.
+    pub fn suspend(
+        &self,
+        state: &RcState,
+        process: RcProcess,
+        timeout: Option<u64>,
+    ) {
+        if timeout.is_none() && !process.is_waiting_for_message() {
+            state.process_pools.schedule(process);
+            return;
+        }
 
-        lock!(self.outer).insert(entry);
+        let mut suspended = lock!(self.suspended);
+
+        // A message may have been sent (or termination requested) after the
+        // process last checked for one, but before we acquired the lock. In
+        // this case the sender would not have been able to resume the process.
+        if process.is_termination_requested()
+            || (process.is_waiting_for_message() && process.has_messages())
+        {
+            drop(suspended);
+            state.process_pools.schedule(process);
+            return;
+        }
 
-        self.condvar.notify_all();
+        let duration = timeout.map(Duration::from_millis);
+
+        if suspended.insert(process, duration, None) {
+            self.condvar.notify_all();
+        }
     }
 
-    /// Notifies the suspension list that it should wake up.
-    pub fn wake_up(&self) {
-        self.condvar.notify_all();
-        self.wake_up.store(true, Ordering::Relaxed);
+    /// Suspends the given process until the mailbox of the receiver has space
+    /// for another message.
+    pub fn suspend_until_space(
+        &self,
+        state: &RcState,
+        process: RcProcess,
+        receiver: &RcProcess,
+    ) {
+        let mut suspended = lock!(self.suspended);
+
+        // A process that finished won't receive any messages, so there's no
+        // point in waiting for it to do so.
+        if process.is_termination_requested()
+            || receiver.mailbox_has_space()
+            || receiver.is_finished()
+        {
+            drop(suspended);
+            state.process_pools.schedule(process);
+            return;
+        }
+
+        suspended.insert(process, None, Some(receiver.pid));
     }
 
-    pub fn reset_wake_up(&self) {
-        self.wake_up.store(false, Ordering::Relaxed);
+    /// Suspends the given process until a socket is ready for the given type
+    /// of operation.
+    pub fn suspend_for_io(
+        &self,
+        state: &RcState,
+        process: RcProcess,
+        fd: RawFd,
+        interest: Interest,
+    ) -> io::Result<()> {
+        let mut suspended = lock!(self.suspended);
+
+        if process.is_termination_requested() {
+            drop(suspended);
+            state.process_pools.schedule(process);
+            return Ok(());
+        }
+
+        // The socket is registered while holding on to the lock, preventing
+        // the poller from resuming the process before it has been suspended.
+        state.network_poller.register(fd, process.pid, interest)?;
+
+        process.waiting_for_io();
+        suspended.insert(process, None, None);
+
+        Ok(())
     }
 
-    pub fn should_wake_up(&self) -> bool {
-        self.wake_up.load(Ordering::Relaxed)
+    /// Returns the number of times processes waiting for an OS child process
+    /// were resumed.
+    ///
+    /// This value must be obtained before checking if a child terminated, and
+    /// passed to `suspend_for_child`.
+    pub fn child_events(&self) -> u64 {
+        lock!(self.suspended).child_events
+    }
+
+    /// Suspends the given process until an OS child process terminates.
+    ///
+    /// If processes waiting for a child were resumed since `events` was
+    /// obtained, the child may have terminated before we acquired the lock. In
+    /// this case the process is rescheduled right away, so it can check its
+    /// child again.
+    pub fn suspend_for_child(
+        &self,
+        state: &RcState,
+        process: RcProcess,
+        events: u64,
+    ) {
+        let mut suspended = lock!(self.suspended);
+
+        if process.is_termination_requested()
+            || suspended.child_events != events
+        {
+            drop(suspended);
+            state.process_pools.schedule(process);
+            return;
+        }
+
+        let pid = process.pid;
+
+        suspended.insert(process, None, None);
+        suspended.waiting_for_child.insert(pid);
+    }
+
+    /// Resumes all processes waiting for an OS child process, allowing them to
+    /// check if their children terminated.
+    pub fn resume_for_child(&self, state: &RcState) {
+        let resumed = {
+            let mut suspended = lock!(self.suspended);
+            let pids = suspended.waiting_for_child.drain().collect::<Vec<_>>();
+
+            suspended.child_events += 1;
+
+            pids.into_iter()
+                .filter_map(|pid| suspended.remove(pid))
+                .collect::<Vec<_>>()
+        };
+
+        for process in resumed {
+            state.process_pools.schedule(process);
+        }
+    }
+
+    /// Resumes a suspended process, regardless of the reason it was suspended
+    /// for.
+    pub fn resume(&self, state: &RcState, process: &RcProcess) {
+        let resumed = lock!(self.suspended).remove(process.pid);
+
+        if let Some(process) = resumed {
+            state.process_pools.schedule(process);
+        }
+    }
+
+    /// Resumes a process waiting for a message, if it has any messages.
+    pub fn resume_for_message(&self, state: &RcState, process: &RcProcess) {
+        let resumed = {
+            let mut suspended = lock!(self.suspended);
+
+            if !suspended.processes.contains_key(&process.pid)
+                || !process.is_waiting_for_message()
+                || !process.has_messages()
+            {
+                return;
+            }
+
+            suspended.remove(process.pid)
+        };
+
+        if let Some(process) = resumed {
+            state.process_pools.schedule(process);
+        }
+    }
+
+    /// Resumes a process waiting for a socket to become ready.
+    ///
+    /// A socket may be reported as ready after the process waiting for it was
+    /// resumed for another reason, so processes that aren't waiting for a
+    /// socket are left alone.
+    pub fn resume_for_io(&self, state: &RcState, pid: PID) {
+        let resumed = {
+            let mut suspended = lock!(self.suspended);
+            let waiting = suspended
+                .processes
+                .get(&pid)
+                .map_or(false, |entry| entry.process.is_waiting_for_io());
+
+            if !waiting {
+                return;
+            }
+
+            suspended.remove(pid)
+        };
+
+        if let Some(process) = resumed {
+            state.process_pools.schedule(process);
+        }
+    }
+
+    /// Resumes the processes waiting for space in the mailbox of the given
+    /// process.
+    pub fn resume_senders(&self, state: &RcState, receiver: &RcProcess) {
+        let resumed = {
+            let mut suspended = lock!(self.suspended);
+            let pids = suspended
+                .waiting_for_space
+                .remove(&receiver.pid)
+                .unwrap_or_else(Vec::new);
+
+            pids.into_iter()
+                .filter_map(|pid| suspended.remove(pid))
+                .collect::<Vec<_>>()
+        };
+
+        // If the mailbox fills up again, the senders that can't send their
+        // message are suspended again.
+        for process in resumed {
+            state.process_pools.schedule(process);
+        }
+    }
+
+    /// Sends a message to a process after a delay in milliseconds, returning
+    /// the ID of the timer.
+    ///
+    /// If an interval (in milliseconds) is given, the message is sent every
+    /// time the interval expires until the timer is cancelled.
+    pub fn schedule_timer(
+        &self,
+        owner: PID,
+        process: RcProcess,
+        message: ObjectPointer,
+        delay: u64,
+        interval: Option<u64>,
+    ) -> Result<u64, String> {
+        let id = lock!(self.timers).schedule(
+            owner,
+            process,
+            message,
+            Duration::from_millis(delay),
+            interval.map(Duration::from_millis),
+        )?;
+
+        // The timer may expire before any of the suspended processes, so the
+        // worker has to recalculate how long to sleep for. Acquiring the lock
+        // ensures the worker is either waiting, or has yet to determine how
+        // long to wait for.
+        let _suspended = lock!(self.suspended);
+
+        self.condvar.notify_all();
+
+        Ok(id)
+    }
+
+    /// Cancels a timer scheduled by the given process, returning true if the
+    /// timer had not yet expired.
+    pub fn cancel_timer(&self, id: u64, owner: PID) -> bool {
+        lock!(self.timers).cancel(id, owner)
     }
 
     pub fn terminate(&self) {
-        self.wake_up();
-        self.process.store(false, Ordering::Relaxed);
+        self.process.store(false, Ordering::Release);
+
+        let _suspended = lock!(self.suspended);
+
+        self.condvar.notify_all();
     }
 
     pub fn should_process(&self) -> bool {
-        self.process.load(Ordering::Relaxed)
+        self.process.load(Ordering::Acquire)
     }
 
+    /// Resumes processes and fires timers as their timeouts expire, until the
+    /// list is terminated.
     pub fn process_suspended_processes(&self, state: &RcState) {
+        let mut suspended = lock!(self.suspended);
+
         while self.should_process() {
-            self.wait_for_work(state);
-            self.copy_outer();
-            self.process_inner(state);
+            let now = Instant::now();
+
+            for process in suspended.remove_expired(now) {
+                process.wakeup_after_suspension_timeout();
+                state.process_pools.schedule(process);
+            }
+
+            suspended.compact();
+
+            // Timers are fired without holding on to the list of suspended
+            // processes, as resuming the receivers requires acquiring it.
+            drop(suspended);
+            self.fire_timers(state, now);
+            suspended = lock!(self.suspended);
+
+            if !self.should_process() {
+                break;
+            }
+
+            let deadline = self.next_deadline(&suspended);
+
+            suspended = if let Some(deadline) = deadline {
+                let now = Instant::now();
+
+                if deadline <= now {
+                    continue;
+                }
+
+                self.condvar
+                    .wait_timeout(suspended, deadline - now)
+                    .unwrap()
+                    .0
+            } else {
+                self.condvar.wait(suspended).unwrap()
+            };
         }
     }
 
-    #[cfg_attr(feature = "cargo-clippy", allow(mut_from_ref))]
-    fn inner_mut(&self) -> &mut HashSet<SuspendedProcess> {
-        unsafe { &mut *self.inner.get() }
-    }
+    /// Sends the messages of all expired timers.
+    fn fire_timers(&self, state: &RcState, now: Instant) {
+        let expired = lock!(self.timers).expired(now);
 
-    fn inner(&self) -> &HashSet<SuspendedProcess> {
-        unsafe { &*self.inner.get() }
+        for timer in expired {
+            if timer.process.fire_timer(timer.id, timer.periodic) {
+                self.resume_for_message(state, &timer.process);
+            }
+        }
     }
 
-    fn copy_outer(&self) {
-        let inner = self.inner_mut();
-        let mut outer = lock!(self.outer);
+    /// Returns the time at which the first timeout or timer expires.
+    fn next_deadline(&self, suspended: &Suspended) -> Option<Instant> {
+        let process_deadline = suspended.next_deadline();
+        let timer_deadline = lock!(self.timers).next_deadline();
 
-        for entry in outer.drain() {
-            inner.insert(entry);
+        match (process_deadline, timer_deadline) {
+            (Some(process), Some(timer)) => Some(process.min(timer)),
+            (process, timer) => process.or(timer),
         }
     }
+}
 
-    fn process_inner(&self, state: &RcState) {
-        let inner = self.inner_mut();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use config::Config;
+    use mailbox::OverflowPolicy;
+    use std::net::TcpListener;
+    use std::os::unix::io::AsRawFd;
+    use vm::state::State;
+    use vm::test::setup;
+
+    fn state() -> RcState {
+        State::new(Config::new())
+    }
 
-        if !inner.is_empty() {
-            inner.retain(|entry| {
-                if entry.should_reschedule() {
-                    state.process_pools.schedule(entry.process.clone());
-                    false
-                } else {
-                    true
-                }
-            });
-        }
+    fn is_suspended(list: &SuspensionList, process: &RcProcess) -> bool {
+        lock!(list.suspended).processes.contains_key(&process.pid)
+    }
+
+    #[test]
+    fn test_timeout_order() {
+        let now = Instant::now();
+        let mut heap = BinaryHeap::new();
+
+        heap.push(Timeout {
+            deadline: now + Duration::from_secs(2),
+            pid: 1,
+            id: 0,
+        });
+
+        heap.push(Timeout {
+            deadline: now + Duration::from_secs(1),
+            pid: 2,
+            id: 1,
+        });
+
+        heap.push(Timeout {
+            deadline: now + Duration::from_secs(3),
+            pid: 3,
+            id: 2,
+        });
+
+        assert_eq!(heap.pop().unwrap().pid, 2);
+        assert_eq!(heap.pop().unwrap().pid, 1);
+        assert_eq!(heap.pop().unwrap().pid, 3);
     }
 
-    /// Blocks the current thread until either a process times out or new
-    /// processes are scheduled for suspension.
-    fn wait_for_work(&self, state: &RcState) {
-        let sleep_for = self.time_to_sleep(state);
-        let mut outer = lock!(self.outer);
+    #[test]
+    fn test_suspend_without_timeout() {
+        let state = state();
+        let list = SuspensionList::new();
+        let (_machine, _block, process) = setup();
 
-        while outer.is_empty() {
-            let result = self.condvar.wait_timeout(outer, sleep_for).unwrap();
+        process.running();
+        list.suspend(&state, process.clone(), None);
 
-            if result.1.timed_out() {
-                return;
-            } else if self.should_wake_up() {
-                self.reset_wake_up();
-                return;
-            } else {
-                outer = result.0;
-            }
-        }
+        assert!(!is_suspended(&list, &process));
+        assert!(process.available_for_execution());
     }
 
-    /// Determine the amount of time we need to sleep before checking the list
-    /// of processes again.
-    fn time_to_sleep(&self, state: &RcState) -> Duration {
-        let inner = self.inner();
-        let mut time = None;
+    #[test]
+    fn test_suspend_waiting_for_message() {
+        let state = state();
+        let list = SuspensionList::new();
+        let (_machine, _block, process) = setup();
 
-        // If there are processes with a timeout we want to make sure we resume
-        // those as soon as possible after these timeouts expire.
-        for entry in inner.iter() {
-            if let Some(timeout) = entry.timeout {
-                let overwrite = if let Some(current) = time {
-                    timeout < current
-                } else {
-                    true
-                };
+        process.waiting_for_message();
+        list.suspend(&state, process.clone(), None);
 
-                if overwrite {
-                    time = Some(timeout);
-                }
-            }
+        assert!(is_suspended(&list, &process));
+        assert!(lock!(list.suspended).timeouts.is_empty());
+    }
+
+    #[test]
+    fn test_suspend_waiting_for_message_with_messages() {
+        let state = state();
+        let list = SuspensionList::new();
+        let (_machine, _block, process) = setup();
+
+        process.send_message(&process, ObjectPointer::integer(1));
+        process.waiting_for_message();
+        list.suspend(&state, process.clone(), Some(1000));
+
+        assert!(!is_suspended(&list, &process));
+        assert!(process.available_for_execution());
+    }
+
+    #[test]
+    fn test_suspend_with_timeout() {
+        let state = state();
+        let list = SuspensionList::new();
+        let (_machine, _block, process) = setup();
+
+        process.running();
+        list.suspend(&state, process.clone(), Some(1000));
+
+        assert!(is_suspended(&list, &process));
+        assert_eq!(lock!(list.suspended).timeouts.len(), 1);
+    }
+
+    #[test]
+    fn test_resume() {
+        let state = state();
+        let list = SuspensionList::new();
+        let (_machine, _block, process) = setup();
+
+        process.running();
+        list.suspend(&state, process.clone(), Some(1000));
+        list.resume(&state, &process);
+
+        assert!(!is_suspended(&list, &process));
+        assert!(process.available_for_execution());
+    }
+
+    #[test]
+    fn test_resume_for_message() {
+        let state = state();
+        let list = SuspensionList::new();
+        let (_machine, _block, process) = setup();
+
+        process.waiting_for_message();
+        list.suspend(&state, process.clone(), None);
+        list.resume_for_message(&state, &process);
+
+        assert!(is_suspended(&list, &process));
+
+        process.send_message(&process, ObjectPointer::integer(1));
+        list.resume_for_message(&state, &process);
+
+        assert!(!is_suspended(&list, &process));
+        assert!(process.available_for_execution());
+    }
+
+    #[test]
+    fn test_resume_for_message_without_waiting_for_message() {
+        let state = state();
+        let list = SuspensionList::new();
+        let (_machine, _block, process) = setup();
+
+        process.running();
+        list.suspend(&state, process.clone(), Some(1000));
+        process.send_message(&process, ObjectPointer::integer(1));
+        list.resume_for_message(&state, &process);
+
+        assert!(is_suspended(&list, &process));
+    }
+
+    #[test]
+    fn test_suspend_for_io() {
+        let state = state();
+        let list = SuspensionList::new();
+        let (_machine, _block, process) = setup();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+
+        list.suspend_for_io(
+            &state,
+            process.clone(),
+            listener.as_raw_fd(),
+            Interest::Read,
+        ).unwrap();
+
+        assert!(is_suspended(&list, &process));
+        assert!(process.is_waiting_for_io());
+    }
+
+    #[test]
+    fn test_suspend_for_io_with_invalid_descriptor() {
+        let state = state();
+        let list = SuspensionList::new();
+        let (_machine, _block, process) = setup();
+
+        process.running();
+
+        let result =
+            list.suspend_for_io(&state, process.clone(), -1, Interest::Read);
+
+        assert!(result.is_err());
+        assert!(!is_suspended(&list, &process));
+        assert_eq!(process.is_waiting_for_io(), false);
+    }
+
+    #[test]
+    fn test_resume_for_io() {
+        let state = state();
+        let list = SuspensionList::new();
+        let (_machine, _block, process) = setup();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+
+        list.suspend_for_io(
+            &state,
+            process.clone(),
+            listener.as_raw_fd(),
+            Interest::Read,
+        ).unwrap();
+
+        list.resume_for_io(&state, process.pid);
+
+        assert!(!is_suspended(&list, &process));
+        assert!(process.available_for_execution());
+    }
+
+    #[test]
+    fn test_resume_for_io_without_waiting_for_io() {
+        let state = state();
+        let list = SuspensionList::new();
+        let (_machine, _block, process) = setup();
+
+        process.waiting_for_message();
+        list.suspend(&state, process.clone(), None);
+        list.resume_for_io(&state, process.pid);
+
+        assert!(is_suspended(&list, &process));
+    }
+
+    #[test]
+    fn test_suspend_until_space() {
+        let state = state();
+        let list = SuspensionList::new();
+        let (machine, block, sender) = setup();
+        let receiver = machine.allocate_process(0, &block).unwrap();
+
+        receiver.set_mailbox_capacity(1, OverflowPolicy::Suspend);
+        receiver.send_message(&sender, ObjectPointer::integer(1));
+
+        sender.running();
+        list.suspend_until_space(&state, sender.clone(), &receiver);
+
+        assert!(is_suspended(&list, &sender));
+
+        receiver.receive_message();
+        list.resume_senders(&state, &receiver);
+
+        assert!(!is_suspended(&list, &sender));
+        assert!(lock!(list.suspended).waiting_for_space.is_empty());
+        assert!(sender.available_for_execution());
+    }
+
+    #[test]
+    fn test_suspend_until_space_with_space() {
+        let state = state();
+        let list = SuspensionList::new();
+        let (machine, block, sender) = setup();
+        let receiver = machine.allocate_process(0, &block).unwrap();
+
+        sender.running();
+        list.suspend_until_space(&state, sender.clone(), &receiver);
+
+        assert!(!is_suspended(&list, &sender));
+        assert!(sender.available_for_execution());
+    }
+
+    #[test]
+    fn test_suspend_for_child() {
+        let state = state();
+        let list = SuspensionList::new();
+        let (_machine, _block, process) = setup();
+        let events = list.child_events();
+
+        process.running();
+        list.suspend_for_child(&state, process.clone(), events);
+
+        assert!(is_suspended(&list, &process));
+
+        list.resume_for_child(&state);
+
+        assert!(!is_suspended(&list, &process));
+        assert!(lock!(list.suspended).waiting_for_child.is_empty());
+        assert_eq!(list.child_events(), events + 1);
+    }
+
+    #[test]
+    fn test_suspend_for_child_after_child_event() {
+        let state = state();
+        let list = SuspensionList::new();
+        let (_machine, _block, process) = setup();
+        let events = list.child_events();
+
+        list.resume_for_child(&state);
+
+        process.running();
+        list.suspend_for_child(&state, process.clone(), events);
+
+        assert!(!is_suspended(&list, &process));
+        assert!(process.available_for_execution());
+    }
+
+    #[test]
+    fn test_remove_expired() {
+        let mut suspended = Suspended::new();
+        let (machine, block, process1) = setup();
+        let process2 = machine.allocate_process(0, &block).unwrap();
+        let now = Instant::now();
+
+        suspended.insert(process1.clone(), Some(Duration::from_secs(0)), None);
+        suspended.insert(process2.clone(), Some(Duration::from_secs(60)), None);
+
+        let expired = suspended.remove_expired(now + Duration::from_secs(1));
+
+        assert_eq!(expired.len(), 1);
+        assert!(expired[0] == process1);
+        assert!(suspended.processes.contains_key(&process2.pid));
+        assert_eq!(suspended.timeouts.len(), 1);
+    }
+
+    #[test]
+    fn test_remove_expired_after_resuming() {
+        let mut suspended = Suspended::new();
+        let (_machine, _block, process) = setup();
+        let now = Instant::now();
+
+        // The first timeout belongs to a suspension that ended, and should be
+        // ignored.
+        suspended.insert(process.clone(), Some(Duration::from_secs(0)), None);
+        suspended.remove(process.pid);
+        suspended.insert(process.clone(), Some(Duration::from_secs(60)), None);
+
+        let expired = suspended.remove_expired(now + Duration::from_secs(1));
+
+        assert!(expired.is_empty());
+        assert!(suspended.processes.contains_key(&process.pid));
+    }
+
+    #[test]
+    fn test_compact() {
+        let mut suspended = Suspended::new();
+        let (_machine, _block, process) = setup();
+        let timeout = Some(Duration::from_secs(60));
+
+        for _ in 0..COMPACT_THRESHOLD {
+            suspended.insert(process.clone(), timeout, None);
         }
 
-        if let Some(duration) = time {
-            duration
-        } else {
-            Duration::from_millis(state.config.suspension_check_interval)
+        suspended.compact();
+
+        assert_eq!(suspended.timeouts.len(), 1);
+    }
+
+    #[test]
+    fn test_process_suspended_processes() {
+        let state = state();
+        let (_machine, _block, process) = setup();
+
+        process.waiting_for_message();
+
+        state
+            .suspension_list
+            .suspend(&state, process.clone(), Some(5));
+
+        let worker_state = state.clone();
+        let worker = ::std::thread::spawn(move || {
+            worker_state
+                .suspension_list
+                .process_suspended_processes(&worker_state)
+        });
+
+        while !process.available_for_execution() {
+            ::std::thread::sleep(Duration::from_millis(1));
         }
+
+        state.suspension_list.terminate();
+        worker.join().unwrap();
+
+        assert!(!is_suspended(&state.suspension_list, &process));
     }
 }
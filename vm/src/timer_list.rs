@@ -0,0 +1,332 @@
+//! Timers that send a message to a process once they expire.
+//!
+//! A TimerList is used to send a message to a process after a given amount of
+//! time, either once or periodically. The messages of timers are stored in the
+//! mailbox of the receiving process, and the TimerList only keeps track of
+//! when to deliver these messages.
+//!
+//! Timers are processed by the same thread that processes suspended processes,
+//! so timers don't require a process (or thread) that waits for them to expire.
+
+#![cfg_attr(feature = "cargo-clippy", allow(new_without_default_derive))]
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use object_pointer::ObjectPointer;
+use process::RcProcess;
+use process_table::PID;
+
+pub struct Timer {
+    /// The PID of the process that scheduled the timer. Only this process is
+    /// allowed to cancel the timer.
+    pub owner: PID,
+
+    /// The process to send the message to.
+    pub process: RcProcess,
+
+    /// The time at which the timer expires.
+    pub deadline: Instant,
+
+    /// The interval of a periodic timer.
+    pub interval: Option<Duration>,
+}
+
+/// A timer that expired, and the process its message should be sent to.
+pub struct ExpiredTimer {
+    pub id: u64,
+    pub process: RcProcess,
+    pub periodic: bool,
+}
+
+pub struct TimerList {
+    /// The ID to use for the next timer.
+    next_id: u64,
+
+    /// The pending timers, mapped to their IDs.
+    timers: HashMap<u64, Timer>,
+}
+
+impl TimerList {
+    pub fn new() -> Self {
+        TimerList {
+            next_id: 0,
+            timers: HashMap::new(),
+        }
+    }
+
+    /// Schedules a message to be sent to a process, returning the ID of the
+    /// timer.
+    ///
+    /// If an interval is given, the message is sent every time the interval
+    /// expires, until the timer is cancelled. An error is returned if the
+    /// message can't be copied into the mailbox of the process.
+    pub fn schedule(
+        &mut self,
+        owner: PID,
+        process: RcProcess,
+        message: ObjectPointer,
+        delay: Duration,
+        interval: Option<Duration>,
+    ) -> Result<u64, String> {
+        let id = self.next_id;
+
+        self.next_id += 1;
+
+        // The message is stored before the timer is added, ensuring it is
+        // available once the timer expires.
+        process.add_timer(id, message)?;
+
+        self.timers.insert(
+            id,
+            Timer {
+                owner,
+                process,
+                deadline: Instant::now() + delay,
+                interval,
+            },
+        );
+
+        Ok(id)
+    }
+
+    /// Cancels a timer, returning true if the timer had not yet expired.
+    ///
+    /// Timers can only be cancelled by the process that scheduled them, so
+    /// false is returned if the timer belongs to another process.
+    pub fn cancel(&mut self, id: u64, owner: PID) -> bool {
+        let owned = self
+            .timers
+            .get(&id)
+            .map_or(false, |timer| timer.owner == owner);
+
+        if !owned {
+            return false;
+        }
+
+        if let Some(timer) = self.timers.remove(&id) {
+            timer.process.cancel_timer(id)
+        } else {
+            false
+        }
+    }
+
+    /// Returns the timers that expired at the given time.
+    ///
+    /// One-shot timers are removed, while periodic timers are scheduled to
+    /// expire again after their interval. Timers of processes that finished
+    /// are removed without being returned.
+    pub fn expired(&mut self, now: Instant) -> Vec<ExpiredTimer> {
+        let mut expired = Vec::new();
+
+        self.timers.retain(|id, timer| {
+            if timer.process.is_finished() {
+                return false;
+            }
+
+            if timer.deadline > now {
+                return true;
+            }
+
+            expired.push(ExpiredTimer {
+                id: *id,
+                process: timer.process.clone(),
+                periodic: timer.interval.is_some(),
+            });
+
+            if let Some(interval) = timer.interval {
+                timer.deadline += interval;
+
+                // If we fell behind we don't want to deliver a burst of
+                // messages to catch up.
+                if timer.deadline <= now {
+                    timer.deadline = now + interval;
+                }
+
+                true
+            } else {
+                false
+            }
+        });
+
+        expired
+    }
+
+    /// Returns the time at which the first timer expires.
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.timers.values().map(|timer| timer.deadline).min()
+    }
+
+    pub fn len(&self) -> usize {
+        self.timers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.timers.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vm::test::setup;
+
+    #[test]
+    fn test_schedule() {
+        let (_machine, _block, process) = setup();
+        let mut timers = TimerList::new();
+
+        let id1 = timers
+            .schedule(
+                process.pid,
+                process.clone(),
+                ObjectPointer::integer(1),
+                Duration::from_millis(10),
+                None,
+            )
+            .unwrap();
+
+        let id2 = timers
+            .schedule(
+                process.pid,
+                process.clone(),
+                ObjectPointer::integer(2),
+                Duration::from_millis(10),
+                None,
+            )
+            .unwrap();
+
+        assert!(id1 != id2);
+        assert_eq!(timers.len(), 2);
+        assert_eq!(process.local_data().mailbox.timers.len(), 2);
+    }
+
+    #[test]
+    fn test_cancel() {
+        let (_machine, _block, process) = setup();
+        let mut timers = TimerList::new();
+        let id = timers
+            .schedule(
+                process.pid,
+                process.clone(),
+                ObjectPointer::integer(1),
+                Duration::from_millis(10),
+                None,
+            )
+            .unwrap();
+
+        assert!(timers.cancel(id, process.pid));
+        assert!(!timers.cancel(id, process.pid));
+        assert!(timers.is_empty());
+        assert!(process.local_data().mailbox.timers.is_empty());
+    }
+
+    #[test]
+    fn test_cancel_from_another_process() {
+        let (_machine, _block, process) = setup();
+        let mut timers = TimerList::new();
+        let id = timers
+            .schedule(
+                process.pid,
+                process.clone(),
+                ObjectPointer::integer(1),
+                Duration::from_millis(10),
+                None,
+            )
+            .unwrap();
+
+        assert!(!timers.cancel(id, process.pid + 1));
+        assert_eq!(timers.len(), 1);
+        assert_eq!(process.local_data().mailbox.timers.len(), 1);
+    }
+
+    #[test]
+    fn test_expired() {
+        let (_machine, _block, process) = setup();
+        let mut timers = TimerList::new();
+        let now = Instant::now();
+
+        timers
+            .schedule(
+                process.pid,
+                process.clone(),
+                ObjectPointer::integer(1),
+                Duration::from_millis(0),
+                None,
+            )
+            .unwrap();
+
+        timers
+            .schedule(
+                process.pid,
+                process.clone(),
+                ObjectPointer::integer(2),
+                Duration::from_secs(60),
+                None,
+            )
+            .unwrap();
+
+        let expired = timers.expired(now + Duration::from_millis(10));
+
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].periodic, false);
+        assert_eq!(timers.len(), 1);
+    }
+
+    #[test]
+    fn test_expired_periodic() {
+        let (_machine, _block, process) = setup();
+        let mut timers = TimerList::new();
+        let now = Instant::now();
+
+        timers
+            .schedule(
+                process.pid,
+                process.clone(),
+                ObjectPointer::integer(1),
+                Duration::from_millis(0),
+                Some(Duration::from_secs(60)),
+            )
+            .unwrap();
+
+        let expired = timers.expired(now + Duration::from_millis(10));
+
+        assert_eq!(expired.len(), 1);
+        assert!(expired[0].periodic);
+        assert_eq!(timers.len(), 1);
+        assert!(timers.next_deadline().unwrap() > now);
+        assert!(timers.expired(now + Duration::from_millis(20)).is_empty());
+    }
+
+    #[test]
+    fn test_next_deadline() {
+        let (_machine, _block, process) = setup();
+        let mut timers = TimerList::new();
+
+        assert!(timers.next_deadline().is_none());
+
+        timers
+            .schedule(
+                process.pid,
+                process.clone(),
+                ObjectPointer::integer(1),
+                Duration::from_secs(60),
+                None,
+            )
+            .unwrap();
+
+        timers
+            .schedule(
+                process.pid,
+                process.clone(),
+                ObjectPointer::integer(2),
+                Duration::from_secs(10),
+                None,
+            )
+            .unwrap();
+
+        let deadline = timers.next_deadline().unwrap();
+
+        assert!(deadline < Instant::now() + Duration::from_secs(11));
+    }
+}
@@ -1,9 +1,27 @@
 //! Structures for encoding virtual machine instructions.
+use std::convert::TryFrom;
 
-/// Enum containing all possible instruction types.
-#[derive(Debug, PartialEq, Eq)]
-#[repr(u8)]
-pub enum InstructionType {
+/// Defines the InstructionType enum, along with a table of all instruction
+/// types ordered by their numeric values.
+///
+/// Both are generated from the same list so that decoding an instruction type
+/// from a byte can never get out of sync with the enum itself.
+macro_rules! instruction_types {
+    ($($name: ident),+) => {
+        /// Enum containing all possible instruction types.
+        #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+        #[repr(u8)]
+        pub enum InstructionType {
+            $($name),+
+        }
+
+        /// All instruction types, indexed by their numeric values.
+        pub const INSTRUCTION_TYPES: &[InstructionType] =
+            &[$(InstructionType::$name),+];
+    };
+}
+
+instruction_types!(
     SetLiteral,
     SetObject,
     SetArray,
@@ -143,6 +161,71 @@ pub enum InstructionType {
     ByteArrayEquals,
     ByteArrayToString,
     GetBooleanPrototype,
+    EnvGet,
+    EnvSet,
+    EnvRemove,
+    EnvVariables,
+    EnvArguments,
+    EnvGetWorkingDirectory,
+    EnvSetWorkingDirectory,
+    ProcessMonitor,
+    ProcessDemonitor,
+    ProcessLink,
+    ProcessUnlink,
+    ProcessTrapExits,
+    ProcessTerminate,
+    ProcessRegister,
+    ProcessUnregister,
+    ProcessWhereis,
+    ProcessRegisteredNames,
+    ProcessReceiveMessageIf,
+    ProcessSetMailboxCapacity,
+    ProcessMailboxLength,
+    ReferenceNew,
+    ReferenceEquals,
+    TimerSchedule,
+    TimerCancel,
+    TcpListen,
+    TcpConnect,
+    SocketAccept,
+    SocketFinishConnect,
+    SocketRead,
+    SocketWrite,
+    SocketShutdown,
+    SocketLocalAddress,
+    SocketPeerAddress,
+    UdpBind,
+    UnixListen,
+    UnixConnect,
+    UnixDatagramBind,
+    UnixPair,
+    SocketConnect,
+    SocketSendTo,
+    SocketReceiveFrom,
+    SocketSetOption,
+    SocketGetOption,
+    SocketJoinMulticast,
+    SocketLeaveMulticast,
+    ChildSpawn,
+    ChildWrite,
+    ChildRead,
+    ChildCloseInput,
+    ChildWait,
+    ChildExitCode,
+    ChildExitSignal,
+    ChildKill,
+    SignalSubscribe,
+    SignalUnsubscribe
+);
+
+impl TryFrom<u8> for InstructionType {
+    type Error = u8;
+
+    /// Decodes an instruction type from a byte, returning the byte as an error
+    /// if it does not map to a known instruction type.
+    fn try_from(byte: u8) -> Result<Self, Self::Error> {
+        INSTRUCTION_TYPES.get(byte as usize).cloned().ok_or(byte)
+    }
 }
 
 /// Struct for storing information about a single instruction.
@@ -190,6 +273,7 @@ impl Instruction {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::u8;
 
     fn new_instruction() -> Instruction {
         Instruction::new(InstructionType::SetLiteral, vec![1, 2], 3)
@@ -227,6 +311,24 @@ mod tests {
         assert_eq!(ins.arg_opt(0).unwrap(), 1);
     }
 
+    #[test]
+    fn test_instruction_type_try_from_valid() {
+        for (index, ins_type) in INSTRUCTION_TYPES.iter().enumerate() {
+            let byte = *ins_type as u8;
+
+            assert_eq!(byte as usize, index);
+            assert_eq!(InstructionType::try_from(byte), Ok(*ins_type));
+        }
+    }
+
+    #[test]
+    fn test_instruction_type_try_from_invalid() {
+        let byte = INSTRUCTION_TYPES.len() as u8;
+
+        assert_eq!(InstructionType::try_from(byte), Err(byte));
+        assert_eq!(InstructionType::try_from(u8::MAX), Err(u8::MAX));
+    }
+
     #[test]
     fn test_boolean() {
         let ins = new_instruction();
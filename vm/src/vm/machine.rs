@@ -4,19 +4,24 @@ extern crate num_traits;
 use float_cmp::ApproxEqUlps;
 use rayon::ThreadPoolBuilder;
 use num_bigint::BigInt;
+use std::env;
 use std::f64;
 use std::fs;
 use std::i32;
 use std::i64;
 use std::io::{self, Seek, SeekFrom, Write};
 use std::ops::{Add, Mul, Sub};
+use std::os::unix::io::AsRawFd;
+use std::process::Command;
 use std::thread;
 
 use binding::Binding;
 use block::Block;
 use byte_array;
+use child_process;
 use compiled_code::CompiledCodePointer;
 use date_time::DateTime;
+use environment;
 use execution_context::ExecutionContext;
 use filesystem;
 use gc::request::Request as GcRequest;
@@ -24,15 +29,20 @@ use hasher::Hasher;
 use immix::copy_object::CopyObject;
 use integer_operations;
 use io::{read_from_stream, ReadResult};
+use mailbox::{OverflowPolicy, SendResult};
 use module_registry::{ModuleRegistry, RcModuleRegistry};
+use net;
+use network_poller::Interest;
 use numeric::division::{FlooredDiv, OverflowingFlooredDiv};
 use numeric::modulo::{Modulo, OverflowingModulo};
 use object_pointer::ObjectPointer;
 use object_value;
 use pool::{JoinGuard as PoolJoinGuard, STACK_SIZE};
 use pools::{PRIMARY_POOL, SECONDARY_POOL};
-use process::{Process, ProcessStatus, RcProcess};
+use process::{ExitReason, Process, ProcessStatus, RcProcess};
+use process_table::PID;
 use runtime_panic;
+use signal_handler;
 use slicing;
 use stacktrace;
 use vm::file_open_mode;
@@ -102,6 +112,39 @@ macro_rules! throw_io_error {
     }};
 }
 
+macro_rules! wait_for_io {
+    (
+        $machine:expr,
+        $process:expr,
+        $io:expr,
+        $interest:expr,
+        $context:ident,
+        $code:ident,
+        $index:ident
+    ) => {{
+        // The instruction is retried once the descriptor is ready. This must be
+        // done before suspending the process, as the process may be resumed
+        // before we return.
+        $context.instruction_index = $index - 1;
+
+        let result = $machine.state.suspension_list.suspend_for_io(
+            &$machine.state,
+            $process.clone(),
+            $io.as_raw_fd(),
+            $interest,
+        );
+
+        match result {
+            Ok(_) => return Ok(()),
+            Err(err) => {
+                throw_io_error!(
+                    $machine, $process, err, $context, $code, $index
+                )
+            }
+        }
+    }};
+}
+
 macro_rules! enter_context {
     ($process:expr, $context:ident, $code:ident, $index:ident) => {{
         $context.instruction_index = $index;
@@ -121,6 +164,10 @@ macro_rules! set_nil_if_immutable {
 
 macro_rules! safepoint_and_reduce {
     ($vm:expr, $process:expr, $reductions:expr) => {{
+        if $vm.terminate_if_requested(&$process) {
+            return Ok(());
+        }
+
         if $vm.gc_safepoint(&$process) {
             return Ok(());
         }
@@ -229,6 +276,8 @@ impl Machine {
         let finalizer_pool_guard = self.start_finalizer_threads();
         let secondary_guard = self.start_secondary_threads();
         let suspend_guard = self.start_suspension_worker();
+        let poller_guard = self.start_network_poller();
+        let signal_guard = self.start_signal_handler();
 
         self.start_main_process(file);
 
@@ -239,6 +288,8 @@ impl Machine {
             || gc_pool_guard.join().is_err()
             || finalizer_pool_guard.join().is_err()
             || suspend_guard.join().is_err()
+            || poller_guard.join().is_err()
+            || signal_guard.join().is_err()
         {
             self.state.set_exit_status(1);
         }
@@ -281,6 +332,30 @@ impl Machine {
             .unwrap()
     }
 
+    fn start_network_poller(&self) -> thread::JoinHandle<()> {
+        let state = self.state.clone();
+
+        let builder = thread::Builder::new()
+            .stack_size(STACK_SIZE)
+            .name("network poller".to_string());
+
+        builder
+            .spawn(move || state.network_poller.poll(&state))
+            .unwrap()
+    }
+
+    fn start_signal_handler(&self) -> thread::JoinHandle<()> {
+        let state = self.state.clone();
+
+        let builder = thread::Builder::new()
+            .stack_size(STACK_SIZE)
+            .name("signal handler".to_string());
+
+        builder
+            .spawn(move || state.signal_handler.run(&state))
+            .unwrap()
+    }
+
     /// Starts the garbage collection threads.
     fn start_gc_threads(&self) -> PoolJoinGuard<()> {
         self.state.gc_pool.run(move |mut request| request.perform())
@@ -297,6 +372,8 @@ impl Machine {
         self.state.gc_pool.terminate();
         self.state.finalizer_pool.terminate();
         self.state.suspension_list.terminate();
+        self.state.network_poller.terminate();
+        self.state.signal_handler.terminate();
     }
 
     /// Starts the main process
@@ -304,10 +381,17 @@ impl Machine {
         let process = {
             let mut registry = write_lock!(self.module_registry);
 
-            let module = registry
-                .parse_module(file)
-                .map_err(|err| err.message())
-                .unwrap();
+            let module = match registry.parse_module(file) {
+                Ok(module) => module,
+                Err(error) => {
+                    eprintln!("{}", error.message());
+
+                    self.state.set_exit_status(1);
+                    self.terminate();
+
+                    return;
+                }
+            };
 
             let code = module.code();
             let block = Block::new(
@@ -357,6 +441,10 @@ impl Machine {
     /// Executes a single process.
     #[cfg_attr(feature = "cargo-clippy", allow(cyclomatic_complexity))]
     pub fn run(&self, process: &RcProcess) -> Result<(), String> {
+        if self.terminate_if_requested(process) {
+            return Ok(());
+        }
+
         let mut reductions = self.state.config.reductions;
 
         process.running();
@@ -1950,22 +2038,77 @@ impl Machine {
                 //
                 // 1. The register to store the message in.
                 // 2. The register containing the PID to send the message
-                //    to.
+                //    to, or the name the process is registered under.
                 // 3. The register containing the message (an object) to
                 //    send to the process.
+                //
+                // If the mailbox of the receiver is full, the overflow policy
+                // of the receiver determines what happens: the current process
+                // may be suspended (retrying this instruction once space is
+                // available), the message may be discarded, or an error may be
+                // thrown.
+                //
+                // An error is also thrown if the message can't be copied to
+                // the receiver, such as when it contains a child process.
                 InstructionType::ProcessSendMessage => {
                     let register = instruction.arg(0);
                     let pid_ptr = context.get_register(instruction.arg(1));
                     let msg_ptr = context.get_register(instruction.arg(2));
-                    let pid = pid_ptr.usize_value()?;
+                    let receiver =
+                        if let Some(pid) = self.resolve_pid(pid_ptr)? {
+                            read_lock!(self.state.process_table).get(pid)
+                        } else {
+                            None
+                        };
 
-                    if let Some(receiver) =
-                        read_lock!(self.state.process_table).get(pid)
-                    {
-                        receiver.send_message(&process, msg_ptr);
+                    if let Some(receiver) = receiver {
+                        let sent = receiver.try_send_message(&process, msg_ptr);
+                        let result = match sent {
+                            Ok(result) => result,
+                            Err(error) => {
+                                throw_error_message!(
+                                    self, process, error, context, code, index
+                                );
+
+                                continue;
+                            }
+                        };
+
+                        match result {
+                            SendResult::Sent => {
+                                if receiver.is_waiting_for_message() {
+                                    self.state
+                                        .suspension_list
+                                        .resume_for_message(
+                                            &self.state,
+                                            &receiver,
+                                        );
+                                }
+                            }
+                            SendResult::Dropped => {}
+                            SendResult::Suspend => {
+                                context.instruction_index = index - 1;
+
+                                self.state.suspension_list.suspend_until_space(
+                                    &self.state,
+                                    process.clone(),
+                                    &receiver,
+                                );
+
+                                return Ok(());
+                            }
+                            SendResult::Full => {
+                                let msg = format!(
+                                    "The mailbox of process {} is full",
+                                    receiver.pid
+                                );
 
-                        if receiver.is_waiting_for_message() {
-                            self.state.suspension_list.wake_up();
+                                throw_error_message!(
+                                    self, process, msg, context, code, index
+                                );
+
+                                continue;
+                            }
                         }
                     }
 
@@ -1989,7 +2132,7 @@ impl Machine {
                 InstructionType::ProcessReceiveMessage => {
                     let register = instruction.arg(0);
 
-                    if let Some(msg_ptr) = process.receive_message() {
+                    if let Some(msg_ptr) = self.receive_message(process) {
                         context.set_register(register, msg_ptr);
                     } else {
                         let time_ptr = context.get_register(instruction.arg(1));
@@ -2007,9 +2150,11 @@ impl Machine {
 
                         process.waiting_for_message();
 
-                        self.state
-                            .suspension_list
-                            .suspend(process.clone(), timeout);
+                        self.state.suspension_list.suspend(
+                            &self.state,
+                            process.clone(),
+                            timeout,
+                        );
 
                         return Ok(());
                     }
@@ -2062,9 +2207,11 @@ impl Machine {
 
                     context.instruction_index = index;
 
-                    self.state
-                        .suspension_list
-                        .suspend(process.clone(), timeout);
+                    self.state.suspension_list.suspend(
+                        &self.state,
+                        process.clone(),
+                        timeout,
+                    );
 
                     return Ok(());
                 }
@@ -3439,97 +3586,2281 @@ impl Machine {
                         self.state.boolean_prototype,
                     );
                 }
-            };
-        }
-
-        process.finished();
+                // Gets the value of an environment variable.
+                //
+                // This instruction requires two arguments:
+                //
+                // 1. The register to store the value in, as a String. This
+                //    register is set to nil if the variable is not set, or if
+                //    the name is not a valid variable name.
+                // 2. The register containing the name of the variable.
+                InstructionType::EnvGet => {
+                    let register = instruction.arg(0);
+                    let name_ptr = context.get_register(instruction.arg(1));
+                    let name = name_ptr.string_value()?;
 
-        write_lock!(self.state.process_table).release(process.pid);
+                    let value = if let Some(value) = environment::get(name) {
+                        process.allocate(
+                            object_value::string(value),
+                            self.state.string_prototype,
+                        )
+                    } else {
+                        self.state.nil_object
+                    };
 
-        // We must clean up _after_ removing the process from the process table
-        // to prevent a cleanup from happening while the process is still
-        // receiving messages as this could lead to memory not being reclaimed.
-        self.schedule_gc_for_finished_process(&process);
+                    context.set_register(register, value);
+                }
+                // Sets the value of an environment variable.
+                //
+                // This instruction requires three arguments:
+                //
+                // 1. The register to store the new value in.
+                // 2. The register containing the name of the variable.
+                // 3. The register containing the new value, as a String.
+                //
+                // This instruction will throw an error if the name is empty or
+                // contains "=" or a NUL byte, or if the value contains a NUL
+                // byte.
+                //
+                // The C library reads the environment without synchronisation,
+                // for example when spawning a child process. Changing the
+                // environment while other processes are running is therefore
+                // not safe.
+                InstructionType::EnvSet => {
+                    let register = instruction.arg(0);
+                    let name_ptr = context.get_register(instruction.arg(1));
+                    let value_ptr = context.get_register(instruction.arg(2));
+                    let name = name_ptr.string_value()?;
+                    let value = value_ptr.string_value()?;
 
-        // Terminate once the main process has finished execution.
-        if process.is_main() {
-            self.terminate();
-        }
+                    if let Err(error) = environment::set(name, value) {
+                        throw_error_message!(
+                            self, process, error, context, code, index
+                        );
+                    } else {
+                        context.set_register(register, value_ptr);
+                    }
+                }
+                // Removes an environment variable.
+                //
+                // This instruction requires two arguments:
+                //
+                // 1. The register to store the result in, which is always
+                //    `nil`.
+                // 2. The register containing the name of the variable.
+                //
+                // This instruction will throw an error if the name is empty or
+                // contains "=" or a NUL byte. Like EnvSet, it is not safe to
+                // use while other processes are running.
+                InstructionType::EnvRemove => {
+                    let register = instruction.arg(0);
+                    let name_ptr = context.get_register(instruction.arg(1));
+                    let name = name_ptr.string_value()?;
 
-        Ok(())
-    }
+                    if let Err(error) = environment::remove(name) {
+                        throw_error_message!(
+                            self, process, error, context, code, index
+                        );
+                    } else {
+                        context.set_register(register, self.state.nil_object);
+                    }
+                }
+                // Returns the names of all environment variables.
+                //
+                // This instruction requires one argument: the register to store
+                // the names in, as an Array of Strings.
+                InstructionType::EnvVariables => {
+                    let register = instruction.arg(0);
+                    let names = env::vars_os()
+                        .map(|(name, _)| {
+                            process.allocate(
+                                object_value::string(
+                                    name.to_string_lossy().to_string(),
+                                ),
+                                self.state.string_prototype,
+                            )
+                        })
+                        .collect();
 
-    /// Collects a set of arguments from an instruction.
-    pub fn collect_arguments(
-        &self,
-        process: &RcProcess,
-        instruction: &Instruction,
-        offset: usize,
-        amount: usize,
-    ) -> Vec<ObjectPointer> {
-        let mut args: Vec<ObjectPointer> = Vec::with_capacity(amount);
+                    let array = process.allocate(
+                        object_value::array(names),
+                        self.state.array_prototype,
+                    );
 
-        for index in offset..(offset + amount) {
-            let arg_index = instruction.arg(index);
+                    context.set_register(register, array);
+                }
+                // Returns the command-line arguments passed to the program.
+                //
+                // This instruction requires one argument: the register to store
+                // the arguments in, as an Array of Strings.
+                InstructionType::EnvArguments => {
+                    let register = instruction.arg(0);
+                    let array = process.allocate(
+                        object_value::array(self.state.arguments.clone()),
+                        self.state.array_prototype,
+                    );
 
-            args.push(process.get_register(arg_index));
-        }
+                    context.set_register(register, array);
+                }
+                // Returns the current working directory.
+                //
+                // This instruction requires one argument: the register to store
+                // the directory in, as a String.
+                //
+                // This instruction may throw an IO error.
+                InstructionType::EnvGetWorkingDirectory => {
+                    let register = instruction.arg(0);
 
-        args
-    }
+                    match env::current_dir() {
+                        Ok(path) => {
+                            let pointer = process.allocate(
+                                object_value::string(
+                                    path.to_string_lossy().to_string(),
+                                ),
+                                self.state.string_prototype,
+                            );
 
-    /// Checks if a garbage collection run should be scheduled for the given
-    /// process.
-    ///
-    /// Returns true if a process should be suspended for garbage collection.
-    fn gc_safepoint(&self, process: &RcProcess) -> bool {
-        if process.should_collect_young_generation() {
-            self.schedule_gc_request(GcRequest::heap(
-                self.state.clone(),
-                process.clone(),
-            ));
+                            context.set_register(register, pointer);
+                        }
+                        Err(error) => {
+                            throw_io_error!(
+                                self, process, error, context, code, index
+                            );
+                        }
+                    }
+                }
+                // Changes the current working directory.
+                //
+                // This instruction requires two arguments:
+                //
+                // 1. The register to store the new directory in.
+                // 2. The register containing the new directory, as a String.
+                //
+                // This instruction may throw an IO error.
+                InstructionType::EnvSetWorkingDirectory => {
+                    let register = instruction.arg(0);
+                    let path_ptr = context.get_register(instruction.arg(1));
+                    let path = path_ptr.string_value()?;
 
-            true
-        } else if process.should_collect_mailbox() {
-            self.schedule_gc_request(GcRequest::mailbox(
-                self.state.clone(),
-                process.clone(),
-            ));
+                    if let Err(error) = env::set_current_dir(path) {
+                        throw_io_error!(
+                            self, process, error, context, code, index
+                        );
+                    } else {
+                        context.set_register(register, path_ptr);
+                    }
+                }
+                // Monitors a process.
+                //
+                // This instruction requires two arguments:
+                //
+                // 1. The register to store the result in. This register is set
+                //    to true if the process is monitored, or false if it does
+                //    not exist.
+                // 2. The register containing the PID of the process to
+                //    monitor.
+                //
+                // When the monitored process finishes, an exit message is sent
+                // to the current process.
+                InstructionType::ProcessMonitor => {
+                    let register = instruction.arg(0);
+                    let pid_ptr = context.get_register(instruction.arg(1));
+                    let pid = pid_ptr.usize_value()?;
 
-            true
-        } else {
-            false
-        }
-    }
+                    let monitored = read_lock!(self.state.process_table)
+                        .get(pid)
+                        .map_or(false, |target| {
+                            target.add_monitor(process.pid)
+                        });
 
-    /// Reschedules a process.
-    fn reschedule(&self, process: RcProcess) {
-        self.state.process_pools.schedule(process);
-    }
+                    context.set_register(
+                        register,
+                        boolean_to_pointer!(self, monitored),
+                    );
+                }
+                // Stops monitoring a process.
+                //
+                // This instruction requires two arguments:
+                //
+                // 1. The register to store the result in, which is always
+                //    `nil`.
+                // 2. The register containing the PID of the process to stop
+                //    monitoring.
+                InstructionType::ProcessDemonitor => {
+                    let register = instruction.arg(0);
+                    let pid_ptr = context.get_register(instruction.arg(1));
+                    let pid = pid_ptr.usize_value()?;
 
-    fn schedule_gc_request(&self, request: GcRequest) {
-        request.process.suspend_for_gc();
-        self.state.gc_pool.schedule(request);
-    }
+                    if let Some(target) =
+                        read_lock!(self.state.process_table).get(pid)
+                    {
+                        target.remove_monitor(process.pid);
+                    }
 
-    fn schedule_gc_for_finished_process(&self, process: &RcProcess) {
-        let request = GcRequest::finished(self.state.clone(), process.clone());
-        self.state.gc_pool.schedule(request);
-    }
+                    context.set_register(register, self.state.nil_object);
+                }
+                // Links the current process with another process.
+                //
+                // This instruction requires two arguments:
+                //
+                // 1. The register to store the result in. This register is set
+                //    to true if the processes are linked, or false if the
+                //    process does not exist.
+                // 2. The register containing the PID of the process to link
+                //    with.
+                //
+                // When either process fails, the other process is terminated
+                // as well unless it traps exits.
+                InstructionType::ProcessLink => {
+                    let register = instruction.arg(0);
+                    let pid_ptr = context.get_register(instruction.arg(1));
+                    let pid = pid_ptr.usize_value()?;
 
-    #[inline(always)]
-    fn validate_number_of_arguments(
-        &self,
-        code: CompiledCodePointer,
-        given_positional: usize,
-        given_keyword: usize,
-    ) -> Result<(), String> {
-        let arguments = given_positional + given_keyword;
+                    let linked = if pid == process.pid {
+                        false
+                    } else {
+                        read_lock!(self.state.process_table).get(pid).map_or(
+                            false,
+                            |target| {
+                                target.add_link(process.pid)
+                                    && process.add_link(pid)
+                            },
+                        )
+                    };
 
-        if !code.valid_number_of_arguments(arguments) {
-            return Err(format!(
-                "{} takes {} arguments but {} were supplied",
-                code.name.string_value().unwrap(),
+                    context.set_register(
+                        register,
+                        boolean_to_pointer!(self, linked),
+                    );
+                }
+                // Removes the link between the current process and another
+                // process.
+                //
+                // This instruction requires two arguments:
+                //
+                // 1. The register to store the result in, which is always
+                //    `nil`.
+                // 2. The register containing the PID of the linked process.
+                InstructionType::ProcessUnlink => {
+                    let register = instruction.arg(0);
+                    let pid_ptr = context.get_register(instruction.arg(1));
+                    let pid = pid_ptr.usize_value()?;
+
+                    process.remove_link(pid);
+
+                    if let Some(target) =
+                        read_lock!(self.state.process_table).get(pid)
+                    {
+                        target.remove_link(process.pid);
+                    }
+
+                    context.set_register(register, self.state.nil_object);
+                }
+                // Sets whether the current process traps exits of linked
+                // processes.
+                //
+                // This instruction requires two arguments:
+                //
+                // 1. The register to store the result in, which is the
+                //    boolean that was set.
+                // 2. The register containing a boolean. When set to true, the
+                //    failure of a linked process results in an exit message
+                //    instead of the current process being terminated.
+                InstructionType::ProcessTrapExits => {
+                    let register = instruction.arg(0);
+                    let value_ptr = context.get_register(instruction.arg(1));
+
+                    process.set_trap_exits(!is_false!(self, value_ptr));
+
+                    context.set_register(register, value_ptr);
+                }
+                // Terminates a process.
+                //
+                // This instruction requires three arguments:
+                //
+                // 1. The register to store the result in. This register is set
+                //    to true if the termination was requested, or false if the
+                //    process does not exist.
+                // 2. The register containing the PID of the process to
+                //    terminate.
+                // 3. The register containing the reason for terminating the
+                //    process, as a String.
+                //
+                // The process is terminated asynchronously: a running process
+                // terminates at its next safepoint, while a suspended process
+                // is terminated right away.
+                InstructionType::ProcessTerminate => {
+                    let register = instruction.arg(0);
+                    let pid_ptr = context.get_register(instruction.arg(1));
+                    let reason_ptr = context.get_register(instruction.arg(2));
+                    let pid = pid_ptr.usize_value()?;
+                    let message = reason_ptr.string_value()?.clone();
+                    let reason = ExitReason::Terminated(message);
+
+                    if pid == process.pid {
+                        process.request_termination(reason);
+
+                        break 'exec_loop;
+                    }
+
+                    let target = read_lock!(self.state.process_table).get(pid);
+                    let terminated = if let Some(target) = target {
+                        target.request_termination(reason);
+
+                        self.state.suspension_list.resume(&self.state, &target);
+
+                        true
+                    } else {
+                        false
+                    };
+
+                    context.set_register(
+                        register,
+                        boolean_to_pointer!(self, terminated),
+                    );
+                }
+                // Registers a process under a name.
+                //
+                // This instruction requires three arguments:
+                //
+                // 1. The register to store the result in. This register is set
+                //    to true if the process was registered, or false if the
+                //    name is already in use, the process already has a name, or
+                //    the process does not exist.
+                // 2. The register containing the name, as a String.
+                // 3. The register containing the PID of the process.
+                //
+                // The name is removed automatically when the process finishes.
+                InstructionType::ProcessRegister => {
+                    let register = instruction.arg(0);
+                    let name_ptr = context.get_register(instruction.arg(1));
+                    let pid_ptr = context.get_register(instruction.arg(2));
+                    let name = self.state.intern_pointer(name_ptr)?;
+                    let pid = pid_ptr.usize_value()?;
+
+                    // The process table is locked while registering the name,
+                    // preventing the process from finishing before its name is
+                    // registered.
+                    let table = read_lock!(self.state.process_table);
+
+                    let registered = table.get(pid).is_some()
+                        && write_lock!(self.state.process_registry)
+                            .register(name, pid);
+
+                    context.set_register(
+                        register,
+                        boolean_to_pointer!(self, registered),
+                    );
+                }
+                // Removes a registered name.
+                //
+                // This instruction requires two arguments:
+                //
+                // 1. The register to store the result in. This register is set
+                //    to true if the name was registered, false otherwise.
+                // 2. The register containing the name to remove, as a String.
+                InstructionType::ProcessUnregister => {
+                    let register = instruction.arg(0);
+                    let name_ptr = context.get_register(instruction.arg(1));
+                    let name = self.state.intern_pointer(name_ptr)?;
+                    let removed = write_lock!(self.state.process_registry)
+                        .unregister(&name)
+                        .is_some();
+
+                    context.set_register(
+                        register,
+                        boolean_to_pointer!(self, removed),
+                    );
+                }
+                // Looks up the PID of a registered process.
+                //
+                // This instruction requires two arguments:
+                //
+                // 1. The register to store the PID in. This register is set to
+                //    nil if no process is registered under the name.
+                // 2. The register containing the name, as a String.
+                InstructionType::ProcessWhereis => {
+                    let register = instruction.arg(0);
+                    let name_ptr = context.get_register(instruction.arg(1));
+                    let name = self.state.intern_pointer(name_ptr)?;
+                    let pid =
+                        read_lock!(self.state.process_registry).whereis(&name);
+
+                    let pid_ptr = if let Some(pid) = pid {
+                        process
+                            .allocate_usize(pid, self.state.integer_prototype)
+                    } else {
+                        self.state.nil_object
+                    };
+
+                    context.set_register(register, pid_ptr);
+                }
+                // Returns the names of all registered processes.
+                //
+                // This instruction requires one argument: the register to store
+                // the names in, as an Array of Strings.
+                InstructionType::ProcessRegisteredNames => {
+                    let register = instruction.arg(0);
+                    let names = read_lock!(self.state.process_registry).names();
+                    let array = process.allocate(
+                        object_value::array(names),
+                        self.state.array_prototype,
+                    );
+
+                    context.set_register(register, array);
+                }
+                // Receives the first message that matches a condition.
+                //
+                // This instruction takes three arguments:
+                //
+                // 1. The register to store the received message in.
+                // 2. The register containing the Block to use as the
+                //    condition. This Block is called with a single argument:
+                //    the message to test.
+                // 3. A timeout after which the process will resume, even if no
+                //    matching message is received. If the register is set to
+                //    nil or the value is negative the timeout is ignored.
+                //
+                // Messages that don't match the condition are left in the
+                // mailbox, in the order they were received.
+                //
+                // To test a message, the condition is executed in a new context
+                // that stores its result in the target register. Once it
+                // returns, this instruction is retried to process the result.
+                //
+                // If no matching message is available the current process will
+                // be suspended until a new message arrives or the timeout
+                // expires. If the timeout expires the given register will be
+                // set to nil.
+                InstructionType::ProcessReceiveMessageIf => {
+                    let register = instruction.arg(0);
+                    let block_ptr = context.get_register(instruction.arg(1));
+                    let block = block_ptr.block_value()?;
+
+                    self.validate_number_of_arguments(block.code, 1, 0)?;
+
+                    if let Some(message) = process.take_receive_candidate() {
+                        if is_false!(self, context.get_register(register)) {
+                            process.skip_message(message);
+                        } else {
+                            process.accept_message();
+                            process.finish_selective_receive();
+                            self.resume_senders(process);
+                            context.set_register(register, message);
+
+                            continue;
+                        }
+                    }
+
+                    process.start_selective_receive(context as *const _);
+
+                    if let Some(message) = process.receive_candidate() {
+                        let mut new_ctx = ExecutionContext::from_block(
+                            &block,
+                            Some(register),
+                        );
+
+                        new_ctx.set_local(0, message);
+                        process.push_context(new_ctx);
+
+                        // Once the condition returns we want to retry this
+                        // instruction so we can process its result.
+                        index -= 1;
+
+                        enter_context!(process, context, code, index);
+
+                        continue;
+                    }
+
+                    let time_ptr = context.get_register(instruction.arg(2));
+                    let timeout = process
+                        .selective_receive_timeout(optional_timeout!(time_ptr));
+
+                    // If the timeout expires we won't retry this instruction so
+                    // we need to ensure the register is already set.
+                    context.set_register(register, self.state.nil_object);
+
+                    if timeout == Some(0) {
+                        process.finish_selective_receive();
+
+                        continue;
+                    }
+
+                    // When resuming (except when the timeout expires) we want
+                    // to retry this instruction so we can test the new
+                    // messages.
+                    context.instruction_index = index - 1;
+
+                    process.waiting_for_message();
+
+                    self.state.suspension_list.suspend(
+                        &self.state,
+                        process.clone(),
+                        timeout,
+                    );
+
+                    return Ok(());
+                }
+                // Limits the number of messages the mailbox of a process can
+                // hold.
+                //
+                // This instruction requires four arguments:
+                //
+                // 1. The register to store the result in. This register is set
+                //    to true if the process exists, false otherwise.
+                // 2. The register containing the PID of the process.
+                // 3. The register containing the capacity, as an Integer. A
+                //    capacity of 0 (or nil) removes the limit.
+                // 4. The register containing the overflow policy to apply when
+                //    the mailbox is full, as an Integer:
+                //
+                //    * 0: suspend the sender until space is available
+                //    * 1: discard the message that is sent
+                //    * 2: discard the oldest message in the mailbox
+                //    * 3: throw an error in the sender
+                InstructionType::ProcessSetMailboxCapacity => {
+                    let register = instruction.arg(0);
+                    let pid_ptr = context.get_register(instruction.arg(1));
+                    let cap_ptr = context.get_register(instruction.arg(2));
+                    let policy_ptr = context.get_register(instruction.arg(3));
+                    let pid = pid_ptr.usize_value()?;
+
+                    let capacity = if cap_ptr == self.state.nil_object {
+                        0
+                    } else {
+                        cap_ptr.usize_value()?
+                    };
+
+                    let policy = policy_ptr.integer_value()?;
+                    let overflow = OverflowPolicy::from_integer(policy)
+                        .ok_or_else(|| {
+                            format!(
+                                "Invalid mailbox overflow policy: {}",
+                                policy
+                            )
+                        })?;
+
+                    let found = if let Some(target) =
+                        read_lock!(self.state.process_table).get(pid)
+                    {
+                        target.set_mailbox_capacity(capacity, overflow);
+
+                        // Increasing the capacity may allow suspended senders
+                        // to resume.
+                        self.state
+                            .suspension_list
+                            .resume_senders(&self.state, &target);
+
+                        true
+                    } else {
+                        false
+                    };
+
+                    context.set_register(
+                        register,
+                        boolean_to_pointer!(self, found),
+                    );
+                }
+                // Returns the number of messages in the mailbox of a process.
+                //
+                // This instruction requires two arguments:
+                //
+                // 1. The register to store the number of messages in.
+                // 2. The register containing the PID of the process. If the
+                //    process does not exist, the number of messages is 0.
+                InstructionType::ProcessMailboxLength => {
+                    let register = instruction.arg(0);
+                    let pid_ptr = context.get_register(instruction.arg(1));
+                    let pid = pid_ptr.usize_value()?;
+
+                    let length = read_lock!(self.state.process_table)
+                        .get(pid)
+                        .map_or(0, |target| target.mailbox_length());
+
+                    let length_ptr = process
+                        .allocate_usize(length, self.state.integer_prototype);
+
+                    context.set_register(register, length_ptr);
+                }
+                // Creates a new unique reference.
+                //
+                // This instruction only requires one argument: the register to
+                // store the reference in.
+                InstructionType::ReferenceNew => {
+                    let register = instruction.arg(0);
+                    let reference = self.state.new_reference();
+                    let pointer = process.allocate(
+                        object_value::reference(reference),
+                        self.state.object_prototype,
+                    );
+
+                    context.set_register(register, pointer);
+                }
+                // Checks two references for equality.
+                //
+                // This instruction requires three arguments:
+                //
+                // 1. The register to store the result in as a boolean.
+                // 2. The register containing the reference to compare.
+                // 3. The register containing the object to compare with.
+                //
+                // Comparing a reference with an object that is not a reference
+                // produces false, making it easier to find a reply in a mailbox
+                // containing other messages.
+                InstructionType::ReferenceEquals => {
+                    let register = instruction.arg(0);
+                    let compare_ptr = context.get_register(instruction.arg(1));
+                    let compare_with_ptr =
+                        context.get_register(instruction.arg(2));
+
+                    let reference = compare_ptr.reference_value()?;
+                    let result = compare_with_ptr
+                        .reference_value()
+                        .map(|other| reference == other)
+                        .unwrap_or(false);
+
+                    context.set_register(
+                        register,
+                        boolean_to_pointer!(self, result),
+                    );
+                }
+                // Schedules a message to be sent to a process after a delay.
+                //
+                // This instruction requires five arguments:
+                //
+                // 1. The register to store the ID of the timer in, as an
+                //    Integer. If the process does not exist, this register is
+                //    set to nil.
+                // 2. The register containing the PID to send the message to,
+                //    or the name the process is registered under.
+                // 3. The register containing the message to send.
+                // 4. The register containing the delay in milliseconds.
+                // 5. The register containing the interval in milliseconds, or
+                //    nil. When an interval is given, the message is sent every
+                //    time the interval expires until the timer is cancelled.
+                //
+                // The message is copied when the timer is scheduled, and sent
+                // by the VM without occupying a process. An error is thrown if
+                // the message can't be copied.
+                InstructionType::TimerSchedule => {
+                    let register = instruction.arg(0);
+                    let pid_ptr = context.get_register(instruction.arg(1));
+                    let msg_ptr = context.get_register(instruction.arg(2));
+                    let delay_ptr = context.get_register(instruction.arg(3));
+                    let interval_ptr = context.get_register(instruction.arg(4));
+
+                    let delay = optional_timeout!(delay_ptr).unwrap_or(0);
+                    let interval = optional_timeout!(interval_ptr);
+
+                    let receiver =
+                        if let Some(pid) = self.resolve_pid(pid_ptr)? {
+                            read_lock!(self.state.process_table).get(pid)
+                        } else {
+                            None
+                        };
+
+                    let result = if let Some(receiver) = receiver {
+                        let scheduled =
+                            self.state.suspension_list.schedule_timer(
+                                process.pid,
+                                receiver,
+                                msg_ptr,
+                                delay,
+                                interval,
+                            );
+
+                        match scheduled {
+                            Ok(id) => process
+                                .allocate_u64(id, self.state.integer_prototype),
+                            Err(error) => {
+                                throw_error_message!(
+                                    self, process, error, context, code, index
+                                );
+
+                                continue;
+                            }
+                        }
+                    } else {
+                        self.state.nil_object
+                    };
+
+                    context.set_register(register, result);
+                }
+                // Cancels a timer.
+                //
+                // This instruction requires two arguments:
+                //
+                // 1. The register to store the result in. This register is set
+                //    to true if the timer was cancelled, and false if it
+                //    already expired or did not exist.
+                // 2. The register containing the ID of the timer, or nil.
+                //
+                // Only the process that scheduled a timer can cancel it. For
+                // any other process this instruction sets the register to
+                // false.
+                InstructionType::TimerCancel => {
+                    let register = instruction.arg(0);
+                    let id_ptr = context.get_register(instruction.arg(1));
+
+                    let cancelled = if id_ptr == self.state.nil_object {
+                        false
+                    } else {
+                        let id = id_ptr.usize_value()? as u64;
+
+                        self.state.suspension_list.cancel_timer(id, process.pid)
+                    };
+
+                    context.set_register(
+                        register,
+                        boolean_to_pointer!(self, cancelled),
+                    );
+                }
+                // Binds a TCP socket to an address, and starts listening for
+                // incoming connections.
+                //
+                // This instruction requires two arguments:
+                //
+                // 1. The register to store the listener in.
+                // 2. The register containing the address to bind to, such as
+                //    "127.0.0.1:8080". Using port 0 binds the listener to a
+                //    random port.
+                //
+                // This instruction will throw when encountering an IO error.
+                InstructionType::TcpListen => {
+                    let register = instruction.arg(0);
+                    let addr_ptr = context.get_register(instruction.arg(1));
+
+                    match net::tcp_listen(addr_ptr.string_value()?) {
+                        Ok(listener) => {
+                            let obj = process.allocate(
+                                object_value::tcp_listener(listener),
+                                self.state.object_prototype,
+                            );
+
+                            context.set_register(register, obj);
+                        }
+                        Err(err) => {
+                            throw_io_error!(
+                                self, process, err, context, code, index
+                            );
+                        }
+                    }
+                }
+                // Starts connecting a TCP socket to an address.
+                //
+                // This instruction requires two arguments:
+                //
+                // 1. The register to store the stream in.
+                // 2. The register containing the address to connect to, such
+                //    as "127.0.0.1:8080".
+                //
+                // The connection is likely still in progress when this
+                // instruction finishes, SocketFinishConnect can be used to wait
+                // for it to be established.
+                //
+                // This instruction will throw when encountering an IO error.
+                InstructionType::TcpConnect => {
+                    let register = instruction.arg(0);
+                    let addr_ptr = context.get_register(instruction.arg(1));
+
+                    match net::tcp_connect(addr_ptr.string_value()?) {
+                        Ok(stream) => {
+                            let obj = process.allocate(
+                                object_value::tcp_stream(stream),
+                                self.state.object_prototype,
+                            );
+
+                            context.set_register(register, obj);
+                        }
+                        Err(err) => {
+                            throw_io_error!(
+                                self, process, err, context, code, index
+                            );
+                        }
+                    }
+                }
+                // Accepts a new connection from a TCP or Unix listener.
+                //
+                // This instruction requires two arguments:
+                //
+                // 1. The register to store the accepted stream in.
+                // 2. The register containing the listener.
+                //
+                // If no connections are pending, the current process is
+                // suspended until a connection arrives.
+                //
+                // This instruction will throw when encountering an IO error.
+                InstructionType::SocketAccept => {
+                    let register = instruction.arg(0);
+                    let socket_ptr = context.get_register(instruction.arg(1));
+                    let socket = socket_ptr.socket_value()?;
+
+                    match socket.accept() {
+                        Ok(stream) => {
+                            let obj = process
+                                .allocate(stream, self.state.object_prototype);
+
+                            context.set_register(register, obj);
+                        }
+                        Err(ref err)
+                            if err.kind() == io::ErrorKind::WouldBlock =>
+                        {
+                            wait_for_io!(
+                                self,
+                                process,
+                                socket,
+                                Interest::Read,
+                                context,
+                                code,
+                                index
+                            );
+                        }
+                        Err(err) => {
+                            throw_io_error!(
+                                self, process, err, context, code, index
+                            );
+                        }
+                    }
+                }
+                // Waits for a connection started using TcpConnect or
+                // UnixConnect to be established.
+                //
+                // This instruction requires two arguments:
+                //
+                // 1. The register to store the result (nil) in.
+                // 2. The register containing the stream.
+                //
+                // The current process is suspended until the connection has
+                // been established, or until establishing it failed.
+                //
+                // This instruction will throw when encountering an IO error,
+                // such as when the connection is refused.
+                InstructionType::SocketFinishConnect => {
+                    let register = instruction.arg(0);
+                    let socket_ptr = context.get_register(instruction.arg(1));
+                    let socket = socket_ptr.socket_value()?;
+
+                    match socket.is_connected() {
+                        Ok(true) => {
+                            context
+                                .set_register(register, self.state.nil_object);
+                        }
+                        Ok(false) => {
+                            wait_for_io!(
+                                self,
+                                process,
+                                socket,
+                                Interest::Write,
+                                context,
+                                code,
+                                index
+                            );
+                        }
+                        Err(err) => {
+                            throw_io_error!(
+                                self, process, err, context, code, index
+                            );
+                        }
+                    }
+                }
+                // Reads data from a socket into an array of bytes.
+                //
+                // This instruction requires four arguments:
+                //
+                // 1. The register to store the number of read bytes in.
+                // 2. The register containing the socket to read from.
+                // 3. The register containing the byte array to read the data
+                //    into.
+                // 4. The register containing the maximum number of bytes to
+                //    read. If set to nil, all data that is currently available
+                //    is read. For datagram sockets a single datagram is read.
+                //
+                // If no data is available, the current process is suspended
+                // until data arrives. Once the end of a stream is reached, the
+                // number of read bytes is 0.
+                //
+                // This instruction will throw when encountering an IO error.
+                InstructionType::SocketRead => {
+                    let register = instruction.arg(0);
+                    let socket_ptr = context.get_register(instruction.arg(1));
+                    let buff_ptr = context.get_register(instruction.arg(2));
+                    let size_ptr = context.get_register(instruction.arg(3));
+
+                    let socket = socket_ptr.socket_value()?;
+                    let buffer = buff_ptr.byte_array_value_mut()?;
+                    let size = self.read_size(size_ptr)?;
+
+                    match socket.read(buffer, size) {
+                        Ok(amount) => {
+                            let amount_ptr = process.allocate_usize(
+                                amount,
+                                self.state.integer_prototype,
+                            );
+
+                            context.set_register(register, amount_ptr);
+                        }
+                        Err(ref err)
+                            if err.kind() == io::ErrorKind::WouldBlock =>
+                        {
+                            wait_for_io!(
+                                self,
+                                process,
+                                socket,
+                                Interest::Read,
+                                context,
+                                code,
+                                index
+                            );
+                        }
+                        Err(err) => {
+                            throw_io_error!(
+                                self, process, err, context, code, index
+                            );
+                        }
+                    }
+                }
+                // Writes a string or byte array to a socket.
+                //
+                // This instruction requires three arguments:
+                //
+                // 1. The register to store the number of written bytes in.
+                // 2. The register containing the socket to write to.
+                // 3. The register containing the string or byte array to write.
+                //
+                // Not all data may be written at once. If no data can be
+                // written, the current process is suspended until the socket is
+                // ready for writing. Datagram sockets must be connected using
+                // SocketConnect before they can be written to.
+                //
+                // This instruction will throw when encountering an IO error.
+                InstructionType::SocketWrite => {
+                    let register = instruction.arg(0);
+                    let socket_ptr = context.get_register(instruction.arg(1));
+                    let value_ptr = context.get_register(instruction.arg(2));
+
+                    let socket = socket_ptr.socket_value()?;
+
+                    match write_bytes_or_string!(socket, value_ptr) {
+                        Ok(amount) => {
+                            let amount_ptr = process.allocate_usize(
+                                amount,
+                                self.state.integer_prototype,
+                            );
+
+                            context.set_register(register, amount_ptr);
+                        }
+                        Err(ref err)
+                            if err.kind() == io::ErrorKind::WouldBlock =>
+                        {
+                            wait_for_io!(
+                                self,
+                                process,
+                                socket,
+                                Interest::Write,
+                                context,
+                                code,
+                                index
+                            );
+                        }
+                        Err(err) => {
+                            throw_io_error!(
+                                self, process, err, context, code, index
+                            );
+                        }
+                    }
+                }
+                // Shuts down the reading and/or writing half of a socket.
+                //
+                // This instruction requires three arguments:
+                //
+                // 1. The register to store the result (nil) in.
+                // 2. The register containing the socket to shut down.
+                // 3. The register containing an integer that specifies what to
+                //    shut down.
+                //
+                // The available shutdown modes are as follows:
+                //
+                // * 0: reading
+                // * 1: writing
+                // * 2: reading and writing
+                //
+                // This instruction will throw when encountering an IO error.
+                InstructionType::SocketShutdown => {
+                    let register = instruction.arg(0);
+                    let socket_ptr = context.get_register(instruction.arg(1));
+                    let how_ptr = context.get_register(instruction.arg(2));
+
+                    let socket = socket_ptr.socket_value()?;
+                    let how =
+                        net::shutdown_for_integer(how_ptr.integer_value()?)?;
+
+                    if let Err(err) = socket.shutdown(how) {
+                        throw_io_error!(
+                            self, process, err, context, code, index
+                        );
+                    } else {
+                        context.set_register(register, self.state.nil_object);
+                    }
+                }
+                // Returns the local address of a socket as a string.
+                //
+                // This instruction requires two arguments:
+                //
+                // 1. The register to store the address in.
+                // 2. The register containing the socket.
+                //
+                // The address of a Unix socket is the path it is bound to, or
+                // an empty string if the socket is not bound to a path.
+                //
+                // This instruction will throw when encountering an IO error.
+                InstructionType::SocketLocalAddress => {
+                    let register = instruction.arg(0);
+                    let socket_ptr = context.get_register(instruction.arg(1));
+
+                    match socket_ptr.socket_value()?.local_address() {
+                        Ok(address) => {
+                            let obj = process.allocate(
+                                object_value::string(address),
+                                self.state.string_prototype,
+                            );
+
+                            context.set_register(register, obj);
+                        }
+                        Err(err) => {
+                            throw_io_error!(
+                                self, process, err, context, code, index
+                            );
+                        }
+                    }
+                }
+                // Returns the address of the remote end of a socket as a
+                // string.
+                //
+                // This instruction requires two arguments:
+                //
+                // 1. The register to store the address in.
+                // 2. The register containing the socket.
+                //
+                // This instruction will throw when encountering an IO error.
+                InstructionType::SocketPeerAddress => {
+                    let register = instruction.arg(0);
+                    let socket_ptr = context.get_register(instruction.arg(1));
+
+                    match socket_ptr.socket_value()?.peer_address() {
+                        Ok(address) => {
+                            let obj = process.allocate(
+                                object_value::string(address),
+                                self.state.string_prototype,
+                            );
+
+                            context.set_register(register, obj);
+                        }
+                        Err(err) => {
+                            throw_io_error!(
+                                self, process, err, context, code, index
+                            );
+                        }
+                    }
+                }
+                // Binds a UDP socket to an address.
+                //
+                // This instruction requires two arguments:
+                //
+                // 1. The register to store the socket in.
+                // 2. The register containing the address to bind to, such as
+                //    "127.0.0.1:8080". Using port 0 binds the socket to a
+                //    random port.
+                //
+                // This instruction will throw when encountering an IO error.
+                InstructionType::UdpBind => {
+                    let register = instruction.arg(0);
+                    let addr_ptr = context.get_register(instruction.arg(1));
+
+                    match net::udp_bind(addr_ptr.string_value()?) {
+                        Ok(socket) => {
+                            let obj = process.allocate(
+                                object_value::udp_socket(socket),
+                                self.state.object_prototype,
+                            );
+
+                            context.set_register(register, obj);
+                        }
+                        Err(err) => {
+                            throw_io_error!(
+                                self, process, err, context, code, index
+                            );
+                        }
+                    }
+                }
+                // Binds a Unix socket to a path, and starts listening for
+                // incoming connections.
+                //
+                // This instruction requires two arguments:
+                //
+                // 1. The register to store the listener in.
+                // 2. The register containing the path to bind to.
+                //
+                // This instruction will throw when encountering an IO error.
+                InstructionType::UnixListen => {
+                    let register = instruction.arg(0);
+                    let path_ptr = context.get_register(instruction.arg(1));
+
+                    match net::unix_listen(path_ptr.string_value()?) {
+                        Ok(listener) => {
+                            let obj = process.allocate(
+                                object_value::unix_listener(listener),
+                                self.state.object_prototype,
+                            );
+
+                            context.set_register(register, obj);
+                        }
+                        Err(err) => {
+                            throw_io_error!(
+                                self, process, err, context, code, index
+                            );
+                        }
+                    }
+                }
+                // Connects a Unix stream socket to a path.
+                //
+                // This instruction requires two arguments:
+                //
+                // 1. The register to store the stream in.
+                // 2. The register containing the path to connect to.
+                //
+                // Connecting to a Unix socket completes right away. If the
+                // listener can not accept any more pending connections, an
+                // error is thrown instead of suspending the process.
+                //
+                // This instruction will throw when encountering an IO error.
+                InstructionType::UnixConnect => {
+                    let register = instruction.arg(0);
+                    let path_ptr = context.get_register(instruction.arg(1));
+
+                    match net::unix_connect(path_ptr.string_value()?) {
+                        Ok(stream) => {
+                            let obj = process.allocate(
+                                object_value::unix_stream(stream),
+                                self.state.object_prototype,
+                            );
+
+                            context.set_register(register, obj);
+                        }
+                        Err(err) => {
+                            throw_io_error!(
+                                self, process, err, context, code, index
+                            );
+                        }
+                    }
+                }
+                // Creates a Unix datagram socket.
+                //
+                // This instruction requires two arguments:
+                //
+                // 1. The register to store the socket in.
+                // 2. The register containing the path to bind the socket to.
+                //    If set to nil, the socket is not bound to a path.
+                //
+                // This instruction will throw when encountering an IO error.
+                InstructionType::UnixDatagramBind => {
+                    let register = instruction.arg(0);
+                    let path_ptr = context.get_register(instruction.arg(1));
+
+                    let path = if path_ptr == self.state.nil_object {
+                        None
+                    } else {
+                        Some(path_ptr.string_value()?.as_str())
+                    };
+
+                    match net::unix_datagram(path) {
+                        Ok(socket) => {
+                            let obj = process.allocate(
+                                object_value::unix_datagram(socket),
+                                self.state.object_prototype,
+                            );
+
+                            context.set_register(register, obj);
+                        }
+                        Err(err) => {
+                            throw_io_error!(
+                                self, process, err, context, code, index
+                            );
+                        }
+                    }
+                }
+                // Creates a pair of connected Unix sockets.
+                //
+                // This instruction requires two arguments:
+                //
+                // 1. The register to store an array containing both sockets
+                //    in.
+                // 2. The register containing an integer that specifies the
+                //    type of sockets to create.
+                //
+                // The available socket types are as follows:
+                //
+                // * 0: stream sockets
+                // * 1: datagram sockets
+                //
+                // This instruction will throw when encountering an IO error.
+                InstructionType::UnixPair => {
+                    let register = instruction.arg(0);
+                    let kind_ptr = context.get_register(instruction.arg(1));
+
+                    let result = match kind_ptr.integer_value()? {
+                        net::PAIR_STREAM => net::unix_stream_pair(),
+                        net::PAIR_DATAGRAM => net::unix_datagram_pair(),
+                        kind => {
+                            return Err(format!(
+                                "Invalid Unix socket pair type: {}",
+                                kind
+                            ));
+                        }
+                    };
+
+                    match result {
+                        Ok((first, second)) => {
+                            let proto = self.state.object_prototype;
+                            let first_ptr = process.allocate(first, proto);
+                            let second_ptr = process.allocate(second, proto);
+
+                            let array = process.allocate(
+                                object_value::array(vec![first_ptr, second_ptr]),
+                                self.state.array_prototype,
+                            );
+
+                            context.set_register(register, array);
+                        }
+                        Err(err) => {
+                            throw_io_error!(
+                                self, process, err, context, code, index
+                            );
+                        }
+                    }
+                }
+                // Connects a UDP or Unix datagram socket to an address.
+                //
+                // This instruction requires three arguments:
+                //
+                // 1. The register to store the result (nil) in.
+                // 2. The register containing the socket.
+                // 3. The register containing the address or path to connect
+                //    to.
+                //
+                // Connecting a datagram socket only sets the default address
+                // to send datagrams to, allowing the use of SocketWrite and
+                // SocketRead.
+                //
+                // This instruction will throw when encountering an IO error.
+                InstructionType::SocketConnect => {
+                    let register = instruction.arg(0);
+                    let socket_ptr = context.get_register(instruction.arg(1));
+                    let addr_ptr = context.get_register(instruction.arg(2));
+
+                    let socket = socket_ptr.socket_value()?;
+
+                    if let Err(err) = socket.connect(addr_ptr.string_value()?) {
+                        throw_io_error!(
+                            self, process, err, context, code, index
+                        );
+                    } else {
+                        context.set_register(register, self.state.nil_object);
+                    }
+                }
+                // Sends a string or byte array to an address using a UDP or
+                // Unix datagram socket.
+                //
+                // This instruction requires four arguments:
+                //
+                // 1. The register to store the number of sent bytes in.
+                // 2. The register containing the socket to send the data with.
+                // 3. The register containing the string or byte array to send.
+                // 4. The register containing the address or path to send the
+                //    data to.
+                //
+                // If the data can not be sent right away, the current process
+                // is suspended until the socket is ready for writing.
+                //
+                // This instruction will throw when encountering an IO error.
+                InstructionType::SocketSendTo => {
+                    let register = instruction.arg(0);
+                    let socket_ptr = context.get_register(instruction.arg(1));
+                    let value_ptr = context.get_register(instruction.arg(2));
+                    let addr_ptr = context.get_register(instruction.arg(3));
+
+                    let socket = socket_ptr.socket_value()?;
+                    let address = addr_ptr.string_value()?;
+
+                    let data = if value_ptr.is_string() {
+                        value_ptr.string_value()?.as_bytes()
+                    } else {
+                        value_ptr.byte_array_value()?.as_slice()
+                    };
+
+                    match socket.send_to(data, address) {
+                        Ok(amount) => {
+                            let amount_ptr = process.allocate_usize(
+                                amount,
+                                self.state.integer_prototype,
+                            );
+
+                            context.set_register(register, amount_ptr);
+                        }
+                        Err(ref err)
+                            if err.kind() == io::ErrorKind::WouldBlock =>
+                        {
+                            wait_for_io!(
+                                self,
+                                process,
+                                socket,
+                                Interest::Write,
+                                context,
+                                code,
+                                index
+                            );
+                        }
+                        Err(err) => {
+                            throw_io_error!(
+                                self, process, err, context, code, index
+                            );
+                        }
+                    }
+                }
+                // Receives a single datagram using a UDP or Unix datagram
+                // socket.
+                //
+                // This instruction requires four arguments:
+                //
+                // 1. The register to store the address of the sender in.
+                // 2. The register containing the socket to receive the data
+                //    with.
+                // 3. The register containing the byte array to read the data
+                //    into.
+                // 4. The register containing the maximum number of bytes to
+                //    read. If set to nil, up to 64 KB is read. Any remaining
+                //    data of the datagram is discarded.
+                //
+                // If no datagram is available, the current process is
+                // suspended until one arrives.
+                //
+                // This instruction will throw when encountering an IO error.
+                InstructionType::SocketReceiveFrom => {
+                    let register = instruction.arg(0);
+                    let socket_ptr = context.get_register(instruction.arg(1));
+                    let buff_ptr = context.get_register(instruction.arg(2));
+                    let size_ptr = context.get_register(instruction.arg(3));
+
+                    let socket = socket_ptr.socket_value()?;
+                    let buffer = buff_ptr.byte_array_value_mut()?;
+                    let size = self.read_size(size_ptr)?;
+
+                    match socket.receive_from(buffer, size) {
+                        Ok((_, address)) => {
+                            let obj = process.allocate(
+                                object_value::string(address),
+                                self.state.string_prototype,
+                            );
+
+                            context.set_register(register, obj);
+                        }
+                        Err(ref err)
+                            if err.kind() == io::ErrorKind::WouldBlock =>
+                        {
+                            wait_for_io!(
+                                self,
+                                process,
+                                socket,
+                                Interest::Read,
+                                context,
+                                code,
+                                index
+                            );
+                        }
+                        Err(err) => {
+                            throw_io_error!(
+                                self, process, err, context, code, index
+                            );
+                        }
+                    }
+                }
+                // Sets an option of a socket.
+                //
+                // This instruction requires four arguments:
+                //
+                // 1. The register to store the result (nil) in.
+                // 2. The register containing the socket.
+                // 3. The register containing the option to set.
+                // 4. The register containing the new value of the option, as
+                //    a boolean or an integer.
+                //
+                // The available options are as follows:
+                //
+                // * 0: broadcast (boolean, UDP)
+                // * 1: multicast loop (boolean, UDP)
+                // * 2: multicast time-to-live (integer, UDP)
+                // * 3: time-to-live (integer, TCP and UDP)
+                // * 4: no delay (boolean, TCP streams)
+                //
+                // This instruction will throw when encountering an IO error,
+                // or when the socket does not support the option.
+                InstructionType::SocketSetOption => {
+                    let register = instruction.arg(0);
+                    let socket_ptr = context.get_register(instruction.arg(1));
+                    let option_ptr = context.get_register(instruction.arg(2));
+                    let value_ptr = context.get_register(instruction.arg(3));
+
+                    let socket = socket_ptr.socket_value()?;
+                    let option = option_ptr.integer_value()?;
+
+                    let value = if net::is_boolean_option(option) {
+                        (value_ptr == self.state.true_object) as i64
+                    } else {
+                        value_ptr.integer_value()?
+                    };
+
+                    if let Err(err) = socket.set_option(option, value) {
+                        throw_io_error!(
+                            self, process, err, context, code, index
+                        );
+                    } else {
+                        context.set_register(register, self.state.nil_object);
+                    }
+                }
+                // Returns the value of a socket option.
+                //
+                // This instruction requires three arguments:
+                //
+                // 1. The register to store the value in.
+                // 2. The register containing the socket.
+                // 3. The register containing the option to get. The available
+                //    options are the same as those of SocketSetOption.
+                //
+                // This instruction will throw when encountering an IO error,
+                // or when the socket does not support the option.
+                InstructionType::SocketGetOption => {
+                    let register = instruction.arg(0);
+                    let socket_ptr = context.get_register(instruction.arg(1));
+                    let option_ptr = context.get_register(instruction.arg(2));
+
+                    let socket = socket_ptr.socket_value()?;
+                    let option = option_ptr.integer_value()?;
+
+                    match socket.get_option(option) {
+                        Ok(value) => {
+                            let value_ptr = if net::is_boolean_option(option) {
+                                boolean_to_pointer!(self, value != 0)
+                            } else {
+                                ObjectPointer::integer(value)
+                            };
+
+                            context.set_register(register, value_ptr);
+                        }
+                        Err(err) => {
+                            throw_io_error!(
+                                self, process, err, context, code, index
+                            );
+                        }
+                    }
+                }
+                // Joins a multicast group using a UDP socket.
+                //
+                // This instruction requires four arguments:
+                //
+                // 1. The register to store the result (nil) in.
+                // 2. The register containing the socket.
+                // 3. The register containing the IP address of the group.
+                // 4. The register containing the interface to use. For IPv4
+                //    groups this is the IPv4 address of the interface, such
+                //    as "0.0.0.0". For IPv6 groups this is the index of the
+                //    interface, such as "0".
+                //
+                // This instruction will throw when encountering an IO error.
+                InstructionType::SocketJoinMulticast => {
+                    let register = instruction.arg(0);
+                    let socket_ptr = context.get_register(instruction.arg(1));
+                    let group_ptr = context.get_register(instruction.arg(2));
+                    let iface_ptr = context.get_register(instruction.arg(3));
+
+                    let result = socket_ptr.socket_value()?.join_multicast(
+                        group_ptr.string_value()?,
+                        iface_ptr.string_value()?,
+                    );
+
+                    if let Err(err) = result {
+                        throw_io_error!(
+                            self, process, err, context, code, index
+                        );
+                    } else {
+                        context.set_register(register, self.state.nil_object);
+                    }
+                }
+                // Leaves a multicast group joined using SocketJoinMulticast.
+                //
+                // This instruction requires the same arguments as
+                // SocketJoinMulticast.
+                //
+                // This instruction will throw when encountering an IO error.
+                InstructionType::SocketLeaveMulticast => {
+                    let register = instruction.arg(0);
+                    let socket_ptr = context.get_register(instruction.arg(1));
+                    let group_ptr = context.get_register(instruction.arg(2));
+                    let iface_ptr = context.get_register(instruction.arg(3));
+
+                    let result = socket_ptr.socket_value()?.leave_multicast(
+                        group_ptr.string_value()?,
+                        iface_ptr.string_value()?,
+                    );
+
+                    if let Err(err) = result {
+                        throw_io_error!(
+                            self, process, err, context, code, index
+                        );
+                    } else {
+                        context.set_register(register, self.state.nil_object);
+                    }
+                }
+                // Spawns an OS child process.
+                //
+                // This instruction requires five arguments:
+                //
+                // 1. The register to store the child process in.
+                // 2. The register containing an array with the program to run,
+                //    followed by the arguments to pass to it. If the program
+                //    is not a path, it is looked up using the PATH
+                //    environment variable.
+                // 3. The register containing an array of environment
+                //    variables to set. Every variable is an array containing
+                //    the name and value of the variable.
+                // 4. The register containing the working directory of the
+                //    child. If set to nil, the working directory of the VM is
+                //    used.
+                // 5. The register containing an array with the modes of the
+                //    standard input, output, and error streams, in that
+                //    order.
+                //
+                // The available stream modes are as follows:
+                //
+                // * 0: the stream is inherited from the VM
+                // * 1: the stream is connected to /dev/null
+                // * 2: the stream is connected to a pipe
+                //
+                // This instruction will throw when encountering an IO error.
+                InstructionType::ChildSpawn => {
+                    let register = instruction.arg(0);
+                    let args_ptr = context.get_register(instruction.arg(1));
+                    let env_ptr = context.get_register(instruction.arg(2));
+                    let dir_ptr = context.get_register(instruction.arg(3));
+                    let stdio_ptr = context.get_register(instruction.arg(4));
+
+                    let args = args_ptr.array_value()?;
+                    let stdio = stdio_ptr.array_value()?;
+
+                    if args.is_empty() {
+                        return Err(
+                            "ChildSpawn requires a program to run".to_string()
+                        );
+                    }
+
+                    if stdio.len() != 3 {
+                        return Err("ChildSpawn requires a mode for every \
+                                    standard stream"
+                            .to_string());
+                    }
+
+                    let mut command = Command::new(args[0].string_value()?);
+
+                    for arg_ptr in &args[1..] {
+                        command.arg(arg_ptr.string_value()?);
+                    }
+
+                    for pair_ptr in env_ptr.array_value()? {
+                        let pair = pair_ptr.array_value()?;
+
+                        if pair.len() != 2 {
+                            return Err("Environment variables must be given \
+                                        as a name and a value"
+                                .to_string());
+                        }
+
+                        command.env(
+                            pair[0].string_value()?,
+                            pair[1].string_value()?,
+                        );
+                    }
+
+                    if dir_ptr != self.state.nil_object {
+                        command.current_dir(dir_ptr.string_value()?);
+                    }
+
+                    command
+                        .stdin(child_process::stdio_for_integer(
+                            stdio[0].integer_value()?,
+                        )?)
+                        .stdout(child_process::stdio_for_integer(
+                            stdio[1].integer_value()?,
+                        )?)
+                        .stderr(child_process::stdio_for_integer(
+                            stdio[2].integer_value()?,
+                        )?);
+
+                    match child_process::spawn(&mut command) {
+                        Ok(child) => {
+                            let obj = process.allocate(
+                                object_value::child_process(child),
+                                self.state.object_prototype,
+                            );
+
+                            context.set_register(register, obj);
+                        }
+                        Err(err) => {
+                            throw_io_error!(
+                                self, process, err, context, code, index
+                            );
+                        }
+                    }
+                }
+                // Writes a string or byte array to the standard input stream
+                // of a child process.
+                //
+                // This instruction requires three arguments:
+                //
+                // 1. The register to store the number of written bytes in.
+                // 2. The register containing the child process.
+                // 3. The register containing the string or byte array to write.
+                //
+                // Not all data may be written at once. If no data can be
+                // written, the current process is suspended until the child
+                // is ready to receive more input.
+                //
+                // This instruction will throw when encountering an IO error.
+                InstructionType::ChildWrite => {
+                    let register = instruction.arg(0);
+                    let child_ptr = context.get_register(instruction.arg(1));
+                    let value_ptr = context.get_register(instruction.arg(2));
+
+                    let child = child_ptr.child_process_value_mut()?;
+                    let stdin = child_process::input(child)?;
+
+                    match write_bytes_or_string!(stdin, value_ptr) {
+                        Ok(amount) => {
+                            let amount_ptr = process.allocate_usize(
+                                amount,
+                                self.state.integer_prototype,
+                            );
+
+                            context.set_register(register, amount_ptr);
+                        }
+                        Err(ref err)
+                            if err.kind() == io::ErrorKind::WouldBlock =>
+                        {
+                            wait_for_io!(
+                                self,
+                                process,
+                                stdin,
+                                Interest::Write,
+                                context,
+                                code,
+                                index
+                            );
+                        }
+                        Err(err) => {
+                            throw_io_error!(
+                                self, process, err, context, code, index
+                            );
+                        }
+                    }
+                }
+                // Reads data from the standard output or error stream of a
+                // child process into an array of bytes.
+                //
+                // This instruction requires five arguments:
+                //
+                // 1. The register to store the number of read bytes in.
+                // 2. The register containing the child process.
+                // 3. The register containing the stream to read from, with 0
+                //    being the standard output stream and 1 the standard error
+                //    stream.
+                // 4. The register containing the byte array to read the data
+                //    into.
+                // 5. The register containing the maximum number of bytes to
+                //    read. If set to nil, all data that is currently available
+                //    is read.
+                //
+                // If no data is available, the current process is suspended
+                // until data arrives. Once the child closes the stream, the
+                // number of read bytes is 0.
+                //
+                // This instruction will throw when encountering an IO error.
+                InstructionType::ChildRead => {
+                    let register = instruction.arg(0);
+                    let child_ptr = context.get_register(instruction.arg(1));
+                    let stream_ptr = context.get_register(instruction.arg(2));
+                    let buff_ptr = context.get_register(instruction.arg(3));
+                    let size_ptr = context.get_register(instruction.arg(4));
+
+                    let child = child_ptr.child_process_value_mut()?;
+                    let stream = stream_ptr.integer_value()?;
+                    let mut output = child_process::output(child, stream)?;
+
+                    let buffer = buff_ptr.byte_array_value_mut()?;
+                    let size = self.read_size(size_ptr)?;
+
+                    match output.read(buffer, size) {
+                        Ok(amount) => {
+                            let amount_ptr = process.allocate_usize(
+                                amount,
+                                self.state.integer_prototype,
+                            );
+
+                            context.set_register(register, amount_ptr);
+                        }
+                        Err(ref err)
+                            if err.kind() == io::ErrorKind::WouldBlock =>
+                        {
+                            wait_for_io!(
+                                self,
+                                process,
+                                output,
+                                Interest::Read,
+                                context,
+                                code,
+                                index
+                            );
+                        }
+                        Err(err) => {
+                            throw_io_error!(
+                                self, process, err, context, code, index
+                            );
+                        }
+                    }
+                }
+                // Closes the standard input stream of a child process.
+                //
+                // This instruction requires two arguments:
+                //
+                // 1. The register to store the result (nil) in.
+                // 2. The register containing the child process.
+                //
+                // Closing the stream allows a child that reads its input until
+                // the end of the stream to finish.
+                InstructionType::ChildCloseInput => {
+                    let register = instruction.arg(0);
+                    let child_ptr = context.get_register(instruction.arg(1));
+
+                    child_process::close_input(
+                        child_ptr.child_process_value_mut()?,
+                    );
+
+                    context.set_register(register, self.state.nil_object);
+                }
+                // Waits for a child process to terminate.
+                //
+                // This instruction requires two arguments:
+                //
+                // 1. The register to store the result (nil) in.
+                // 2. The register containing the child process.
+                //
+                // The standard input stream of the child is closed before
+                // waiting. The current process is suspended until the child
+                // terminates, after which ChildExitCode and ChildExitSignal can
+                // be used to obtain its exit status. Suspended processes are
+                // resumed when the VM receives the SIGCHLD signal.
+                //
+                // This instruction will throw when encountering an IO error.
+                InstructionType::ChildWait => {
+                    let register = instruction.arg(0);
+                    let child_ptr = context.get_register(instruction.arg(1));
+                    let child = child_ptr.child_process_value_mut()?;
+
+                    // The number of child events must be obtained before
+                    // checking the child, otherwise we may miss the SIGCHLD
+                    // of a child that terminates right after checking it.
+                    let events = self.state.suspension_list.child_events();
+
+                    if let Err(err) = self.state.signal_handler.watch_children()
+                    {
+                        throw_io_error!(
+                            self, process, err, context, code, index
+                        );
+
+                        continue;
+                    }
+
+                    match child_process::try_wait(child) {
+                        Ok(Some(_)) => {
+                            context
+                                .set_register(register, self.state.nil_object);
+                        }
+                        Ok(None) => {
+                            context.instruction_index = index - 1;
+
+                            self.state.suspension_list.suspend_for_child(
+                                &self.state,
+                                process.clone(),
+                                events,
+                            );
+
+                            return Ok(());
+                        }
+                        Err(err) => {
+                            throw_io_error!(
+                                self, process, err, context, code, index
+                            );
+                        }
+                    }
+                }
+                // Returns the exit code of a child process.
+                //
+                // This instruction requires two arguments:
+                //
+                // 1. The register to store the exit code in.
+                // 2. The register containing the child process.
+                //
+                // If the child is still running, or was terminated by a
+                // signal, nil is returned instead.
+                //
+                // This instruction will throw when encountering an IO error.
+                InstructionType::ChildExitCode => {
+                    let register = instruction.arg(0);
+                    let child_ptr = context.get_register(instruction.arg(1));
+                    let child = child_ptr.child_process_value_mut()?;
+
+                    match child.try_wait() {
+                        Ok(status) => {
+                            let code_ptr = status
+                                .and_then(child_process::exit_code)
+                                .map_or(
+                                    self.state.nil_object,
+                                    ObjectPointer::integer,
+                                );
+
+                            context.set_register(register, code_ptr);
+                        }
+                        Err(err) => {
+                            throw_io_error!(
+                                self, process, err, context, code, index
+                            );
+                        }
+                    }
+                }
+                // Returns the signal that terminated a child process.
+                //
+                // This instruction requires two arguments:
+                //
+                // 1. The register to store the signal number in.
+                // 2. The register containing the child process.
+                //
+                // If the child is still running, or terminated normally, nil
+                // is returned instead.
+                //
+                // This instruction will throw when encountering an IO error.
+                InstructionType::ChildExitSignal => {
+                    let register = instruction.arg(0);
+                    let child_ptr = context.get_register(instruction.arg(1));
+                    let child = child_ptr.child_process_value_mut()?;
+
+                    match child.try_wait() {
+                        Ok(status) => {
+                            let signal_ptr = status
+                                .and_then(child_process::exit_signal)
+                                .map_or(
+                                    self.state.nil_object,
+                                    ObjectPointer::integer,
+                                );
+
+                            context.set_register(register, signal_ptr);
+                        }
+                        Err(err) => {
+                            throw_io_error!(
+                                self, process, err, context, code, index
+                            );
+                        }
+                    }
+                }
+                // Forcefully terminates a child process.
+                //
+                // This instruction requires two arguments:
+                //
+                // 1. The register to store the result (nil) in.
+                // 2. The register containing the child process.
+                //
+                // This instruction will throw when encountering an IO error,
+                // such as when the child already terminated.
+                InstructionType::ChildKill => {
+                    let register = instruction.arg(0);
+                    let child_ptr = context.get_register(instruction.arg(1));
+
+                    let child = child_ptr.child_process_value_mut()?;
+
+                    if let Err(err) = child.kill() {
+                        throw_io_error!(
+                            self, process, err, context, code, index
+                        );
+                    } else {
+                        context.set_register(register, self.state.nil_object);
+                    }
+                }
+                // Subscribes the current process to an OS signal.
+                //
+                // This instruction requires two arguments:
+                //
+                // 1. The register to store the result (nil) in.
+                // 2. The register containing the signal number.
+                //
+                // Every time the signal is received, its number is sent as a
+                // message to the current process. Only SIGHUP, SIGINT,
+                // SIGTERM, SIGUSR1, SIGUSR2, and SIGCHLD are supported.
+                //
+                // This instruction will throw when encountering an IO error.
+                InstructionType::SignalSubscribe => {
+                    let register = instruction.arg(0);
+                    let signal_ptr = context.get_register(instruction.arg(1));
+                    let signal = signal_handler::signal_for_integer(
+                        signal_ptr.integer_value()?,
+                    )?;
+
+                    let result = self
+                        .state
+                        .signal_handler
+                        .subscribe(signal, process.clone());
+
+                    if let Err(err) = result {
+                        throw_io_error!(
+                            self, process, err, context, code, index
+                        );
+                    } else {
+                        context.set_register(register, self.state.nil_object);
+                    }
+                }
+                // Unsubscribes the current process from an OS signal.
+                //
+                // This instruction requires two arguments:
+                //
+                // 1. The register to store the result (nil) in.
+                // 2. The register containing the signal number.
+                //
+                // The default action of the signal is restored once no
+                // processes are subscribed to it.
+                //
+                // This instruction will throw when encountering an IO error.
+                InstructionType::SignalUnsubscribe => {
+                    let register = instruction.arg(0);
+                    let signal_ptr = context.get_register(instruction.arg(1));
+                    let signal = signal_handler::signal_for_integer(
+                        signal_ptr.integer_value()?,
+                    )?;
+
+                    let result = self
+                        .state
+                        .signal_handler
+                        .unsubscribe(signal, process.pid);
+
+                    if let Err(err) = result {
+                        throw_io_error!(
+                            self, process, err, context, code, index
+                        );
+                    } else {
+                        context.set_register(register, self.state.nil_object);
+                    }
+                }
+            };
+        }
+
+        // A process that terminated itself using ProcessTerminate does so
+        // with the reason it specified.
+        let reason =
+            process.pending_termination().unwrap_or(ExitReason::Normal);
+
+        process.set_exit_reason(reason);
+
+        self.finish_process(process);
+
+        Ok(())
+    }
+
+    /// Receives a message for a process, waking up any processes waiting for
+    /// space in its mailbox.
+    fn receive_message(&self, process: &RcProcess) -> Option<ObjectPointer> {
+        let message = process.receive_message();
+
+        if message.is_some() {
+            self.resume_senders(process);
+        }
+
+        message
+    }
+
+    /// Resumes the processes waiting for space in the mailbox of a process,
+    /// after it removed a message from its mailbox.
+    ///
+    /// The senders waiting for space are looked up while holding on to the
+    /// lock of the suspension list, instead of relying on a flag set by the
+    /// senders. This way a sender can't miss its wake-up when it checks for
+    /// space just before the receiver removes a message.
+    fn resume_senders(&self, process: &RcProcess) {
+        if process.has_bounded_mailbox() {
+            self.state
+                .suspension_list
+                .resume_senders(&self.state, process);
+        }
+    }
+
+    /// Returns the PID for a pointer containing either a PID, or the name of a
+    /// registered process.
+    fn resolve_pid(
+        &self,
+        pointer: ObjectPointer,
+    ) -> Result<Option<PID>, String> {
+        if pointer.is_string() {
+            let name = self.state.intern_pointer(pointer)?;
+
+            Ok(read_lock!(self.state.process_registry).whereis(&name))
+        } else {
+            pointer.usize_value().map(Some)
+        }
+    }
+
+    /// Returns the number of bytes to read from a socket, with nil meaning no
+    /// limit was given.
+    fn read_size(
+        &self,
+        pointer: ObjectPointer,
+    ) -> Result<Option<usize>, String> {
+        if pointer == self.state.nil_object {
+            return Ok(None);
+        }
+
+        let size = pointer.integer_value()?;
+
+        if size < 0 {
+            return Err(format!(
+                "{} is not a valid number of bytes to read",
+                size
+            ));
+        }
+
+        Ok(Some(size as usize))
+    }
+
+    /// Finishes a process if its termination was requested, returning true if
+    /// the process finished.
+    fn terminate_if_requested(&self, process: &RcProcess) -> bool {
+        if !process.is_termination_requested() {
+            return false;
+        }
+
+        if let Some(reason) = process.pending_termination() {
+            process.set_exit_reason(reason);
+        }
+
+        self.finish_process(process);
+
+        true
+    }
+
+    /// Removes a process that finished execution and schedules it for
+    /// cleanup.
+    fn finish_process(&self, process: &RcProcess) {
+        process.finished();
+
+        {
+            let mut table = write_lock!(self.state.process_table);
+
+            table.release(process.pid);
+
+            // The name is released while holding on to the process table, this
+            // way the process can't be registered again after this point.
+            write_lock!(self.state.process_registry).release(process.pid);
+        }
+
+        // The process is gone, so there's nobody left to report a failure to
+        // restore the default action of a signal to.
+        let _ = self.state.signal_handler.unsubscribe_all(process.pid);
+
+        // The messages of pending timers are never delivered, so there's no
+        // need to keep them around.
+        process.clear_timers();
+
+        // Processes waiting for space in the mailbox are resumed, as their
+        // messages can't be delivered any more.
+        self.state
+            .suspension_list
+            .resume_senders(&self.state, process);
+
+        // Exit messages are allocated on the heap of the finished process, so
+        // watchers must be notified before scheduling the process for cleanup.
+        self.notify_watchers(process);
+
+        // We must clean up _after_ removing the process from the process table
+        // to prevent a cleanup from happening while the process is still
+        // receiving messages as this could lead to memory not being reclaimed.
+        self.schedule_gc_for_finished_process(process);
+
+        // Terminate once the main process has finished execution.
+        if process.is_main() {
+            if process.exit_reason().map_or(false, |r| r.is_abnormal()) {
+                self.state.set_exit_status(1);
+            }
+
+            self.terminate();
+        }
+    }
+
+    /// Notifies the monitors and linked processes of a finished process.
+    ///
+    /// Monitors, and linked processes that trap exits, receive an exit
+    /// message. Linked processes that don't trap exits are terminated if the
+    /// process did not finish normally.
+    fn notify_watchers(&self, process: &RcProcess) {
+        let watchers = process.close_watchers();
+
+        if watchers.monitors.is_empty() && watchers.links.is_empty() {
+            return;
+        }
+
+        let reason = process.exit_reason().unwrap_or(ExitReason::Normal);
+        let message = self.allocate_exit_message(process, &reason);
+        let table = read_lock!(self.state.process_table);
+
+        for pid in watchers.monitors {
+            if let Some(receiver) = table.get(pid) {
+                self.send_exit_message(process, &receiver, message);
+            }
+        }
+
+        for pid in watchers.links {
+            if let Some(receiver) = table.get(pid) {
+                receiver.remove_link(process.pid);
+
+                if receiver.traps_exits() {
+                    self.send_exit_message(process, &receiver, message);
+                } else if reason.is_abnormal() {
+                    receiver.request_termination(reason.clone());
+
+                    self.state.suspension_list.resume(&self.state, &receiver);
+                }
+            }
+        }
+    }
+
+    /// Allocates the exit message for a finished process.
+    ///
+    /// An exit message is an Array containing the PID of the process, the
+    /// exit reason as an integer, and the panic or termination message (or
+    /// nil).
+    fn allocate_exit_message(
+        &self,
+        process: &RcProcess,
+        reason: &ExitReason,
+    ) -> ObjectPointer {
+        let pid =
+            process.allocate_usize(process.pid, self.state.integer_prototype);
+
+        let details = match *reason {
+            ExitReason::Panic(ref message, _)
+            | ExitReason::Terminated(ref message) => process.allocate(
+                object_value::string(message.clone()),
+                self.state.string_prototype,
+            ),
+            ExitReason::Normal => self.state.nil_object,
+        };
+
+        process.allocate(
+            object_value::array(vec![
+                pid,
+                ObjectPointer::integer(reason.code()),
+                details,
+            ]),
+            self.state.array_prototype,
+        )
+    }
+
+    fn send_exit_message(
+        &self,
+        process: &RcProcess,
+        receiver: &RcProcess,
+        message: ObjectPointer,
+    ) {
+        receiver.send_message(process, message);
+
+        if receiver.is_waiting_for_message() {
+            self.state
+                .suspension_list
+                .resume_for_message(&self.state, receiver);
+        }
+    }
+
+    /// Collects a set of arguments from an instruction.
+    pub fn collect_arguments(
+        &self,
+        process: &RcProcess,
+        instruction: &Instruction,
+        offset: usize,
+        amount: usize,
+    ) -> Vec<ObjectPointer> {
+        let mut args: Vec<ObjectPointer> = Vec::with_capacity(amount);
+
+        for index in offset..(offset + amount) {
+            let arg_index = instruction.arg(index);
+
+            args.push(process.get_register(arg_index));
+        }
+
+        args
+    }
+
+    /// Checks if a garbage collection run should be scheduled for the given
+    /// process.
+    ///
+    /// Returns true if a process should be suspended for garbage collection.
+    fn gc_safepoint(&self, process: &RcProcess) -> bool {
+        if process.should_collect_young_generation() {
+            self.schedule_gc_request(GcRequest::heap(
+                self.state.clone(),
+                process.clone(),
+            ));
+
+            true
+        } else if process.should_collect_mailbox() {
+            self.schedule_gc_request(GcRequest::mailbox(
+                self.state.clone(),
+                process.clone(),
+            ));
+
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Reschedules a process.
+    fn reschedule(&self, process: RcProcess) {
+        self.state.process_pools.schedule(process);
+    }
+
+    fn schedule_gc_request(&self, request: GcRequest) {
+        request.process.suspend_for_gc();
+        self.state.gc_pool.schedule(request);
+    }
+
+    fn schedule_gc_for_finished_process(&self, process: &RcProcess) {
+        let request = GcRequest::finished(self.state.clone(), process.clone());
+        self.state.gc_pool.schedule(request);
+    }
+
+    #[inline(always)]
+    fn validate_number_of_arguments(
+        &self,
+        code: CompiledCodePointer,
+        given_positional: usize,
+        given_keyword: usize,
+    ) -> Result<(), String> {
+        let arguments = given_positional + given_keyword;
+
+        if !code.valid_number_of_arguments(arguments) {
+            return Err(format!(
+                "{} takes {} arguments but {} were supplied",
+                code.name.string_value().unwrap(),
                 code.label_for_number_of_arguments(),
                 arguments
             ));
@@ -3665,11 +5996,24 @@ impl Machine {
         }
     }
 
+    /// Handles a panic in the given process.
+    ///
+    /// A panic in the main process always terminates the VM. A panic in any
+    /// other process only terminates that process if panics are isolated,
+    /// storing the panic as its exit reason.
     fn panic(&self, process: &RcProcess, message: &str) {
         runtime_panic::display_panic(process, message);
 
-        self.state.set_exit_status(1);
-        self.terminate();
+        if self.state.config.isolate_panics && !process.is_main() {
+            let reason = runtime_panic::exit_reason(process, message);
+
+            process.set_exit_reason(reason);
+
+            self.finish_process(process);
+        } else {
+            self.state.set_exit_status(1);
+            self.terminate();
+        }
     }
 
     fn unwind_until_defining_scope(&self, process: &RcProcess) {
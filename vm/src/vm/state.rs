@@ -6,6 +6,7 @@
 
 use parking_lot::Mutex;
 use num_bigint::BigInt;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, RwLock};
 use std::time;
 
@@ -16,12 +17,15 @@ use deref_pointer::DerefPointer;
 use immix::block::Block;
 use immix::global_allocator::{GlobalAllocator, RcGlobalAllocator};
 use immix::permanent_allocator::PermanentAllocator;
+use network_poller::NetworkPoller;
 use object_pointer::ObjectPointer;
 use object_value;
 use pool::Pool;
 use pools::Pools;
 use process::RcProcess;
+use process_registry::ProcessRegistry;
 use process_table::ProcessTable;
+use signal_handler::SignalHandler;
 use string_pool::StringPool;
 use suspension_list::SuspensionList;
 
@@ -60,6 +64,10 @@ pub struct State {
     /// Table containing all processes.
     pub process_table: RwLock<ProcessTable<RcProcess>>,
 
+    /// The names of registered processes, mapped to their PIDs. Names are
+    /// interned strings.
+    pub process_registry: RwLock<ProcessRegistry<ObjectPointer>>,
+
     /// The pool to use for garbage collection.
     pub gc_pool: Pool<Request>,
 
@@ -84,9 +92,22 @@ pub struct State {
     /// The list of suspended processes.
     pub suspension_list: SuspensionList,
 
+    /// The poller used for waiting until sockets are ready.
+    pub network_poller: NetworkPoller,
+
+    /// The handler used for sending OS signals to processes.
+    pub signal_handler: SignalHandler,
+
     /// The exit status to use when the VM terminates.
     pub exit_status: Mutex<i32>,
 
+    /// The value of the next unique reference to create.
+    pub next_reference: AtomicUsize,
+
+    /// The command-line arguments passed to the program, as permanent
+    /// Strings.
+    pub arguments: Vec<ObjectPointer>,
+
     /// The prototype of the base object, used as the prototype for all other
     /// prototypes.
     pub object_prototype: ObjectPointer,
@@ -158,6 +179,15 @@ impl State {
             false_obj.set_prototype(boolean_proto);
         }
 
+        let arguments = config
+            .arguments
+            .iter()
+            .map(|argument| {
+                let value = object_value::string(argument.clone());
+
+                perm_alloc.allocate_with_prototype(value, string_proto)
+            }).collect();
+
         let gc_pool = Pool::new(config.gc_threads, Some("GC".to_string()));
 
         let finalizer_pool =
@@ -169,6 +199,7 @@ impl State {
         let state = State {
             config,
             process_table: RwLock::new(ProcessTable::new()),
+            process_registry: RwLock::new(ProcessRegistry::new()),
             process_pools,
             gc_pool,
             finalizer_pool,
@@ -177,7 +208,11 @@ impl State {
             string_pool: Mutex::new(StringPool::new()),
             start_time: time::Instant::now(),
             exit_status: Mutex::new(0),
+            next_reference: AtomicUsize::new(0),
+            arguments,
             suspension_list: SuspensionList::new(),
+            network_poller: NetworkPoller::new(),
+            signal_handler: SignalHandler::new(),
             top_level,
             object_prototype: object_proto,
             integer_prototype: integer_proto,
@@ -249,6 +284,11 @@ impl State {
     pub fn current_exit_status(&self) -> i32 {
         *self.exit_status.lock()
     }
+
+    /// Returns a value that is unique for the lifetime of the VM.
+    pub fn new_reference(&self) -> u64 {
+        self.next_reference.fetch_add(1, Ordering::Relaxed) as u64
+    }
 }
 
 #[cfg(test)]
@@ -295,4 +335,12 @@ mod tests {
 
         assert_eq!(float.float_value().unwrap(), 10.5);
     }
+
+    #[test]
+    fn test_new_reference() {
+        let state = State::new(Config::new());
+
+        assert_eq!(state.new_reference(), 0);
+        assert_eq!(state.new_reference(), 1);
+    }
 }
@@ -11,7 +11,12 @@ use vm::state::State;
 
 /// Sets up a VM with a single process.
 pub fn setup() -> (Machine, Block, RcProcess) {
-    let state = State::new(Config::new());
+    setup_with_config(Config::new())
+}
+
+/// Sets up a VM with a single process, using the given configuration.
+pub fn setup_with_config(config: Config) -> (Machine, Block, RcProcess) {
+    let state = State::new(config);
     let name = state.intern(&"a".to_string());
     let machine = Machine::default(state);
     let mut code = CompiledCode::new(name, name, 1, Vec::new());
@@ -0,0 +1,256 @@
+use libinko::object_pointer::ObjectPointer;
+use libinko::object_value;
+use libinko::process::RcProcess;
+use libinko::vm::instruction::InstructionType;
+use libinko::vm::machine::Machine;
+use libinko::vm::test::*;
+use std::thread;
+use std::time::Duration;
+
+fn string(process: &RcProcess, value: &str) -> ObjectPointer {
+    process.allocate_without_prototype(object_value::string(value.to_string()))
+}
+
+fn array(process: &RcProcess, values: Vec<ObjectPointer>) -> ObjectPointer {
+    process.allocate_without_prototype(object_value::array(values))
+}
+
+fn byte_array(process: &RcProcess) -> ObjectPointer {
+    process.allocate_without_prototype(object_value::byte_array(Vec::new()))
+}
+
+/// Runs a process until it finishes, retrying any instruction that suspended
+/// the process.
+fn run_until_finished(machine: &Machine, process: &RcProcess) {
+    machine.run(process).unwrap();
+
+    while !process.is_finished() {
+        thread::sleep(Duration::from_millis(1));
+        machine.run(process).unwrap();
+    }
+}
+
+/// The stream modes used by most tests: standard input is connected to
+/// /dev/null, and the output streams are connected to pipes.
+const PIPE_OUTPUT: [i64; 3] = [1, 2, 2];
+
+/// Sets the registers used by ChildSpawn, running the given command using the
+/// given stream modes.
+fn spawn_registers(
+    machine: &Machine,
+    process: &RcProcess,
+    args: &[&str],
+    stdio: [i64; 3],
+) {
+    let args = args.iter().map(|arg| string(process, arg)).collect();
+    let stdio = stdio
+        .iter()
+        .map(|mode| ObjectPointer::integer(*mode))
+        .collect();
+
+    process.set_register(1, array(process, args));
+    process.set_register(2, array(process, Vec::new()));
+    process.set_register(3, machine.state.nil_object);
+    process.set_register(4, array(process, stdio));
+}
+
+#[test]
+fn test_child_spawn_and_read() {
+    let (machine, mut block, process) = setup();
+
+    block.code.instructions = vec![
+        new_instruction(InstructionType::ChildSpawn, vec![0, 1, 2, 3, 4]),
+        new_instruction(InstructionType::ChildWait, vec![8, 0]),
+        new_instruction(InstructionType::ChildRead, vec![9, 0, 10, 11, 12]),
+        new_instruction(InstructionType::ChildExitCode, vec![13, 0]),
+        new_instruction(InstructionType::ChildExitSignal, vec![14, 0]),
+        new_instruction(InstructionType::Return, vec![9]),
+    ];
+
+    spawn_registers(&machine, &process, &["echo", "hello"], PIPE_OUTPUT);
+
+    process.set_register(10, ObjectPointer::integer(0));
+    process.set_register(11, byte_array(&process));
+    process.set_register(12, machine.state.nil_object);
+
+    run_until_finished(&machine, &process);
+
+    assert_eq!(process.get_register(9).integer_value().unwrap(), 6);
+    assert_eq!(
+        process.get_register(11).byte_array_value().unwrap(),
+        &b"hello\n".to_vec()
+    );
+
+    assert_eq!(process.get_register(13).integer_value().unwrap(), 0);
+    assert!(process.get_register(14) == machine.state.nil_object);
+}
+
+#[test]
+fn test_child_spawn_with_environment_and_directory() {
+    let (machine, mut block, process) = setup();
+
+    block.code.instructions = vec![
+        new_instruction(InstructionType::ChildSpawn, vec![0, 1, 2, 3, 4]),
+        new_instruction(InstructionType::ChildWait, vec![8, 0]),
+        new_instruction(InstructionType::ChildRead, vec![9, 0, 10, 11, 12]),
+        new_instruction(InstructionType::Return, vec![9]),
+    ];
+
+    spawn_registers(
+        &machine,
+        &process,
+        &["sh", "-c", "printf $FOO; pwd"],
+        PIPE_OUTPUT,
+    );
+
+    let variable = vec![string(&process, "FOO"), string(&process, "bar")];
+    let variable_ptr = array(&process, variable);
+
+    process.set_register(2, array(&process, vec![variable_ptr]));
+    process.set_register(3, string(&process, "/"));
+    process.set_register(10, ObjectPointer::integer(0));
+    process.set_register(11, byte_array(&process));
+    process.set_register(12, machine.state.nil_object);
+
+    run_until_finished(&machine, &process);
+
+    assert_eq!(
+        process.get_register(11).byte_array_value().unwrap(),
+        &b"bar/\n".to_vec()
+    );
+}
+
+#[test]
+fn test_child_write_and_read_error() {
+    let (machine, mut block, process) = setup();
+
+    block.code.instructions = vec![
+        new_instruction(InstructionType::ChildSpawn, vec![0, 1, 2, 3, 4]),
+        new_instruction(InstructionType::ChildWrite, vec![8, 0, 9]),
+        new_instruction(InstructionType::ChildCloseInput, vec![10, 0]),
+        new_instruction(InstructionType::ChildRead, vec![11, 0, 12, 13, 14]),
+        new_instruction(InstructionType::ChildWait, vec![15, 0]),
+        new_instruction(InstructionType::Return, vec![11]),
+    ];
+
+    spawn_registers(&machine, &process, &["sh", "-c", "cat >&2"], [2, 2, 2]);
+
+    process.set_register(9, string(&process, "hello"));
+    process.set_register(12, ObjectPointer::integer(1));
+    process.set_register(13, byte_array(&process));
+    process.set_register(14, ObjectPointer::integer(5));
+
+    run_until_finished(&machine, &process);
+
+    assert_eq!(process.get_register(8).integer_value().unwrap(), 5);
+    assert_eq!(process.get_register(11).integer_value().unwrap(), 5);
+    assert_eq!(
+        process.get_register(13).byte_array_value().unwrap(),
+        &b"hello".to_vec()
+    );
+}
+
+#[test]
+fn test_child_wait_suspends_process() {
+    let (machine, mut block, process) = setup();
+
+    block.code.instructions = vec![
+        new_instruction(InstructionType::ChildSpawn, vec![0, 1, 2, 3, 4]),
+        new_instruction(InstructionType::ChildWait, vec![8, 0]),
+        new_instruction(InstructionType::ChildExitSignal, vec![9, 0]),
+        new_instruction(InstructionType::Return, vec![9]),
+    ];
+
+    spawn_registers(&machine, &process, &["sleep", "10"], PIPE_OUTPUT);
+
+    machine.run(&process).unwrap();
+
+    assert!(!process.is_finished());
+    assert_eq!(process.context().instruction_index, 1);
+    assert!(machine.state.signal_handler.watches_children());
+    assert!(machine
+        .state
+        .suspension_list
+        .suspended
+        .lock()
+        .unwrap()
+        .waiting_for_child
+        .contains(&process.pid));
+
+    process
+        .get_register(0)
+        .child_process_value_mut()
+        .unwrap()
+        .kill()
+        .unwrap();
+
+    run_until_finished(&machine, &process);
+
+    assert_eq!(process.get_register(9).integer_value().unwrap(), 9);
+}
+
+#[test]
+fn test_child_kill() {
+    let (machine, mut block, process) = setup();
+
+    block.code.instructions = vec![
+        new_instruction(InstructionType::ChildSpawn, vec![0, 1, 2, 3, 4]),
+        new_instruction(InstructionType::ChildKill, vec![8, 0]),
+        new_instruction(InstructionType::ChildWait, vec![9, 0]),
+        new_instruction(InstructionType::ChildExitCode, vec![10, 0]),
+        new_instruction(InstructionType::Return, vec![10]),
+    ];
+
+    spawn_registers(&machine, &process, &["sleep", "10"], PIPE_OUTPUT);
+
+    run_until_finished(&machine, &process);
+
+    assert!(process.get_register(8) == machine.state.nil_object);
+    assert!(process.get_register(10) == machine.state.nil_object);
+}
+
+#[test]
+fn test_child_spawn_with_invalid_program() {
+    let (machine, mut block, process) = setup();
+
+    block.code.instructions = vec![
+        new_instruction(InstructionType::ChildSpawn, vec![0, 1, 2, 3, 4]),
+        new_instruction(InstructionType::Return, vec![0]),
+    ];
+
+    spawn_registers(&machine, &process, &["inko-does-not-exist"], PIPE_OUTPUT);
+
+    assert!(machine.run(&process).is_err());
+}
+
+#[test]
+fn test_child_spawn_with_invalid_stream_mode() {
+    let (machine, mut block, process) = setup();
+
+    block.code.instructions = vec![
+        new_instruction(InstructionType::ChildSpawn, vec![0, 1, 2, 3, 4]),
+        new_instruction(InstructionType::Return, vec![0]),
+    ];
+
+    spawn_registers(&machine, &process, &["true"], [3, 2, 2]);
+
+    assert!(machine.run(&process).is_err());
+}
+
+#[test]
+fn test_child_read_without_pipe() {
+    let (machine, mut block, process) = setup();
+
+    block.code.instructions = vec![
+        new_instruction(InstructionType::ChildSpawn, vec![0, 1, 2, 3, 4]),
+        new_instruction(InstructionType::ChildRead, vec![8, 0, 9, 10, 11]),
+        new_instruction(InstructionType::Return, vec![8]),
+    ];
+
+    spawn_registers(&machine, &process, &["true"], [1, 1, 2]);
+    process.set_register(9, ObjectPointer::integer(0));
+    process.set_register(10, byte_array(&process));
+    process.set_register(11, machine.state.nil_object);
+
+    assert!(machine.run(&process).is_err());
+}
@@ -0,0 +1,215 @@
+use libinko::object_value;
+use libinko::vm::instruction::InstructionType;
+use libinko::vm::test::*;
+use std::env;
+
+#[test]
+fn test_env_set_and_get() {
+    let (machine, mut block, process) = setup();
+
+    block.code.instructions = vec![
+        new_instruction(InstructionType::EnvSet, vec![2, 0, 1]),
+        new_instruction(InstructionType::EnvGet, vec![3, 0]),
+        new_instruction(InstructionType::Return, vec![3]),
+    ];
+
+    let name = process.allocate_without_prototype(object_value::string(
+        "INKO_TEST_ENV_SET_AND_GET".to_string(),
+    ));
+
+    let value = process
+        .allocate_without_prototype(object_value::string("foo".to_string()));
+
+    process.set_register(0, name);
+    process.set_register(1, value);
+
+    machine.run(&process).unwrap();
+
+    assert!(process.get_register(2) == value);
+    assert_eq!(process.get_register(3).string_value().unwrap(), "foo");
+
+    env::remove_var("INKO_TEST_ENV_SET_AND_GET");
+}
+
+#[test]
+fn test_env_get_undefined() {
+    let (machine, mut block, process) = setup();
+
+    block.code.instructions = vec![
+        new_instruction(InstructionType::EnvGet, vec![1, 0]),
+        new_instruction(InstructionType::Return, vec![1]),
+    ];
+
+    let name = process.allocate_without_prototype(object_value::string(
+        "INKO_TEST_ENV_GET_UNDEFINED".to_string(),
+    ));
+
+    process.set_register(0, name);
+
+    machine.run(&process).unwrap();
+
+    assert!(process.get_register(1) == machine.state.nil_object);
+}
+
+#[test]
+fn test_env_set_with_invalid_name() {
+    for name in &["", "INKO_TEST=ENV", "INKO_TEST\0ENV"] {
+        let (machine, mut block, process) = setup();
+
+        block.code.instructions = vec![
+            new_instruction(InstructionType::EnvSet, vec![2, 0, 1]),
+            new_instruction(InstructionType::Return, vec![2]),
+        ];
+
+        let name = process
+            .allocate_without_prototype(object_value::string(name.to_string()));
+
+        let value = process.allocate_without_prototype(object_value::string(
+            "foo".to_string(),
+        ));
+
+        process.set_register(0, name);
+        process.set_register(1, value);
+
+        assert!(machine.run(&process).is_err());
+    }
+}
+
+#[test]
+fn test_env_set_with_invalid_value() {
+    let (machine, mut block, process) = setup();
+
+    block.code.instructions = vec![
+        new_instruction(InstructionType::EnvSet, vec![2, 0, 1]),
+        new_instruction(InstructionType::Return, vec![2]),
+    ];
+
+    let name = process.allocate_without_prototype(object_value::string(
+        "INKO_TEST_ENV_SET_WITH_INVALID_VALUE".to_string(),
+    ));
+
+    let value = process
+        .allocate_without_prototype(object_value::string("f\0o".to_string()));
+
+    process.set_register(0, name);
+    process.set_register(1, value);
+
+    assert!(machine.run(&process).is_err());
+    assert!(env::var_os("INKO_TEST_ENV_SET_WITH_INVALID_VALUE").is_none());
+}
+
+#[test]
+fn test_env_get_with_invalid_name() {
+    let (machine, mut block, process) = setup();
+
+    block.code.instructions = vec![
+        new_instruction(InstructionType::EnvGet, vec![1, 0]),
+        new_instruction(InstructionType::Return, vec![1]),
+    ];
+
+    let name = process.allocate_without_prototype(object_value::string(
+        "INKO_TEST\0ENV".to_string(),
+    ));
+
+    process.set_register(0, name);
+
+    machine.run(&process).unwrap();
+
+    assert!(process.get_register(1) == machine.state.nil_object);
+}
+
+#[test]
+fn test_env_remove() {
+    let (machine, mut block, process) = setup();
+
+    block.code.instructions = vec![
+        new_instruction(InstructionType::EnvRemove, vec![1, 0]),
+        new_instruction(InstructionType::Return, vec![1]),
+    ];
+
+    env::set_var("INKO_TEST_ENV_REMOVE", "foo");
+
+    let name = process.allocate_without_prototype(object_value::string(
+        "INKO_TEST_ENV_REMOVE".to_string(),
+    ));
+
+    process.set_register(0, name);
+
+    machine.run(&process).unwrap();
+
+    assert!(process.get_register(1) == machine.state.nil_object);
+    assert!(env::var_os("INKO_TEST_ENV_REMOVE").is_none());
+}
+
+#[test]
+fn test_env_remove_with_invalid_name() {
+    let (machine, mut block, process) = setup();
+
+    block.code.instructions = vec![
+        new_instruction(InstructionType::EnvRemove, vec![1, 0]),
+        new_instruction(InstructionType::Return, vec![1]),
+    ];
+
+    let name =
+        process.allocate_without_prototype(object_value::string(String::new()));
+
+    process.set_register(0, name);
+
+    assert!(machine.run(&process).is_err());
+}
+
+#[test]
+fn test_env_variables() {
+    let (machine, mut block, process) = setup();
+
+    block.code.instructions = vec![
+        new_instruction(InstructionType::EnvVariables, vec![0]),
+        new_instruction(InstructionType::Return, vec![0]),
+    ];
+
+    env::set_var("INKO_TEST_ENV_VARIABLES", "foo");
+
+    machine.run(&process).unwrap();
+
+    let pointer = process.get_register(0);
+    let names = pointer.get().value.as_array().unwrap();
+
+    assert!(names.iter().any(|name| {
+        name.string_value().unwrap() == "INKO_TEST_ENV_VARIABLES"
+    }));
+
+    env::remove_var("INKO_TEST_ENV_VARIABLES");
+}
+
+#[test]
+fn test_env_arguments() {
+    let (machine, mut block, process) = setup();
+
+    block.code.instructions = vec![
+        new_instruction(InstructionType::EnvArguments, vec![0]),
+        new_instruction(InstructionType::Return, vec![0]),
+    ];
+
+    machine.run(&process).unwrap();
+
+    let pointer = process.get_register(0);
+
+    assert!(pointer.get().value.as_array().unwrap().is_empty());
+}
+
+#[test]
+fn test_env_get_working_directory() {
+    let (machine, mut block, process) = setup();
+
+    block.code.instructions = vec![
+        new_instruction(InstructionType::EnvGetWorkingDirectory, vec![0]),
+        new_instruction(InstructionType::Return, vec![0]),
+    ];
+
+    machine.run(&process).unwrap();
+
+    assert_eq!(
+        process.get_register(0).string_value().unwrap(),
+        &env::current_dir().unwrap().to_string_lossy().to_string()
+    );
+}
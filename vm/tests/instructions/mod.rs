@@ -1,4 +1,11 @@
 mod array;
+mod child_process;
+mod env;
 mod float;
 mod integer;
 mod literals;
+mod process;
+mod reference;
+mod signal;
+mod socket;
+mod timer;
@@ -0,0 +1,933 @@
+use libinko::block::Block;
+use libinko::compiled_code::CompiledCode;
+use libinko::config::Config;
+use libinko::mailbox::{OverflowPolicy, SendResult};
+use libinko::object_pointer::ObjectPointer;
+use libinko::object_value;
+use libinko::process::{ExitReason, RcProcess};
+use libinko::process_table;
+use libinko::vm::instruction::{Instruction, InstructionType};
+use libinko::vm::machine::Machine;
+use libinko::vm::test::*;
+use std::process::Command;
+
+fn setup_with_isolated_panics() -> (Machine, Block, RcProcess) {
+    let mut config = Config::new();
+
+    config.isolate_panics = true;
+
+    setup_with_config(config)
+}
+
+#[test]
+fn test_panic_in_main_process() {
+    let (machine, mut block, process) = setup();
+
+    block.code.instructions =
+        vec![new_instruction(InstructionType::Panic, vec![0])];
+
+    let message = process
+        .allocate_without_prototype(object_value::string("oops".to_string()));
+
+    process.set_register(0, message);
+
+    machine.run_with_error_handling(&process);
+
+    assert_eq!(machine.state.current_exit_status(), 1);
+    assert!(process.exit_reason().is_none());
+}
+
+#[test]
+fn test_panic_in_other_process_without_isolation() {
+    let (machine, mut block, main_process) = setup();
+
+    block.code.instructions =
+        vec![new_instruction(InstructionType::Panic, vec![0])];
+
+    let process = machine.allocate_process(0, &block).unwrap();
+    let message = process
+        .allocate_without_prototype(object_value::string("oops".to_string()));
+
+    process.set_register(0, message);
+
+    machine.run_with_error_handling(&process);
+
+    assert_eq!(machine.state.current_exit_status(), 1);
+    assert!(process.exit_reason().is_none());
+    assert!(main_process.exit_reason().is_none());
+}
+
+#[test]
+fn test_panic_in_isolated_process() {
+    let (machine, mut block, main_process) = setup_with_isolated_panics();
+
+    block.code.instructions =
+        vec![new_instruction(InstructionType::Panic, vec![0])];
+
+    let process = machine.allocate_process(0, &block).unwrap();
+    let message = process
+        .allocate_without_prototype(object_value::string("oops".to_string()));
+
+    process.set_register(0, message);
+
+    machine.run_with_error_handling(&process);
+
+    assert_eq!(machine.state.current_exit_status(), 0);
+    assert_eq!(process.status_integer(), 5);
+
+    assert!(machine
+        .state
+        .process_table
+        .read()
+        .unwrap()
+        .get(process.pid)
+        .is_none());
+
+    assert!(machine
+        .state
+        .process_table
+        .read()
+        .unwrap()
+        .get(main_process.pid)
+        .is_some());
+
+    match process.exit_reason() {
+        Some(ExitReason::Panic(message, stacktrace)) => {
+            assert_eq!(message, "oops".to_string());
+            assert_eq!(stacktrace, vec!["\"a\", line 1, in \"a\"".to_string()]);
+        }
+        _ => panic!("expected the process to exit with a panic"),
+    }
+}
+
+#[test]
+fn test_process_monitor() {
+    let (machine, mut block, process) = setup();
+
+    block.code.instructions = vec![
+        new_instruction(InstructionType::ProcessMonitor, vec![2, 0]),
+        new_instruction(InstructionType::ProcessMonitor, vec![3, 1]),
+        new_instruction(InstructionType::Return, vec![2]),
+    ];
+
+    let target = machine.allocate_process(0, &block).unwrap();
+
+    process.set_register(0, ObjectPointer::integer(target.pid as i64));
+    process.set_register(1, ObjectPointer::integer(1000));
+
+    machine.run(&process).unwrap();
+
+    assert!(process.get_register(2) == machine.state.true_object);
+    assert!(process.get_register(3) == machine.state.false_object);
+    assert!(target
+        .watchers
+        .lock()
+        .unwrap()
+        .monitors
+        .contains(&process.pid));
+}
+
+#[test]
+fn test_process_link() {
+    let (machine, mut block, process) = setup();
+
+    block.code.instructions = vec![
+        new_instruction(InstructionType::ProcessLink, vec![2, 0]),
+        new_instruction(InstructionType::ProcessLink, vec![3, 1]),
+        new_instruction(InstructionType::Return, vec![2]),
+    ];
+
+    let target = machine.allocate_process(0, &block).unwrap();
+
+    process.set_register(0, ObjectPointer::integer(target.pid as i64));
+    process.set_register(1, ObjectPointer::integer(process.pid as i64));
+
+    machine.run(&process).unwrap();
+
+    assert!(process.get_register(2) == machine.state.true_object);
+    assert!(process.get_register(3) == machine.state.false_object);
+
+    // The link is removed from the target once the current process finished.
+    assert!(target.watchers.lock().unwrap().links.is_empty());
+}
+
+#[test]
+fn test_exit_message_sent_to_monitors() {
+    let (machine, mut block, process) = setup();
+
+    block.code.instructions =
+        vec![new_instruction(InstructionType::Return, vec![0])];
+
+    let target = machine.allocate_process(0, &block).unwrap();
+
+    assert!(target.add_monitor(process.pid));
+
+    machine.run(&target).unwrap();
+
+    let message = process.receive_message().unwrap();
+    let values = message.get().value.as_array().unwrap().clone();
+
+    assert_eq!(values[0].integer_value().unwrap(), target.pid as i64);
+    assert_eq!(values[1].integer_value().unwrap(), 0);
+    assert!(values[2] == machine.state.nil_object);
+}
+
+#[test]
+fn test_linked_process_terminated_after_panic() {
+    let (machine, mut block, process) = setup_with_isolated_panics();
+
+    block.code.instructions =
+        vec![new_instruction(InstructionType::Panic, vec![0])];
+
+    let target = machine.allocate_process(0, &block).unwrap();
+    let message = target
+        .allocate_without_prototype(object_value::string("oops".to_string()));
+
+    target.set_register(0, message);
+
+    assert!(target.add_link(process.pid));
+    assert!(process.add_link(target.pid));
+
+    machine.run_with_error_handling(&target);
+
+    match process.pending_termination() {
+        Some(ExitReason::Panic(message, _)) => {
+            assert_eq!(message, "oops".to_string());
+        }
+        _ => panic!("expected the linked process to be terminated"),
+    }
+
+    assert!(process.receive_message().is_none());
+    assert!(process.watchers.lock().unwrap().links.is_empty());
+}
+
+#[test]
+fn test_linked_process_trapping_exits_after_panic() {
+    let (machine, mut block, process) = setup_with_isolated_panics();
+
+    block.code.instructions =
+        vec![new_instruction(InstructionType::Panic, vec![0])];
+
+    let target = machine.allocate_process(0, &block).unwrap();
+    let message = target
+        .allocate_without_prototype(object_value::string("oops".to_string()));
+
+    target.set_register(0, message);
+
+    assert!(target.add_link(process.pid));
+    assert!(process.add_link(target.pid));
+
+    process.set_trap_exits(true);
+
+    machine.run_with_error_handling(&target);
+
+    assert!(!process.is_termination_requested());
+
+    let message = process.receive_message().unwrap();
+    let values = message.get().value.as_array().unwrap().clone();
+
+    assert_eq!(values[0].integer_value().unwrap(), target.pid as i64);
+    assert_eq!(values[1].integer_value().unwrap(), 1);
+    assert_eq!(values[2].string_value().unwrap(), "oops");
+}
+
+#[test]
+fn test_run_process_with_pending_termination() {
+    let (machine, mut block, _process) = setup();
+
+    block.code.instructions =
+        vec![new_instruction(InstructionType::Return, vec![0])];
+
+    let target = machine.allocate_process(0, &block).unwrap();
+    let reason = ExitReason::Panic("oops".to_string(), Vec::new());
+
+    target.request_termination(reason.clone());
+
+    machine.run(&target).unwrap();
+
+    assert_eq!(target.exit_reason(), Some(reason));
+    assert_eq!(target.status_integer(), 5);
+}
+
+#[test]
+fn test_process_trap_exits() {
+    let (machine, mut block, process) = setup();
+
+    block.code.instructions = vec![
+        new_instruction(InstructionType::ProcessTrapExits, vec![1, 0]),
+        new_instruction(InstructionType::Return, vec![1]),
+    ];
+
+    process.set_register(0, machine.state.true_object);
+
+    machine.run(&process).unwrap();
+
+    assert!(process.traps_exits());
+}
+
+#[test]
+fn test_process_terminate() {
+    let (machine, mut block, process) = setup();
+
+    block.code.instructions = vec![
+        new_instruction(InstructionType::ProcessTerminate, vec![3, 0, 2]),
+        new_instruction(InstructionType::ProcessTerminate, vec![4, 1, 2]),
+        new_instruction(InstructionType::Return, vec![3]),
+    ];
+
+    let target = machine.allocate_process(0, &block).unwrap();
+    let reason = process
+        .allocate_without_prototype(object_value::string("stop".to_string()));
+
+    process.set_register(0, ObjectPointer::integer(target.pid as i64));
+    process.set_register(1, ObjectPointer::integer(1000));
+    process.set_register(2, reason);
+
+    machine.run(&process).unwrap();
+
+    assert!(process.get_register(3) == machine.state.true_object);
+    assert!(process.get_register(4) == machine.state.false_object);
+    assert!(target.is_termination_requested());
+
+    machine.run(&target).unwrap();
+
+    assert_eq!(
+        target.exit_reason(),
+        Some(ExitReason::Terminated("stop".to_string()))
+    );
+
+    assert_eq!(target.status_integer(), 5);
+    assert!(machine
+        .state
+        .process_table
+        .read()
+        .unwrap()
+        .get(target.pid)
+        .is_none());
+}
+
+#[test]
+fn test_process_terminate_current_process() {
+    let (machine, mut block, _process) = setup();
+
+    block.code.instructions = vec![
+        new_instruction(InstructionType::ProcessTerminate, vec![2, 0, 1]),
+        new_instruction(InstructionType::Panic, vec![1]),
+    ];
+
+    let target = machine.allocate_process(0, &block).unwrap();
+    let reason = target
+        .allocate_without_prototype(object_value::string("stop".to_string()));
+
+    target.set_register(0, ObjectPointer::integer(target.pid as i64));
+    target.set_register(1, reason);
+
+    machine.run(&target).unwrap();
+
+    assert_eq!(
+        target.exit_reason(),
+        Some(ExitReason::Terminated("stop".to_string()))
+    );
+}
+
+#[test]
+fn test_process_register() {
+    let (machine, mut block, process) = setup();
+
+    block.code.instructions = vec![
+        new_instruction(InstructionType::ProcessRegister, vec![3, 0, 1]),
+        new_instruction(InstructionType::ProcessRegister, vec![4, 0, 1]),
+        new_instruction(InstructionType::ProcessRegister, vec![5, 2, 1]),
+        new_instruction(InstructionType::ProcessWhereis, vec![6, 0]),
+        new_instruction(InstructionType::ProcessWhereis, vec![7, 2]),
+        new_instruction(InstructionType::ProcessRegisteredNames, vec![8]),
+        new_instruction(InstructionType::Return, vec![3]),
+    ];
+
+    let target = machine.allocate_process(0, &block).unwrap();
+    let logger = process
+        .allocate_without_prototype(object_value::string("logger".to_string()));
+
+    let config = process
+        .allocate_without_prototype(object_value::string("config".to_string()));
+
+    process.set_register(0, logger);
+    process.set_register(1, ObjectPointer::integer(target.pid as i64));
+    process.set_register(2, config);
+
+    machine.run(&process).unwrap();
+
+    assert!(process.get_register(3) == machine.state.true_object);
+    assert!(process.get_register(4) == machine.state.false_object);
+    assert!(process.get_register(5) == machine.state.false_object);
+
+    assert_eq!(
+        process.get_register(6).integer_value().unwrap(),
+        target.pid as i64
+    );
+
+    assert!(process.get_register(7) == machine.state.nil_object);
+
+    let names = process
+        .get_register(8)
+        .get()
+        .value
+        .as_array()
+        .unwrap()
+        .clone();
+
+    assert_eq!(names.len(), 1);
+    assert_eq!(names[0].string_value().unwrap(), "logger");
+}
+
+#[test]
+fn test_process_register_missing_process() {
+    let (machine, mut block, process) = setup();
+
+    block.code.instructions = vec![
+        new_instruction(InstructionType::ProcessRegister, vec![2, 0, 1]),
+        new_instruction(InstructionType::Return, vec![2]),
+    ];
+
+    let name = process
+        .allocate_without_prototype(object_value::string("logger".to_string()));
+
+    process.set_register(0, name);
+    process.set_register(1, ObjectPointer::integer(1000));
+
+    machine.run(&process).unwrap();
+
+    assert!(process.get_register(2) == machine.state.false_object);
+}
+
+#[test]
+fn test_process_unregister() {
+    let (machine, mut block, process) = setup();
+
+    block.code.instructions = vec![
+        new_instruction(InstructionType::ProcessRegister, vec![2, 0, 1]),
+        new_instruction(InstructionType::ProcessUnregister, vec![3, 0]),
+        new_instruction(InstructionType::ProcessUnregister, vec![4, 0]),
+        new_instruction(InstructionType::ProcessWhereis, vec![5, 0]),
+        new_instruction(InstructionType::Return, vec![2]),
+    ];
+
+    let target = machine.allocate_process(0, &block).unwrap();
+    let name = process
+        .allocate_without_prototype(object_value::string("logger".to_string()));
+
+    process.set_register(0, name);
+    process.set_register(1, ObjectPointer::integer(target.pid as i64));
+
+    machine.run(&process).unwrap();
+
+    assert!(process.get_register(3) == machine.state.true_object);
+    assert!(process.get_register(4) == machine.state.false_object);
+    assert!(process.get_register(5) == machine.state.nil_object);
+}
+
+#[test]
+fn test_process_send_message_to_registered_name() {
+    let (machine, mut block, process) = setup();
+
+    block.code.instructions = vec![
+        new_instruction(InstructionType::ProcessRegister, vec![3, 0, 1]),
+        new_instruction(InstructionType::ProcessSendMessage, vec![4, 0, 2]),
+        new_instruction(InstructionType::Return, vec![3]),
+    ];
+
+    let target = machine.allocate_process(0, &block).unwrap();
+    let name = process
+        .allocate_without_prototype(object_value::string("logger".to_string()));
+
+    process.set_register(0, name);
+    process.set_register(1, ObjectPointer::integer(target.pid as i64));
+    process.set_register(2, ObjectPointer::integer(42));
+
+    machine.run(&process).unwrap();
+
+    let message = target.receive_message().unwrap();
+
+    assert_eq!(message.integer_value().unwrap(), 42);
+}
+
+#[test]
+fn test_process_send_message_with_child_process() {
+    let (machine, mut block, process) = setup();
+
+    block.code.instructions = vec![
+        new_instruction(InstructionType::ProcessSendMessage, vec![2, 0, 1]),
+        new_instruction(InstructionType::Return, vec![2]),
+    ];
+
+    let target = machine.allocate_process(0, &block).unwrap();
+    let child = Command::new("true").spawn().unwrap();
+    let message =
+        process.allocate_without_prototype(object_value::child_process(child));
+
+    process.set_register(0, ObjectPointer::integer(target.pid as i64));
+    process.set_register(1, message);
+
+    assert!(machine.run(&process).is_err());
+    assert!(!target.has_messages());
+}
+
+#[test]
+fn test_registered_name_removed_when_process_finishes() {
+    let (machine, mut block, _process) = setup();
+
+    block.code.instructions =
+        vec![new_instruction(InstructionType::Return, vec![0])];
+
+    let target = machine.allocate_process(0, &block).unwrap();
+    let name = machine.state.intern(&"logger".to_string());
+
+    assert!(machine
+        .state
+        .process_registry
+        .write()
+        .unwrap()
+        .register(name, target.pid));
+
+    machine.run(&target).unwrap();
+
+    assert!(machine
+        .state
+        .process_registry
+        .read()
+        .unwrap()
+        .whereis(&name)
+        .is_none());
+}
+
+/// Returns a CompiledCode for a Block that takes a single argument, and runs
+/// the given instructions.
+fn condition_code(
+    machine: &Machine,
+    instructions: Vec<Instruction>,
+) -> CompiledCode {
+    let name = machine.state.intern(&"condition".to_string());
+    let mut code = CompiledCode::new(name, name, 1, instructions);
+
+    code.arguments = vec![name];
+    code.required_arguments = 1;
+    code.locals = 1;
+    code.registers = 8;
+    code.literals = vec![ObjectPointer::integer(10)];
+
+    code
+}
+
+/// Sets up a process that receives the first message for which the condition
+/// returns true, and a process to send messages from.
+fn setup_receive_if(
+    condition: Vec<Instruction>,
+    timeout: Option<i64>,
+) -> (Machine, RcProcess, RcProcess) {
+    let (machine, mut block, process) = setup();
+
+    block.code.code_objects = vec![condition_code(&machine, condition)];
+    block.code.instructions = vec![
+        new_instruction(InstructionType::SetBlock, vec![1, 0]),
+        new_instruction(
+            InstructionType::ProcessReceiveMessageIf,
+            vec![2, 1, 3],
+        ),
+        new_instruction(InstructionType::Return, vec![0, 2]),
+    ];
+
+    let timeout_ptr = if let Some(time) = timeout {
+        ObjectPointer::integer(time)
+    } else {
+        machine.state.nil_object
+    };
+
+    process.set_register(3, timeout_ptr);
+
+    let sender = machine.allocate_process(0, &block).unwrap();
+
+    (machine, process, sender)
+}
+
+/// Returns the instructions of a condition that checks if a message equals 10.
+fn equals_ten() -> Vec<Instruction> {
+    vec![
+        new_instruction(InstructionType::GetLocal, vec![0, 0]),
+        new_instruction(InstructionType::SetLiteral, vec![1, 0]),
+        new_instruction(InstructionType::IntegerEquals, vec![2, 0, 1]),
+        new_instruction(InstructionType::Return, vec![0, 2]),
+    ]
+}
+
+/// Receives all messages of a process as integers.
+fn receive_integers(process: &RcProcess) -> Vec<i64> {
+    let mut values = Vec::new();
+
+    while let Some(message) = process.receive_message() {
+        values.push(message.integer_value().unwrap());
+    }
+
+    values
+}
+
+#[test]
+fn test_process_receive_message_if() {
+    let (machine, process, sender) = setup_receive_if(equals_ten(), None);
+
+    process.send_message(&sender, ObjectPointer::integer(20));
+    process.send_message(&sender, ObjectPointer::integer(10));
+    process.send_message(&sender, ObjectPointer::integer(30));
+
+    machine.run(&process).unwrap();
+
+    assert_eq!(process.get_register(2).integer_value().unwrap(), 10);
+    assert_eq!(process.mailbox_length(), 2);
+    assert_eq!(receive_integers(&process), vec![20, 30]);
+}
+
+#[test]
+fn test_process_receive_message_if_without_match() {
+    let (machine, process, sender) = setup_receive_if(equals_ten(), Some(50));
+
+    process.send_message(&sender, ObjectPointer::integer(20));
+    process.send_message(&sender, ObjectPointer::integer(30));
+
+    machine.run(&process).unwrap();
+
+    assert!(process.is_waiting_for_message());
+    assert!(!process.has_messages());
+
+    // This is what happens when the suspension list resumes the process after
+    // its timeout expires.
+    process.wakeup_after_suspension_timeout();
+
+    assert!(process.get_register(2) == machine.state.nil_object);
+    assert_eq!(receive_integers(&process), vec![20, 30]);
+}
+
+#[test]
+fn test_process_receive_message_if_with_full_mailbox() {
+    let (machine, process, sender) = setup_receive_if(equals_ten(), Some(50));
+
+    process.set_mailbox_capacity(2, OverflowPolicy::Throw);
+    process.send_message(&sender, ObjectPointer::integer(20));
+    process.send_message(&sender, ObjectPointer::integer(30));
+
+    machine.run(&process).unwrap();
+
+    // Skipped messages still take up space in the mailbox.
+    assert_eq!(process.mailbox_length(), 2);
+    assert_eq!(
+        process.try_send_message(&sender, ObjectPointer::integer(10)),
+        Ok(SendResult::Full)
+    );
+}
+
+#[test]
+fn test_process_receive_message_if_with_throwing_condition() {
+    let (machine, process, sender) = setup_receive_if(
+        vec![
+            new_instruction(InstructionType::GetLocal, vec![0, 0]),
+            new_instruction(InstructionType::Throw, vec![0]),
+        ],
+        None,
+    );
+
+    process.send_message(&sender, ObjectPointer::integer(20));
+    process.send_message(&sender, ObjectPointer::integer(30));
+
+    assert!(machine.run(&process).is_err());
+
+    // The message being tested and the skipped messages are put back.
+    assert_eq!(receive_integers(&process), vec![20, 30]);
+}
+
+#[test]
+fn test_process_set_mailbox_capacity() {
+    let (machine, mut block, process) = setup();
+
+    block.code.instructions = vec![
+        new_instruction(
+            InstructionType::ProcessSetMailboxCapacity,
+            vec![4, 0, 2, 3],
+        ),
+        new_instruction(
+            InstructionType::ProcessSetMailboxCapacity,
+            vec![5, 1, 2, 3],
+        ),
+        new_instruction(InstructionType::Return, vec![0, 4]),
+    ];
+
+    let target = machine.allocate_process(0, &block).unwrap();
+
+    process.set_register(0, ObjectPointer::integer(target.pid as i64));
+    process.set_register(1, ObjectPointer::integer(1000));
+    process.set_register(2, ObjectPointer::integer(1));
+    process.set_register(3, ObjectPointer::integer(1));
+
+    machine.run(&process).unwrap();
+
+    assert!(process.get_register(4) == machine.state.true_object);
+    assert!(process.get_register(5) == machine.state.false_object);
+
+    let mailbox = &target.local_data().mailbox;
+
+    assert_eq!(mailbox.capacity, 1);
+    assert_eq!(mailbox.overflow, OverflowPolicy::DropNewest);
+}
+
+#[test]
+fn test_process_set_mailbox_capacity_with_invalid_policy() {
+    let (machine, mut block, process) = setup();
+
+    block.code.instructions = vec![new_instruction(
+        InstructionType::ProcessSetMailboxCapacity,
+        vec![3, 0, 1, 2],
+    )];
+
+    process.set_register(0, ObjectPointer::integer(process.pid as i64));
+    process.set_register(1, ObjectPointer::integer(1));
+    process.set_register(2, ObjectPointer::integer(10));
+
+    assert!(machine.run(&process).is_err());
+}
+
+#[test]
+fn test_process_mailbox_length() {
+    let (machine, mut block, process) = setup();
+
+    block.code.instructions = vec![
+        new_instruction(InstructionType::ProcessMailboxLength, vec![2, 0]),
+        new_instruction(InstructionType::ProcessMailboxLength, vec![3, 1]),
+        new_instruction(InstructionType::Return, vec![0, 2]),
+    ];
+
+    let target = machine.allocate_process(0, &block).unwrap();
+
+    target.send_message(&process, ObjectPointer::integer(1));
+    target.send_message(&process, ObjectPointer::integer(2));
+
+    process.set_register(0, ObjectPointer::integer(target.pid as i64));
+    process.set_register(1, ObjectPointer::integer(1000));
+
+    machine.run(&process).unwrap();
+
+    assert_eq!(process.get_register(2).integer_value().unwrap(), 2);
+    assert_eq!(process.get_register(3).integer_value().unwrap(), 0);
+}
+
+/// Sets up a process that sends a message to a process with a full mailbox.
+fn setup_send_to_full_mailbox(
+    overflow: OverflowPolicy,
+) -> (Machine, RcProcess, RcProcess) {
+    let (machine, mut block, process) = setup();
+
+    block.code.instructions = vec![
+        new_instruction(InstructionType::ProcessSendMessage, vec![2, 0, 1]),
+        new_instruction(InstructionType::Return, vec![0, 2]),
+    ];
+
+    let target = machine.allocate_process(0, &block).unwrap();
+
+    target.set_mailbox_capacity(1, overflow);
+    target.send_message(&process, ObjectPointer::integer(1));
+
+    process.set_register(0, ObjectPointer::integer(target.pid as i64));
+    process.set_register(1, ObjectPointer::integer(2));
+
+    (machine, process, target)
+}
+
+#[test]
+fn test_process_send_message_to_full_mailbox_with_suspend_policy() {
+    let (machine, process, target) =
+        setup_send_to_full_mailbox(OverflowPolicy::Suspend);
+
+    machine.run(&process).unwrap();
+
+    // The instruction is retried once the process is resumed.
+    assert_eq!(process.context().instruction_index, 0);
+    assert_eq!(target.mailbox_length(), 1);
+    assert!(machine
+        .state
+        .suspension_list
+        .suspended
+        .lock()
+        .unwrap()
+        .waiting_for_space
+        .contains_key(&target.pid));
+}
+
+fn is_suspended(machine: &Machine, process: &RcProcess) -> bool {
+    machine
+        .state
+        .suspension_list
+        .suspended
+        .lock()
+        .unwrap()
+        .processes
+        .contains_key(&process.pid)
+}
+
+#[test]
+fn test_process_receive_message_resumes_sender_suspended_after_refill() {
+    let (machine, mut block, receiver) = setup();
+
+    block.code.instructions = vec![
+        new_instruction(InstructionType::ProcessReceiveMessage, vec![0, 1]),
+        new_instruction(InstructionType::ProcessReceiveMessage, vec![0, 1]),
+        new_instruction(InstructionType::ProcessReceiveMessage, vec![0, 1]),
+        new_instruction(InstructionType::Return, vec![0]),
+    ];
+
+    let sender = machine.allocate_process(0, &block).unwrap();
+    let other = machine.allocate_process(0, &block).unwrap();
+
+    receiver.set_register(1, machine.state.nil_object);
+    receiver.set_mailbox_capacity(1, OverflowPolicy::Suspend);
+    receiver.send_message(&other, ObjectPointer::integer(1));
+
+    assert_eq!(
+        receiver.try_send_message(&sender, ObjectPointer::integer(2)),
+        Ok(SendResult::Suspend)
+    );
+
+    // The receiver makes space before the sender is suspended, after which
+    // another process fills up the mailbox again.
+    machine.run(&receiver).unwrap();
+
+    receiver.send_message(&other, ObjectPointer::integer(3));
+    sender.running();
+
+    machine.state.suspension_list.suspend_until_space(
+        &machine.state,
+        sender.clone(),
+        &receiver,
+    );
+
+    assert!(is_suspended(&machine, &sender));
+
+    machine.run(&receiver).unwrap();
+
+    assert_eq!(receiver.mailbox_length(), 0);
+    assert_eq!(is_suspended(&machine, &sender), false);
+}
+
+#[test]
+fn test_process_send_message_to_full_mailbox_with_drop_newest_policy() {
+    let (machine, process, target) =
+        setup_send_to_full_mailbox(OverflowPolicy::DropNewest);
+
+    machine.run(&process).unwrap();
+
+    assert_eq!(process.get_register(2).integer_value().unwrap(), 2);
+    assert_eq!(
+        target.receive_message().unwrap().integer_value().unwrap(),
+        1
+    );
+    assert!(target.receive_message().is_none());
+}
+
+#[test]
+fn test_process_send_message_to_full_mailbox_with_drop_oldest_policy() {
+    let (machine, process, target) =
+        setup_send_to_full_mailbox(OverflowPolicy::DropOldest);
+
+    machine.run(&process).unwrap();
+
+    assert_eq!(
+        target.receive_message().unwrap().integer_value().unwrap(),
+        2
+    );
+    assert!(target.receive_message().is_none());
+}
+
+#[test]
+fn test_process_send_message_to_full_mailbox_with_throw_policy() {
+    let (machine, process, target) =
+        setup_send_to_full_mailbox(OverflowPolicy::Throw);
+
+    assert!(machine.run(&process).is_err());
+    assert_eq!(target.mailbox_length(), 1);
+}
+
+/// Returns a PID that uses the index of the given PID, but a different epoch,
+/// as is the case for a PID of which the index has been recycled.
+fn stale_pid(pid: usize) -> usize {
+    pid + process_table::MAX_INDEX + 1
+}
+
+/// Sets up a process that operates on two PIDs: one of a process that has
+/// finished, and a stale PID of which the index is used by a live process.
+fn setup_stale_pids(
+    instructions: Vec<Instruction>,
+) -> (Machine, RcProcess, RcProcess) {
+    let (machine, mut block, process) = setup();
+
+    block.code.instructions =
+        vec![new_instruction(InstructionType::Return, vec![0])];
+
+    let finished = machine.allocate_process(0, &block).unwrap();
+    let live = machine.allocate_process(0, &block).unwrap();
+
+    machine.run(&finished).unwrap();
+
+    block.code.instructions = instructions;
+
+    let reason = process
+        .allocate_without_prototype(object_value::string("stop".to_string()));
+
+    process.set_register(0, ObjectPointer::integer(finished.pid as i64));
+    process.set_register(1, ObjectPointer::integer(stale_pid(live.pid) as i64));
+    process.set_register(2, reason);
+
+    (machine, process, live)
+}
+
+#[test]
+fn test_process_send_message_to_released_or_stale_pid() {
+    let (machine, process, live) = setup_stale_pids(vec![
+        new_instruction(InstructionType::ProcessSendMessage, vec![3, 0, 2]),
+        new_instruction(InstructionType::ProcessSendMessage, vec![4, 1, 2]),
+        new_instruction(InstructionType::Return, vec![3]),
+    ]);
+
+    machine.run(&process).unwrap();
+
+    assert!(process.get_register(3) == process.get_register(2));
+    assert!(process.get_register(4) == process.get_register(2));
+    assert_eq!(live.mailbox_length(), 0);
+}
+
+#[test]
+fn test_process_terminate_released_or_stale_pid() {
+    let (machine, process, live) = setup_stale_pids(vec![
+        new_instruction(InstructionType::ProcessTerminate, vec![3, 0, 2]),
+        new_instruction(InstructionType::ProcessTerminate, vec![4, 1, 2]),
+        new_instruction(InstructionType::Return, vec![3]),
+    ]);
+
+    machine.run(&process).unwrap();
+
+    assert!(process.get_register(3) == machine.state.false_object);
+    assert!(process.get_register(4) == machine.state.false_object);
+    assert_eq!(live.is_termination_requested(), false);
+}
+
+#[test]
+fn test_process_status_of_released_or_stale_pid() {
+    let (machine, process, live) = setup_stale_pids(vec![
+        new_instruction(InstructionType::ProcessStatus, vec![3, 0]),
+        new_instruction(InstructionType::ProcessStatus, vec![4, 1]),
+        new_instruction(InstructionType::ProcessStatus, vec![5, 6]),
+        new_instruction(InstructionType::Return, vec![3]),
+    ]);
+
+    process.set_register(6, ObjectPointer::integer(live.pid as i64));
+
+    machine.run(&process).unwrap();
+
+    assert_eq!(process.get_register(3).integer_value().unwrap(), 5);
+    assert_eq!(process.get_register(4).integer_value().unwrap(), 5);
+    assert_eq!(process.get_register(5).integer_value().unwrap(), 0);
+}
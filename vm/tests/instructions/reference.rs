@@ -0,0 +1,52 @@
+use libinko::object_pointer::ObjectPointer;
+use libinko::object_value;
+use libinko::vm::instruction::InstructionType;
+use libinko::vm::test::*;
+
+#[test]
+fn test_reference_new() {
+    let (machine, mut block, process) = setup();
+
+    block.code.instructions = vec![
+        new_instruction(InstructionType::ReferenceNew, vec![0]),
+        new_instruction(InstructionType::ReferenceNew, vec![1]),
+        new_instruction(InstructionType::Return, vec![1]),
+    ];
+
+    machine.run(&process).unwrap();
+
+    let ref1 = process.get_register(0);
+    let ref2 = process.get_register(1);
+
+    assert!(ref1.get().value.is_reference());
+    assert!(ref2.get().value.is_reference());
+
+    assert!(ref1.reference_value().unwrap() != ref2.reference_value().unwrap());
+}
+
+#[test]
+fn test_reference_equals() {
+    let (machine, mut block, process) = setup();
+
+    block.code.instructions = vec![
+        new_instruction(InstructionType::ReferenceEquals, vec![3, 0, 1]),
+        new_instruction(InstructionType::ReferenceEquals, vec![4, 0, 2]),
+        new_instruction(InstructionType::ReferenceEquals, vec![5, 0, 6]),
+        new_instruction(InstructionType::Return, vec![4]),
+    ];
+
+    let ref1 = process.allocate_without_prototype(object_value::reference(1));
+    let ref2 = process.allocate_without_prototype(object_value::reference(1));
+    let ref3 = process.allocate_without_prototype(object_value::reference(2));
+
+    process.set_register(0, ref1);
+    process.set_register(1, ref2);
+    process.set_register(2, ref3);
+    process.set_register(6, ObjectPointer::integer(1));
+
+    machine.run(&process).unwrap();
+
+    assert!(process.get_register(3) == machine.state.true_object);
+    assert!(process.get_register(4) == machine.state.false_object);
+    assert!(process.get_register(5) == machine.state.false_object);
+}
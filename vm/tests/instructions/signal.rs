@@ -0,0 +1,113 @@
+use libc;
+use libinko::object_pointer::ObjectPointer;
+use libinko::vm::instruction::InstructionType;
+use libinko::vm::test::*;
+use std::thread;
+use std::time::Duration;
+
+/// The number of SIGUSR1 on Linux.
+const SIGUSR1: i64 = 10;
+
+/// The number of SIGKILL, which processes can't subscribe to.
+const SIGKILL: i64 = 9;
+
+#[test]
+fn test_signal_subscribe() {
+    let (machine, mut block, process) = setup();
+
+    block.code.instructions = vec![
+        new_instruction(InstructionType::SignalSubscribe, vec![1, 0]),
+        new_instruction(InstructionType::Return, vec![1]),
+    ];
+
+    process.set_register(0, ObjectPointer::integer(SIGUSR1));
+
+    machine.run(&process).unwrap();
+
+    assert!(process.get_register(1) == machine.state.nil_object);
+}
+
+#[test]
+fn test_signal_subscribe_with_unsupported_signal() {
+    let (machine, mut block, process) = setup();
+
+    block.code.instructions = vec![
+        new_instruction(InstructionType::SignalSubscribe, vec![1, 0]),
+        new_instruction(InstructionType::Return, vec![1]),
+    ];
+
+    process.set_register(0, ObjectPointer::integer(SIGKILL));
+
+    assert!(machine.run(&process).is_err());
+}
+
+#[test]
+fn test_signal_unsubscribe() {
+    let (machine, mut block, process) = setup();
+
+    block.code.instructions = vec![
+        new_instruction(InstructionType::SignalSubscribe, vec![1, 0]),
+        new_instruction(InstructionType::SignalUnsubscribe, vec![2, 0]),
+        new_instruction(InstructionType::Return, vec![2]),
+    ];
+
+    process.set_register(0, ObjectPointer::integer(SIGUSR1));
+
+    machine.run(&process).unwrap();
+
+    assert!(process.get_register(2) == machine.state.nil_object);
+}
+
+#[test]
+fn test_signal_delivered_to_subscribed_process() {
+    let (machine, mut block, process) = setup();
+
+    block.code.instructions = vec![
+        new_instruction(InstructionType::SignalSubscribe, vec![1, 0]),
+        new_instruction(InstructionType::ProcessReceiveMessage, vec![2, 3]),
+        new_instruction(InstructionType::Return, vec![2]),
+    ];
+
+    // SIGUSR1 is used by the other tests, which restore its default action
+    // when they finish.
+    process.set_register(0, ObjectPointer::integer(i64::from(libc::SIGUSR2)));
+    process.set_register(3, machine.state.nil_object);
+
+    // This subscribes the process, then suspends it until a message arrives.
+    machine.run(&process).unwrap();
+
+    let state = machine.state.clone();
+    let handler = thread::spawn(move || state.signal_handler.run(&state));
+
+    for _ in 0..100 {
+        // Other tests may install their own signal pipe in the mean time, so
+        // we subscribe again before raising the signal.
+        let signal_handler = &machine.state.signal_handler;
+
+        signal_handler
+            .unsubscribe(libc::SIGUSR2, process.pid)
+            .unwrap();
+        signal_handler
+            .subscribe(libc::SIGUSR2, process.clone())
+            .unwrap();
+
+        unsafe {
+            libc::raise(libc::SIGUSR2);
+        }
+
+        thread::sleep(Duration::from_millis(10));
+
+        if process.has_messages() {
+            break;
+        }
+    }
+
+    machine.state.signal_handler.terminate();
+    handler.join().unwrap();
+    machine.run(&process).unwrap();
+
+    assert_eq!(
+        process.get_register(2).integer_value().unwrap(),
+        i64::from(libc::SIGUSR2)
+    );
+}
@@ -0,0 +1,660 @@
+use libinko::object_pointer::ObjectPointer;
+use libinko::object_value;
+use libinko::process::RcProcess;
+use libinko::vm::instruction::InstructionType;
+use libinko::vm::test::*;
+use std::env;
+use std::fs;
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream, UdpSocket};
+use std::os::unix::net::UnixStream;
+use std::process;
+use std::thread;
+
+fn string(process: &RcProcess, value: &str) -> ObjectPointer {
+    process.allocate_without_prototype(object_value::string(value.to_string()))
+}
+
+fn socket_path(name: &str) -> String {
+    let path = env::temp_dir().join(format!("inko-{}-{}", process::id(), name));
+
+    let _ = fs::remove_file(&path);
+
+    path.to_str().unwrap().to_string()
+}
+
+#[test]
+fn test_tcp_listen() {
+    let (machine, mut block, process) = setup();
+
+    block.code.instructions = vec![
+        new_instruction(InstructionType::TcpListen, vec![1, 0]),
+        new_instruction(InstructionType::SocketLocalAddress, vec![2, 1]),
+        new_instruction(InstructionType::Return, vec![2]),
+    ];
+
+    process.set_register(0, string(&process, "127.0.0.1:0"));
+
+    machine.run(&process).unwrap();
+
+    let address = process.get_register(2).string_value().unwrap().clone();
+
+    assert!(address.starts_with("127.0.0.1:"));
+    assert!(TcpStream::connect(address).is_ok());
+}
+
+#[test]
+fn test_tcp_listen_with_invalid_address() {
+    let (machine, mut block, process) = setup();
+
+    block.code.instructions = vec![
+        new_instruction(InstructionType::TcpListen, vec![1, 0]),
+        new_instruction(InstructionType::Return, vec![1]),
+    ];
+
+    process.set_register(0, string(&process, "localhost"));
+
+    assert!(machine.run(&process).is_err());
+}
+
+#[test]
+fn test_socket_accept_without_connections() {
+    let (machine, mut block, process) = setup();
+
+    block.code.instructions = vec![
+        new_instruction(InstructionType::TcpListen, vec![1, 0]),
+        new_instruction(InstructionType::SocketAccept, vec![2, 1]),
+        new_instruction(InstructionType::Return, vec![2]),
+    ];
+
+    process.set_register(0, string(&process, "127.0.0.1:0"));
+
+    machine.run(&process).unwrap();
+
+    assert!(process.is_waiting_for_io());
+    assert_eq!(process.context().instruction_index, 1);
+}
+
+#[test]
+fn test_socket_accept_resumes_process() {
+    let (machine, mut block, process) = setup();
+
+    block.code.instructions = vec![
+        new_instruction(InstructionType::SocketAccept, vec![1, 0]),
+        new_instruction(InstructionType::SocketPeerAddress, vec![2, 1]),
+        new_instruction(InstructionType::Return, vec![2]),
+    ];
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+
+    listener.set_nonblocking(true).unwrap();
+
+    process.set_register(
+        0,
+        process
+            .allocate_without_prototype(object_value::tcp_listener(listener)),
+    );
+
+    machine.run(&process).unwrap();
+
+    assert!(process.is_waiting_for_io());
+
+    let state = machine.state.clone();
+    let poller = thread::spawn(move || state.network_poller.poll(&state));
+    let client = TcpStream::connect(address).unwrap();
+
+    while !process.available_for_execution() {
+        thread::yield_now();
+    }
+
+    machine.state.network_poller.terminate();
+    poller.join().unwrap();
+
+    machine.run(&process).unwrap();
+
+    assert_eq!(
+        process.get_register(2).string_value().unwrap(),
+        &client.local_addr().unwrap().to_string()
+    );
+}
+
+#[test]
+fn test_tcp_connect() {
+    let (machine, mut block, process) = setup();
+
+    block.code.instructions = vec![
+        new_instruction(InstructionType::TcpConnect, vec![1, 0]),
+        new_instruction(InstructionType::SocketFinishConnect, vec![2, 1]),
+        new_instruction(InstructionType::Return, vec![1]),
+    ];
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+
+    process.set_register(0, string(&process, &address));
+
+    // Connecting to a loopback address may complete right away, or the
+    // process may have to wait for the connection to be established.
+    machine.run(&process).unwrap();
+
+    while process.is_waiting_for_io() {
+        machine
+            .state
+            .suspension_list
+            .resume_for_io(&machine.state, process.pid);
+        machine.run(&process).unwrap();
+    }
+
+    let stream = process.get_register(1);
+
+    assert!(process.get_register(2) == machine.state.nil_object);
+    assert_eq!(
+        stream
+            .tcp_stream_value()
+            .unwrap()
+            .peer_addr()
+            .unwrap()
+            .to_string(),
+        address
+    );
+}
+
+#[test]
+fn test_tcp_connect_refused() {
+    let (machine, mut block, process) = setup();
+
+    block.code.instructions = vec![
+        new_instruction(InstructionType::TcpConnect, vec![1, 0]),
+        new_instruction(InstructionType::SocketFinishConnect, vec![2, 1]),
+        new_instruction(InstructionType::Return, vec![2]),
+    ];
+
+    let address = {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+
+        listener.local_addr().unwrap().to_string()
+    };
+
+    process.set_register(0, string(&process, &address));
+
+    let mut result = machine.run(&process);
+
+    while result.is_ok() && process.is_waiting_for_io() {
+        machine
+            .state
+            .suspension_list
+            .resume_for_io(&machine.state, process.pid);
+        result = machine.run(&process);
+    }
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_socket_write_and_read() {
+    let (machine, mut block, process) = setup();
+
+    block.code.instructions = vec![
+        new_instruction(InstructionType::SocketWrite, vec![3, 0, 1]),
+        new_instruction(InstructionType::SocketRead, vec![4, 0, 2, 5]),
+        new_instruction(InstructionType::Return, vec![4]),
+    ];
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let (mut server, _) = listener.accept().unwrap();
+
+    stream.set_nonblocking(true).unwrap();
+    server.write_all(b"world").unwrap();
+
+    process.set_register(
+        0,
+        process.allocate_without_prototype(object_value::tcp_stream(stream)),
+    );
+
+    process.set_register(1, string(&process, "hello"));
+    process.set_register(
+        2,
+        process
+            .allocate_without_prototype(object_value::byte_array(Vec::new())),
+    );
+
+    process.set_register(5, ObjectPointer::integer(32));
+
+    let mut received = [0; 5];
+
+    machine.run(&process).unwrap();
+    server.read_exact(&mut received).unwrap();
+
+    while process.is_waiting_for_io() {
+        machine
+            .state
+            .suspension_list
+            .resume_for_io(&machine.state, process.pid);
+        machine.run(&process).unwrap();
+    }
+
+    assert_eq!(&received, b"hello");
+    assert_eq!(process.get_register(3).integer_value().unwrap(), 5);
+    assert_eq!(process.get_register(4).integer_value().unwrap(), 5);
+    assert_eq!(
+        process.get_register(2).byte_array_value().unwrap(),
+        &b"world".to_vec()
+    );
+}
+
+#[test]
+fn test_socket_read_without_data() {
+    let (machine, mut block, process) = setup();
+
+    block.code.instructions = vec![
+        new_instruction(InstructionType::SocketRead, vec![3, 0, 1, 2]),
+        new_instruction(InstructionType::Return, vec![3]),
+    ];
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let _server = listener.accept().unwrap();
+
+    stream.set_nonblocking(true).unwrap();
+
+    process.set_register(
+        0,
+        process.allocate_without_prototype(object_value::tcp_stream(stream)),
+    );
+
+    process.set_register(
+        1,
+        process
+            .allocate_without_prototype(object_value::byte_array(Vec::new())),
+    );
+
+    process.set_register(2, ObjectPointer::integer(32));
+
+    machine.run(&process).unwrap();
+
+    assert!(process.is_waiting_for_io());
+    assert!(process
+        .get_register(1)
+        .byte_array_value()
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn test_socket_shutdown() {
+    let (machine, mut block, process) = setup();
+
+    block.code.instructions = vec![
+        new_instruction(InstructionType::SocketShutdown, vec![2, 0, 1]),
+        new_instruction(InstructionType::Return, vec![2]),
+    ];
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let (mut server, _) = listener.accept().unwrap();
+
+    process.set_register(
+        0,
+        process.allocate_without_prototype(object_value::tcp_stream(stream)),
+    );
+
+    process.set_register(1, ObjectPointer::integer(1));
+
+    machine.run(&process).unwrap();
+
+    let mut buffer = Vec::new();
+
+    assert!(process.get_register(2) == machine.state.nil_object);
+    assert_eq!(server.read_to_end(&mut buffer).unwrap(), 0);
+}
+
+#[test]
+fn test_socket_read_available_data() {
+    let (machine, mut block, process) = setup();
+
+    block.code.instructions = vec![
+        new_instruction(InstructionType::SocketRead, vec![3, 0, 1, 2]),
+        new_instruction(InstructionType::Return, vec![3]),
+    ];
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let (mut server, _) = listener.accept().unwrap();
+
+    stream.set_nonblocking(true).unwrap();
+    server.write_all(b"hello").unwrap();
+    drop(server);
+
+    process.set_register(
+        0,
+        process.allocate_without_prototype(object_value::tcp_stream(stream)),
+    );
+
+    process.set_register(
+        1,
+        process
+            .allocate_without_prototype(object_value::byte_array(Vec::new())),
+    );
+
+    process.set_register(2, machine.state.nil_object);
+
+    machine.run(&process).unwrap();
+
+    while process.is_waiting_for_io() {
+        machine
+            .state
+            .suspension_list
+            .resume_for_io(&machine.state, process.pid);
+        machine.run(&process).unwrap();
+    }
+
+    assert_eq!(process.get_register(3).integer_value().unwrap(), 5);
+    assert_eq!(
+        process.get_register(1).byte_array_value().unwrap(),
+        &b"hello".to_vec()
+    );
+}
+
+#[test]
+fn test_udp_send_to_and_receive_from() {
+    let (machine, mut block, process) = setup();
+
+    block.code.instructions = vec![
+        new_instruction(InstructionType::UdpBind, vec![1, 0]),
+        new_instruction(InstructionType::UdpBind, vec![2, 0]),
+        new_instruction(InstructionType::SocketLocalAddress, vec![3, 2]),
+        new_instruction(InstructionType::SocketSendTo, vec![4, 1, 5, 3]),
+        new_instruction(InstructionType::SocketReceiveFrom, vec![7, 2, 6, 8]),
+        new_instruction(InstructionType::Return, vec![7]),
+    ];
+
+    process.set_register(0, string(&process, "127.0.0.1:0"));
+    process.set_register(5, string(&process, "hello"));
+    process.set_register(
+        6,
+        process
+            .allocate_without_prototype(object_value::byte_array(Vec::new())),
+    );
+
+    process.set_register(8, machine.state.nil_object);
+
+    machine.run(&process).unwrap();
+
+    while process.is_waiting_for_io() {
+        machine
+            .state
+            .suspension_list
+            .resume_for_io(&machine.state, process.pid);
+        machine.run(&process).unwrap();
+    }
+
+    let sender = process
+        .get_register(1)
+        .socket_value()
+        .unwrap()
+        .local_address()
+        .unwrap();
+
+    assert_eq!(process.get_register(4).integer_value().unwrap(), 5);
+    assert_eq!(process.get_register(7).string_value().unwrap(), &sender);
+    assert_eq!(
+        process.get_register(6).byte_array_value().unwrap(),
+        &b"hello".to_vec()
+    );
+}
+
+#[test]
+fn test_socket_receive_from_without_data() {
+    let (machine, mut block, process) = setup();
+
+    block.code.instructions = vec![
+        new_instruction(InstructionType::SocketReceiveFrom, vec![3, 0, 1, 2]),
+        new_instruction(InstructionType::Return, vec![3]),
+    ];
+
+    let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+
+    socket.set_nonblocking(true).unwrap();
+
+    process.set_register(
+        0,
+        process.allocate_without_prototype(object_value::udp_socket(socket)),
+    );
+
+    process.set_register(
+        1,
+        process
+            .allocate_without_prototype(object_value::byte_array(Vec::new())),
+    );
+
+    process.set_register(2, ObjectPointer::integer(32));
+
+    machine.run(&process).unwrap();
+
+    assert!(process.is_waiting_for_io());
+    assert_eq!(process.context().instruction_index, 0);
+}
+
+#[test]
+fn test_socket_set_and_get_option() {
+    let (machine, mut block, process) = setup();
+
+    block.code.instructions = vec![
+        new_instruction(InstructionType::UdpBind, vec![1, 0]),
+        new_instruction(InstructionType::SocketSetOption, vec![4, 1, 2, 3]),
+        new_instruction(InstructionType::SocketGetOption, vec![5, 1, 2]),
+        new_instruction(InstructionType::Return, vec![5]),
+    ];
+
+    process.set_register(0, string(&process, "127.0.0.1:0"));
+    process.set_register(2, ObjectPointer::integer(0));
+    process.set_register(3, machine.state.true_object);
+
+    machine.run(&process).unwrap();
+
+    assert!(process.get_register(4) == machine.state.nil_object);
+    assert!(process.get_register(5) == machine.state.true_object);
+}
+
+#[test]
+fn test_socket_set_unsupported_option() {
+    let (machine, mut block, process) = setup();
+
+    block.code.instructions = vec![
+        new_instruction(InstructionType::UdpBind, vec![1, 0]),
+        new_instruction(InstructionType::SocketSetOption, vec![4, 1, 2, 3]),
+        new_instruction(InstructionType::Return, vec![4]),
+    ];
+
+    process.set_register(0, string(&process, "127.0.0.1:0"));
+    process.set_register(2, ObjectPointer::integer(4));
+    process.set_register(3, machine.state.true_object);
+
+    assert!(machine.run(&process).is_err());
+}
+
+#[test]
+fn test_unix_listen_connect_and_accept() {
+    let (machine, mut block, process) = setup();
+
+    block.code.instructions = vec![
+        new_instruction(InstructionType::UnixListen, vec![1, 0]),
+        new_instruction(InstructionType::UnixConnect, vec![2, 0]),
+        new_instruction(InstructionType::SocketFinishConnect, vec![3, 2]),
+        new_instruction(InstructionType::SocketAccept, vec![4, 1]),
+        new_instruction(InstructionType::SocketWrite, vec![5, 2, 6]),
+        new_instruction(InstructionType::SocketRead, vec![7, 4, 8, 9]),
+        new_instruction(InstructionType::SocketPeerAddress, vec![10, 2]),
+        new_instruction(InstructionType::Return, vec![10]),
+    ];
+
+    let path = socket_path("instruction.sock");
+
+    process.set_register(0, string(&process, &path));
+    process.set_register(6, string(&process, "hello"));
+    process.set_register(
+        8,
+        process
+            .allocate_without_prototype(object_value::byte_array(Vec::new())),
+    );
+
+    process.set_register(9, machine.state.nil_object);
+
+    machine.run(&process).unwrap();
+
+    while process.is_waiting_for_io() {
+        machine
+            .state
+            .suspension_list
+            .resume_for_io(&machine.state, process.pid);
+        machine.run(&process).unwrap();
+    }
+
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(process.get_register(7).integer_value().unwrap(), 5);
+    assert_eq!(process.get_register(10).string_value().unwrap(), &path);
+    assert_eq!(
+        process.get_register(8).byte_array_value().unwrap(),
+        &b"hello".to_vec()
+    );
+}
+
+#[test]
+fn test_unix_connect_without_listener() {
+    let (machine, mut block, process) = setup();
+
+    block.code.instructions = vec![
+        new_instruction(InstructionType::UnixConnect, vec![1, 0]),
+        new_instruction(InstructionType::Return, vec![1]),
+    ];
+
+    process.set_register(0, string(&process, &socket_path("missing.sock")));
+
+    assert!(machine.run(&process).is_err());
+}
+
+#[test]
+fn test_unix_datagram_bind_and_connect() {
+    let (machine, mut block, process) = setup();
+
+    block.code.instructions = vec![
+        new_instruction(InstructionType::UnixDatagramBind, vec![2, 0]),
+        new_instruction(InstructionType::UnixDatagramBind, vec![3, 1]),
+        new_instruction(InstructionType::SocketConnect, vec![4, 3, 0]),
+        new_instruction(InstructionType::SocketWrite, vec![5, 3, 6]),
+        new_instruction(InstructionType::SocketRead, vec![7, 2, 8, 1]),
+        new_instruction(InstructionType::Return, vec![7]),
+    ];
+
+    let path = socket_path("datagram.sock");
+
+    process.set_register(0, string(&process, &path));
+    process.set_register(1, machine.state.nil_object);
+    process.set_register(6, string(&process, "hello"));
+    process.set_register(
+        8,
+        process
+            .allocate_without_prototype(object_value::byte_array(Vec::new())),
+    );
+
+    machine.run(&process).unwrap();
+
+    fs::remove_file(&path).unwrap();
+
+    assert!(process.get_register(4) == machine.state.nil_object);
+    assert_eq!(process.get_register(7).integer_value().unwrap(), 5);
+    assert_eq!(
+        process.get_register(8).byte_array_value().unwrap(),
+        &b"hello".to_vec()
+    );
+}
+
+#[test]
+fn test_unix_pair() {
+    let (machine, mut block, process) = setup();
+
+    block.code.instructions = vec![
+        new_instruction(InstructionType::UnixPair, vec![1, 0]),
+        new_instruction(InstructionType::Return, vec![1]),
+    ];
+
+    process.set_register(0, ObjectPointer::integer(0));
+
+    machine.run(&process).unwrap();
+
+    let pair = process.get_register(1);
+    let sockets = pair.array_value().unwrap();
+    let mut buffer = Vec::new();
+
+    assert_eq!(sockets.len(), 2);
+
+    sockets[0].socket_value().unwrap().write(b"hello").unwrap();
+
+    assert_eq!(
+        sockets[1]
+            .socket_value()
+            .unwrap()
+            .read(&mut buffer, None)
+            .unwrap(),
+        5
+    );
+}
+
+#[test]
+fn test_unix_pair_with_invalid_type() {
+    let (machine, mut block, process) = setup();
+
+    block.code.instructions = vec![
+        new_instruction(InstructionType::UnixPair, vec![1, 0]),
+        new_instruction(InstructionType::Return, vec![1]),
+    ];
+
+    process.set_register(0, ObjectPointer::integer(2));
+
+    assert!(machine.run(&process).is_err());
+}
+
+#[test]
+fn test_socket_read_from_unix_stream() {
+    let (machine, mut block, process) = setup();
+
+    block.code.instructions = vec![
+        new_instruction(InstructionType::SocketRead, vec![3, 0, 1, 2]),
+        new_instruction(InstructionType::Return, vec![3]),
+    ];
+
+    let (stream, mut other) = UnixStream::pair().unwrap();
+
+    stream.set_nonblocking(true).unwrap();
+
+    process.set_register(
+        0,
+        process.allocate_without_prototype(object_value::unix_stream(stream)),
+    );
+
+    process.set_register(
+        1,
+        process
+            .allocate_without_prototype(object_value::byte_array(Vec::new())),
+    );
+
+    process.set_register(2, ObjectPointer::integer(32));
+
+    machine.run(&process).unwrap();
+
+    assert!(process.is_waiting_for_io());
+
+    other.write_all(b"hello").unwrap();
+
+    machine
+        .state
+        .suspension_list
+        .resume_for_io(&machine.state, process.pid);
+
+    machine.run(&process).unwrap();
+
+    assert_eq!(process.get_register(3).integer_value().unwrap(), 5);
+}
@@ -0,0 +1,164 @@
+use libinko::object_pointer::ObjectPointer;
+use libinko::object_value;
+use libinko::vm::instruction::InstructionType;
+use libinko::vm::test::*;
+use std::process::Command;
+
+#[test]
+fn test_timer_schedule() {
+    let (machine, mut block, process) = setup();
+
+    block.code.instructions = vec![
+        new_instruction(InstructionType::TimerSchedule, vec![4, 0, 1, 2, 3]),
+        new_instruction(InstructionType::Return, vec![4]),
+    ];
+
+    let receiver = machine.allocate_process(0, &block).unwrap();
+
+    process.set_register(0, ObjectPointer::integer(receiver.pid as i64));
+    process.set_register(1, ObjectPointer::integer(10));
+    process.set_register(2, ObjectPointer::integer(60_000));
+    process.set_register(3, machine.state.nil_object);
+
+    machine.run(&process).unwrap();
+
+    assert!(process.get_register(4).integer_value().is_ok());
+    assert_eq!(receiver.local_data().mailbox.timers.len(), 1);
+    assert!(!receiver.has_messages());
+}
+
+#[test]
+fn test_timer_schedule_with_child_process() {
+    let (machine, mut block, process) = setup();
+
+    block.code.instructions = vec![
+        new_instruction(InstructionType::TimerSchedule, vec![4, 0, 1, 2, 3]),
+        new_instruction(InstructionType::Return, vec![4]),
+    ];
+
+    let receiver = machine.allocate_process(0, &block).unwrap();
+    let child = Command::new("true").spawn().unwrap();
+    let message =
+        process.allocate_without_prototype(object_value::child_process(child));
+
+    process.set_register(0, ObjectPointer::integer(receiver.pid as i64));
+    process.set_register(1, message);
+    process.set_register(2, ObjectPointer::integer(60_000));
+    process.set_register(3, machine.state.nil_object);
+
+    assert!(machine.run(&process).is_err());
+    assert!(receiver.local_data().mailbox.timers.is_empty());
+}
+
+#[test]
+fn test_timer_schedule_without_process() {
+    let (machine, mut block, process) = setup();
+
+    block.code.instructions = vec![
+        new_instruction(InstructionType::TimerSchedule, vec![4, 0, 1, 2, 3]),
+        new_instruction(InstructionType::Return, vec![4]),
+    ];
+
+    process.set_register(0, ObjectPointer::integer(5));
+    process.set_register(1, ObjectPointer::integer(10));
+    process.set_register(2, ObjectPointer::integer(10));
+    process.set_register(3, ObjectPointer::integer(10));
+
+    machine.run(&process).unwrap();
+
+    assert!(process.get_register(4) == machine.state.nil_object);
+}
+
+#[test]
+fn test_timer_cancel() {
+    let (machine, mut block, process) = setup();
+
+    block.code.instructions = vec![
+        new_instruction(InstructionType::TimerSchedule, vec![4, 0, 1, 2, 3]),
+        new_instruction(InstructionType::TimerCancel, vec![5, 4]),
+        new_instruction(InstructionType::TimerCancel, vec![6, 4]),
+        new_instruction(InstructionType::Return, vec![6]),
+    ];
+
+    process.set_register(0, ObjectPointer::integer(0));
+    process.set_register(1, ObjectPointer::integer(10));
+    process.set_register(2, ObjectPointer::integer(60_000));
+    process.set_register(3, ObjectPointer::integer(60_000));
+
+    machine.run(&process).unwrap();
+
+    assert!(process.get_register(5) == machine.state.true_object);
+    assert!(process.get_register(6) == machine.state.false_object);
+    assert!(process.local_data().mailbox.timers.is_empty());
+}
+
+#[test]
+fn test_timer_cancel_with_nil() {
+    let (machine, mut block, process) = setup();
+
+    block.code.instructions = vec![
+        new_instruction(InstructionType::TimerCancel, vec![1, 0]),
+        new_instruction(InstructionType::Return, vec![1]),
+    ];
+
+    process.set_register(0, machine.state.nil_object);
+
+    machine.run(&process).unwrap();
+
+    assert!(process.get_register(1) == machine.state.false_object);
+}
+
+#[test]
+fn test_timer_cancel_from_another_process() {
+    let (machine, mut block, process) = setup();
+
+    block.code.instructions = vec![
+        new_instruction(InstructionType::TimerCancel, vec![1, 0]),
+        new_instruction(InstructionType::Return, vec![1]),
+    ];
+
+    let receiver = machine.allocate_process(0, &block).unwrap();
+    let id = machine
+        .state
+        .suspension_list
+        .schedule_timer(
+            receiver.pid,
+            receiver.clone(),
+            ObjectPointer::integer(10),
+            60_000,
+            None,
+        )
+        .unwrap();
+
+    process.set_register(0, ObjectPointer::integer(id as i64));
+
+    machine.run(&process).unwrap();
+
+    assert!(process.get_register(1) == machine.state.false_object);
+    assert_eq!(receiver.local_data().mailbox.timers.len(), 1);
+}
+
+#[test]
+fn test_timers_removed_when_process_finishes() {
+    let (machine, mut block, _process) = setup();
+
+    block.code.instructions = vec![
+        new_instruction(InstructionType::ProcessCurrentPid, vec![0]),
+        new_instruction(InstructionType::TimerSchedule, vec![4, 0, 1, 2, 3]),
+        new_instruction(InstructionType::Return, vec![4]),
+    ];
+
+    let process = machine.allocate_process(0, &block).unwrap();
+
+    process.set_register(1, ObjectPointer::integer(10));
+    process.set_register(2, ObjectPointer::integer(60_000));
+    process.set_register(3, machine.state.nil_object);
+
+    machine.run(&process).unwrap();
+
+    let id = process.get_register(4).integer_value().unwrap() as u64;
+
+    assert!(process.is_finished());
+    assert!(process.local_data().mailbox.timers.is_empty());
+    assert!(!process.fire_timer(id, false));
+}
@@ -1,4 +1,5 @@
 /// Integration tests for the virtual machine.
+extern crate libc;
 extern crate libinko;
 
 mod instructions;